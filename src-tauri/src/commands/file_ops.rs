@@ -138,14 +138,17 @@ pub fn read_directory_sync(path: &str) -> Result<Vec<FileEntry>> {
         })
         .collect();
 
-    // Sort: directories first, then alphabetically (case-insensitive)
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// Sorts a listing: directories first, then alphabetically (case-insensitive).
+pub(crate) fn sort_entries(entries: &mut [FileEntry]) {
     entries.sort_unstable_by(|a, b| match (a.is_dir, b.is_dir) {
         (true, false) => std::cmp::Ordering::Less,
         (false, true) => std::cmp::Ordering::Greater,
         _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
     });
-
-    Ok(entries)
 }
 
 /// Reads a directory in batches, invoking `on_batch` for each chunk.
@@ -0,0 +1,334 @@
+//! Full-text content index over the files of indexed roots.
+//!
+//! Plain text files and the text of office documents are stored in a
+//! tantivy index next to the metadata database. Each document records the
+//! file's mtime and size so unchanged files are skipped on rescans.
+
+use std::fs;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, RegexQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, FAST, STORED, STRING, TEXT};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{
+    doc, DocSet, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument, Term,
+    TERMINATED,
+};
+
+use crate::commands::search::office_text;
+use crate::constants::{
+    CONTENT_INDEX_COMMIT_INTERVAL, CONTENT_INDEX_WRITER_HEAP, CONTENT_SNIFF_LENGTH,
+    CONTENT_SNIPPET_MAX_CHARS, DEFAULT_MAX_SEARCH_RESULTS, MAX_SEARCH_FILE_SIZE,
+};
+use crate::error::{FileManagerError, Result};
+use crate::models::{ContentHit, ContentQuery, TextRange};
+use crate::utils::get_filename;
+
+/// Characters with special meaning in tantivy's regex syntax.
+const REGEX_META: &[char] = &[
+    '\\', '.', '+', '*', '?', '(', ')', '|', '[', ']', '{', '}', '^', '$', '#', '&', '-', '~',
+];
+
+/// A file as last seen on disk.
+#[derive(Debug, Clone)]
+pub(super) struct FileVersion {
+    pub path: PathBuf,
+    pub modified: i64,
+    pub size: u64,
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    path: Field,
+    name: Field,
+    body: Field,
+    modified: Field,
+    size: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        path: builder.add_text_field("path", STRING | STORED),
+        name: builder.add_text_field("name", TEXT | STORED),
+        body: builder.add_text_field("body", TEXT | STORED),
+        modified: builder.add_i64_field("modified", FAST),
+        size: builder.add_u64_field("size", FAST),
+    };
+    (builder.build(), fields)
+}
+
+pub(super) struct ContentIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+}
+
+impl ContentIndex {
+    /// Opens the index in `dir`, recreating it if it is unreadable or has an old schema.
+    pub(super) fn open(dir: &Path) -> Result<Self> {
+        let (schema, fields) = schema();
+        fs::create_dir_all(dir)?;
+
+        let open = || -> Result<Index> {
+            let directory = MmapDirectory::open(dir)
+                .map_err(|e| FileManagerError::IndexError(e.to_string()))?;
+            Ok(Index::open_or_create(directory, schema.clone())?)
+        };
+
+        let index = match open() {
+            Ok(index) => index,
+            Err(_) => {
+                fs::remove_dir_all(dir)?;
+                fs::create_dir_all(dir)?;
+                open()?
+            }
+        };
+        Self::with_index(index, fields)
+    }
+
+    pub(super) fn in_memory() -> Result<Self> {
+        let (schema, fields) = schema();
+        Self::with_index(Index::create_in_ram(schema), fields)
+    }
+
+    fn with_index(index: Index, fields: Fields) -> Result<Self> {
+        let writer = index.writer(CONTENT_INDEX_WRITER_HEAP)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+
+        Ok(Self {
+            index,
+            reader,
+            writer: Mutex::new(writer),
+            fields,
+        })
+    }
+
+    fn writer(&self) -> Result<MutexGuard<'_, IndexWriter>> {
+        self.writer
+            .lock()
+            .map_err(|e| FileManagerError::IndexError(e.to_string()))
+    }
+
+    /// Makes pending changes durable and visible to searches.
+    pub(super) fn commit(&self) -> Result<()> {
+        self.writer()?.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    pub(super) fn num_docs(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    /// Re-extracts files whose mtime or size changed since they were indexed.
+    ///
+    /// Commits every `CONTENT_INDEX_COMMIT_INTERVAL` files and at the end.
+    pub(super) fn sync_files(&self, files: &[FileVersion], cancelled: &AtomicBool) -> Result<()> {
+        let searcher = self.reader.searcher();
+        let mut written = 0;
+
+        for file in files {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(FileManagerError::Cancelled);
+            }
+
+            let path = file.path.to_string_lossy();
+            if self.indexed_version(&searcher, &path) == Some((file.modified, file.size)) {
+                continue;
+            }
+
+            // Files without text are recorded too, so they aren't re-read on every scan.
+            let body = extract_text(&file.path, file.size).unwrap_or_default();
+            {
+                let writer = self.writer()?;
+                writer.delete_term(Term::from_field_text(self.fields.path, &path));
+                writer.add_document(doc!(
+                    self.fields.path => path.as_ref(),
+                    self.fields.name => get_filename(&file.path),
+                    self.fields.body => body,
+                    self.fields.modified => file.modified,
+                    self.fields.size => file.size,
+                ))?;
+            }
+
+            written += 1;
+            if written % CONTENT_INDEX_COMMIT_INTERVAL == 0 {
+                self.commit()?;
+            }
+        }
+
+        if written > 0 {
+            self.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the `(modified, size)` a path was indexed with, if any.
+    fn indexed_version(&self, searcher: &Searcher, path: &str) -> Option<(i64, u64)> {
+        let term = Term::from_field_text(self.fields.path, path);
+
+        for segment in searcher.segment_readers() {
+            let inverted = segment.inverted_index(self.fields.path).ok()?;
+            let Some(mut postings) = inverted
+                .read_postings(&term, IndexRecordOption::Basic)
+                .ok()?
+            else {
+                continue;
+            };
+
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if !segment.is_deleted(doc) {
+                    let fast = segment.fast_fields();
+                    let modified = fast.i64("modified").ok()?.first(doc)?;
+                    let size = fast.u64("size").ok()?.first(doc)?;
+                    return Some((modified, size));
+                }
+                doc = postings.advance();
+            }
+        }
+        None
+    }
+
+    /// Removes the given files.
+    pub(super) fn remove_paths(&self, paths: &[String]) -> Result<()> {
+        let writer = self.writer()?;
+        for path in paths {
+            writer.delete_term(Term::from_field_text(self.fields.path, path));
+        }
+        Ok(())
+    }
+
+    /// Removes `path` and every file below it.
+    pub(super) fn remove_tree(&self, path: &str) -> Result<()> {
+        let writer = self.writer()?;
+        writer.delete_term(Term::from_field_text(self.fields.path, path));
+        writer.delete_query(Box::new(RegexQuery::from_pattern(
+            &descendants_regex(path),
+            self.fields.path,
+        )?))?;
+        Ok(())
+    }
+
+    /// Ranked full-text search with snippets from the matching text.
+    pub(super) fn search(&self, query: &ContentQuery) -> Result<Vec<ContentHit>> {
+        let text = query.query.trim();
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+        let limit = query
+            .max_results
+            .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS as u32) as usize;
+
+        let mut parser =
+            QueryParser::for_index(&self.index, vec![self.fields.name, self.fields.body]);
+        parser.set_conjunction_by_default();
+        parser.set_field_boost(self.fields.name, 2.0);
+        let (parsed, _errors) = parser.parse_query_lenient(text);
+
+        let scoped: Box<dyn Query> = match query.roots {
+            Some(ref roots) => {
+                let scopes = roots
+                    .iter()
+                    .map(|root| {
+                        let root: PathBuf = Path::new(root.trim()).components().collect();
+                        let pattern = descendants_regex(&root.to_string_lossy());
+                        let scope: Box<dyn Query> =
+                            Box::new(RegexQuery::from_pattern(&pattern, self.fields.path)?);
+                        Ok((Occur::Should, scope))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Box::new(BooleanQuery::new(vec![
+                    (Occur::Must, parsed),
+                    (Occur::Must, Box::new(BooleanQuery::new(scopes))),
+                ]))
+            }
+            None => parsed,
+        };
+
+        let searcher = self.reader.searcher();
+        let top = searcher.search(&*scoped, &TopDocs::with_limit(limit))?;
+        let mut snippets = SnippetGenerator::create(&searcher, &*scoped, self.fields.body)?;
+        snippets.set_max_num_chars(CONTENT_SNIPPET_MAX_CHARS);
+
+        top.into_iter()
+            .map(|(score, address)| {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                let text_of = |field| {
+                    doc.get_first(field)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let snippet = snippets.snippet_from_doc(&doc);
+                let fragment = snippet.fragment();
+
+                Ok(ContentHit {
+                    path: text_of(self.fields.path),
+                    name: text_of(self.fields.name),
+                    score,
+                    snippet: fragment.to_string(),
+                    highlights: snippet
+                        .highlighted()
+                        .iter()
+                        .map(|range| TextRange {
+                            start: utf16_len(&fragment[..range.start]),
+                            end: utf16_len(&fragment[..range.end]),
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+fn utf16_len(text: &str) -> u32 {
+    u32::try_from(text.encode_utf16().count()).unwrap_or(u32::MAX)
+}
+
+/// Regex matching every path strictly below `path`.
+fn descendants_regex(path: &str) -> String {
+    let mut pattern = String::with_capacity(path.len() + 8);
+    for ch in path.chars() {
+        if REGEX_META.contains(&ch) {
+            pattern.push('\\');
+        }
+        pattern.push(ch);
+    }
+    if !path.ends_with(MAIN_SEPARATOR) {
+        if REGEX_META.contains(&MAIN_SEPARATOR) {
+            pattern.push('\\');
+        }
+        pattern.push(MAIN_SEPARATOR);
+    }
+    pattern.push_str(".*");
+    pattern
+}
+
+/// Extracts indexable text: the full text of office documents as content
+/// search reads it, otherwise any file up to `MAX_SEARCH_FILE_SIZE` that
+/// doesn't look binary.
+fn extract_text(path: &Path, size: u64) -> Option<String> {
+    if let Some(text) = office_text(path, size) {
+        return Some(text);
+    }
+
+    if size > MAX_SEARCH_FILE_SIZE {
+        return None;
+    }
+
+    let bytes = fs::read(path).ok()?;
+    if bytes[..bytes.len().min(CONTENT_SNIFF_LENGTH)].contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
@@ -0,0 +1,733 @@
+//! Opt-in persistent file index for instant name and metadata search.
+//!
+//! Roots chosen by the user are scanned in the background into an SQLite
+//! database, kept fresh by recursive watchers and reconciled with a full
+//! rescan every `INDEX_RECONCILE_INTERVAL_SECS`. File contents go into a
+//! separate full-text index (see [`content`]).
+
+mod content;
+mod store;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use ignore::WalkBuilder;
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{async_runtime::spawn_blocking, AppHandle, Manager};
+
+use crate::commands::search::filters::EntryFilter;
+use crate::constants::{
+    DEFAULT_MAX_SEARCH_RESULTS, INDEX_BATCH_SIZE, INDEX_RECONCILE_INTERVAL_SECS,
+};
+use crate::error::{FileManagerError, Result};
+use crate::models::{
+    ContentHit, ContentQuery, FileEntry, IndexQuery, IndexRootState, IndexRootStatus, IndexStatus,
+    SearchFilters,
+};
+use crate::utils::{system_time_to_timestamp, validate_absolute_path};
+
+use content::{ContentIndex, FileVersion};
+use store::{IndexRecord, Store, StoredRoot};
+
+enum Job {
+    Scan { root: String, rebuild: bool },
+    Changed(Vec<PathBuf>),
+    Shutdown,
+}
+
+/// Job queue handle that tracks how many jobs are still outstanding.
+#[derive(Clone)]
+struct JobSender {
+    tx: Sender<Job>,
+    pending: Arc<AtomicUsize>,
+}
+
+impl JobSender {
+    fn send(&self, job: Job) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        if self.tx.send(job).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    fn done(&self) {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct RootRuntime {
+    id: i64,
+    state: IndexRootState,
+    watcher: Option<RecommendedWatcher>,
+    /// Raised when the root is removed so a running scan stops early.
+    cancelled: Arc<AtomicBool>,
+}
+
+struct Shared {
+    store: Mutex<Store>,
+    roots: Mutex<HashMap<String, RootRuntime>>,
+    content: ContentIndex,
+    database_path: Option<PathBuf>,
+}
+
+impl Shared {
+    fn store(&self) -> Result<MutexGuard<'_, Store>> {
+        self.store
+            .lock()
+            .map_err(|e| FileManagerError::IndexError(e.to_string()))
+    }
+
+    fn roots(&self) -> Result<MutexGuard<'_, HashMap<String, RootRuntime>>> {
+        self.roots
+            .lock()
+            .map_err(|e| FileManagerError::IndexError(e.to_string()))
+    }
+
+    fn set_state(&self, root: &str, state: IndexRootState) {
+        if let Ok(mut roots) = self.roots.lock() {
+            if let Some(runtime) = roots.get_mut(root) {
+                runtime.state = state;
+            }
+        }
+    }
+
+    /// Finds the indexed root containing `path`.
+    fn root_for(&self, path: &Path) -> Option<(String, i64)> {
+        let roots = self.roots.lock().ok()?;
+        roots
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.len())
+            .map(|(root, runtime)| (root.clone(), runtime.id))
+    }
+
+    /// True for the database, its WAL/SHM files and the content index, whose
+    /// writes must not feed back into the index when they live inside an indexed root.
+    fn is_own_file(&self, path: &Path) -> bool {
+        let Some(ref db) = self.database_path else {
+            return false;
+        };
+        if path.starts_with(content_dir(db)) {
+            return true;
+        }
+        let (Some(db_name), Some(name)) = (db.file_name(), path.file_name()) else {
+            return false;
+        };
+        path.parent() == db.parent()
+            && name
+                .to_string_lossy()
+                .starts_with(&*db_name.to_string_lossy())
+    }
+
+    fn status(&self, root: &StoredRoot) -> Result<IndexRootStatus> {
+        let entries = self.store()?.count(Some(root.id))?;
+        let roots = self.roots()?;
+        let runtime = roots.get(&root.path);
+
+        Ok(IndexRootStatus {
+            path: root.path.clone(),
+            state: runtime
+                .map(|r| r.state.clone())
+                .unwrap_or(IndexRootState::Pending),
+            entries,
+            last_scan: root.last_scan,
+            watching: runtime.is_some_and(|r| r.watcher.is_some()),
+        })
+    }
+}
+
+/// Global state for the persistent file index.
+///
+/// Owns the database and a background worker thread that runs scans and
+/// applies watcher events.
+pub struct IndexState {
+    shared: Arc<Shared>,
+    jobs: JobSender,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl IndexState {
+    /// Opens (or creates) the index database at `path` and resumes indexing its roots.
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::start(
+            Store::open(path)?,
+            ContentIndex::open(&content_dir(path))?,
+            Some(path.to_path_buf()),
+        )
+    }
+
+    /// Creates an index that is not persisted across restarts.
+    pub fn in_memory() -> Result<Self> {
+        Self::start(Store::open_in_memory()?, ContentIndex::in_memory()?, None)
+    }
+
+    fn start(store: Store, content: ContentIndex, database_path: Option<PathBuf>) -> Result<Self> {
+        let stored_roots = store.roots()?;
+        let (tx, rx) = channel();
+        let jobs = JobSender {
+            tx,
+            pending: Arc::new(AtomicUsize::new(0)),
+        };
+        let shared = Arc::new(Shared {
+            store: Mutex::new(store),
+            roots: Mutex::new(HashMap::new()),
+            content,
+            database_path,
+        });
+
+        let worker = {
+            let shared = shared.clone();
+            let jobs = jobs.clone();
+            thread::Builder::new()
+                .name("file-index".to_string())
+                .spawn(move || run_worker(&shared, rx, &jobs))
+                .map_err(|e| FileManagerError::IndexError(e.to_string()))?
+        };
+
+        let state = Self {
+            shared,
+            jobs,
+            worker: Some(worker),
+        };
+        for root in stored_roots {
+            state.activate_root(&root)?;
+        }
+        Ok(state)
+    }
+
+    /// Starts watching a stored root and queues a reconciliation scan.
+    fn activate_root(&self, root: &StoredRoot) -> Result<()> {
+        let (watcher, state) = match start_watcher(&root.path, &self.jobs) {
+            Ok(watcher) => (Some(watcher), IndexRootState::Pending),
+            Err(e) => (
+                None,
+                IndexRootState::Error {
+                    message: e.to_string(),
+                },
+            ),
+        };
+
+        self.shared.roots()?.insert(
+            root.path.clone(),
+            RootRuntime {
+                id: root.id,
+                state,
+                watcher,
+                cancelled: Arc::new(AtomicBool::new(false)),
+            },
+        );
+        self.jobs.send(Job::Scan {
+            root: root.path.clone(),
+            rebuild: false,
+        });
+        Ok(())
+    }
+
+    /// Recently changed files under `root`, or `None` unless a fully scanned
+    /// indexed root covers it.
+    pub(crate) fn recent_files(
+        &self,
+        root: &str,
+        since: i64,
+        limit: usize,
+        accept: impl FnMut(&FileEntry) -> bool,
+    ) -> Result<Option<Vec<FileEntry>>> {
+        let ready = self.shared.roots()?.iter().any(|(indexed, runtime)| {
+            Path::new(root).starts_with(indexed) && runtime.state == IndexRootState::Ready
+        });
+        if !ready {
+            return Ok(None);
+        }
+        self.shared
+            .store()?
+            .recent(root, since, limit, accept)
+            .map(Some)
+    }
+
+    /// Blocks until queued scans and watcher updates are applied (integration tests).
+    #[doc(hidden)]
+    pub fn wait_until_idle(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.jobs.pending.load(Ordering::SeqCst) > 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        true
+    }
+}
+
+impl Drop for IndexState {
+    fn drop(&mut self) {
+        if let Ok(roots) = self.shared.roots() {
+            for runtime in roots.values() {
+                runtime.cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+        self.jobs.send(Job::Shutdown);
+        // The worker shares the full-text writer, whose directory lock must be
+        // released before the index can be opened again.
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Directory of the full-text index that belongs to the database at `db`.
+fn content_dir(db: &Path) -> PathBuf {
+    db.with_extension("content")
+}
+
+fn start_watcher(root: &str, jobs: &JobSender) -> Result<RecommendedWatcher> {
+    let jobs = jobs.clone();
+    let root_key = root.to_string();
+
+    let mut watcher = recommended_watcher(move |res: notify::Result<Event>| {
+        let Ok(event) = res else {
+            return;
+        };
+        if event.need_rescan() {
+            // The OS dropped events; only a full scan can catch up.
+            jobs.send(Job::Scan {
+                root: root_key.clone(),
+                rebuild: false,
+            });
+        } else if !matches!(event.kind, EventKind::Access(_)) && !event.paths.is_empty() {
+            jobs.send(Job::Changed(event.paths));
+        }
+    })
+    .map_err(|e| FileManagerError::WatchError(e.to_string()))?;
+
+    watcher
+        .watch(Path::new(root), RecursiveMode::Recursive)
+        .map_err(|e| FileManagerError::WatchError(e.to_string()))?;
+
+    Ok(watcher)
+}
+
+fn run_worker(shared: &Shared, rx: Receiver<Job>, jobs: &JobSender) {
+    let interval = Duration::from_secs(INDEX_RECONCILE_INTERVAL_SECS);
+    let mut next_reconcile = Instant::now() + interval;
+
+    loop {
+        match rx.recv_timeout(next_reconcile.saturating_duration_since(Instant::now())) {
+            Ok(Job::Scan { root, rebuild }) => {
+                scan_root(shared, &root, rebuild);
+                jobs.done();
+            }
+            Ok(Job::Changed(paths)) => {
+                apply_changes(shared, paths);
+                jobs.done();
+            }
+            Ok(Job::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                let roots: Vec<String> = shared
+                    .roots
+                    .lock()
+                    .map(|roots| roots.keys().cloned().collect())
+                    .unwrap_or_default();
+                for root in roots {
+                    jobs.send(Job::Scan {
+                        root,
+                        rebuild: false,
+                    });
+                }
+                next_reconcile = Instant::now() + interval;
+            }
+        }
+    }
+
+    // Dropping the watchers releases their job senders.
+    if let Ok(mut roots) = shared.roots.lock() {
+        roots.clear();
+    }
+}
+
+/// Walks `dir` (excluding itself) and writes its entries with `generation`.
+///
+/// Returns the regular files seen, for the content index.
+fn index_tree(
+    shared: &Shared,
+    root_id: i64,
+    generation: i64,
+    dir: &Path,
+    cancelled: &AtomicBool,
+    mut on_batch: impl FnMut(u64),
+) -> Result<Vec<FileVersion>> {
+    let mut batch = Vec::with_capacity(INDEX_BATCH_SIZE);
+    let mut files = Vec::new();
+    let mut scanned: u64 = 0;
+
+    let walker = WalkBuilder::new(dir)
+        .standard_filters(false)
+        .follow_links(false)
+        .build();
+
+    for entry in walker {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(FileManagerError::Cancelled);
+        }
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.depth() == 0 || shared.is_own_file(entry.path()) {
+            continue;
+        }
+        let Ok(meta) = entry.path().symlink_metadata() else {
+            continue;
+        };
+
+        let record = IndexRecord::new(entry.path(), &meta);
+        files.extend(record.file_version());
+        batch.push(record);
+        scanned += 1;
+
+        if batch.len() >= INDEX_BATCH_SIZE {
+            shared.store()?.upsert(root_id, generation, &batch)?;
+            batch.clear();
+            on_batch(scanned);
+        }
+    }
+
+    if !batch.is_empty() {
+        shared.store()?.upsert(root_id, generation, &batch)?;
+    }
+    Ok(files)
+}
+
+/// Full scan of a root; entries not seen during the scan are dropped afterwards.
+fn scan_root(shared: &Shared, root: &str, rebuild: bool) {
+    let Some((root_id, cancelled)) = shared.roots.lock().ok().and_then(|roots| {
+        roots
+            .get(root)
+            .map(|runtime| (runtime.id, runtime.cancelled.clone()))
+    }) else {
+        return;
+    };
+
+    shared.set_state(root, IndexRootState::Scanning { scanned: 0 });
+
+    let result = (|| -> Result<()> {
+        if !Path::new(root).is_dir() {
+            return Err(FileManagerError::DirectoryNotFound(root.to_string()));
+        }
+
+        if rebuild {
+            shared.content.remove_tree(root)?;
+        }
+        let generation = shared.store()?.begin_scan(root_id, rebuild)?;
+        let files = index_tree(
+            shared,
+            root_id,
+            generation,
+            Path::new(root),
+            &cancelled,
+            |scanned| shared.set_state(root, IndexRootState::Scanning { scanned }),
+        )?;
+
+        let now = system_time_to_timestamp(SystemTime::now()).unwrap_or(0);
+        let stale = shared.store()?.finish_scan(root_id, generation, now)?;
+        shared.content.remove_paths(&stale)?;
+        shared.content.commit()?;
+
+        // Names are searchable now; contents follow while the root reports ready.
+        shared.set_state(root, IndexRootState::Ready);
+        shared.content.sync_files(&files, &cancelled)
+    })();
+
+    match result {
+        Ok(()) | Err(FileManagerError::Cancelled) => {}
+        Err(e) => shared.set_state(
+            root,
+            IndexRootState::Error {
+                message: e.to_string(),
+            },
+        ),
+    }
+}
+
+/// Applies watcher-reported paths: existing paths are (re)indexed, missing ones removed.
+fn apply_changes(shared: &Shared, paths: Vec<PathBuf>) {
+    let unique: HashSet<PathBuf> = paths.into_iter().collect();
+    let mut changed_files = Vec::new();
+    let mut removed = Vec::new();
+
+    for path in unique {
+        if shared.is_own_file(&path) {
+            continue;
+        }
+        let Some((root, root_id)) = shared.root_for(&path) else {
+            continue;
+        };
+        // Changes to the root itself are left to reconciliation.
+        if path == Path::new(&root) {
+            continue;
+        }
+
+        let _ = (|| -> Result<()> {
+            let path_str = path.to_string_lossy();
+
+            match fs::symlink_metadata(&path) {
+                Ok(meta) => {
+                    let (generation, known) = {
+                        let store = shared.store()?;
+                        (store.generation(root_id)?, store.contains(&path_str)?)
+                    };
+                    let record = IndexRecord::new(&path, &meta);
+                    changed_files.extend(record.file_version());
+                    shared.store()?.upsert(root_id, generation, &[record])?;
+
+                    // A directory we haven't seen was created or moved in with its contents.
+                    if meta.is_dir() && !known {
+                        changed_files.extend(index_tree(
+                            shared,
+                            root_id,
+                            generation,
+                            &path,
+                            &AtomicBool::new(false),
+                            |_| {},
+                        )?);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    shared.store()?.delete_tree(&path_str)?;
+                    removed.push(path_str.to_string());
+                }
+                Err(_) => {}
+            }
+            Ok(())
+        })();
+    }
+
+    let _ = (|| -> Result<()> {
+        for path in &removed {
+            shared.content.remove_tree(path)?;
+        }
+        shared.content.commit()?;
+        shared
+            .content
+            .sync_files(&changed_files, &AtomicBool::new(false))
+    })();
+}
+
+/// Normalizes a root path so the same directory always maps to one key.
+fn normalize_root(path: &str) -> Result<String> {
+    validate_absolute_path(path)?;
+
+    let normalized: PathBuf = Path::new(path.trim()).components().collect();
+    if !normalized.exists() {
+        return Err(FileManagerError::DirectoryNotFound(path.to_string()));
+    }
+    if !normalized.is_dir() {
+        return Err(FileManagerError::NotADirectory(path.to_string()));
+    }
+    Ok(normalized.to_string_lossy().to_string())
+}
+
+#[doc(hidden)]
+pub fn add_index_root_sync(path: &str, state: &IndexState) -> Result<IndexRootStatus> {
+    let root = normalize_root(path)?;
+    let shared = &state.shared;
+
+    let existing = shared.store()?.roots()?;
+    if let Some(stored) = existing.iter().find(|r| r.path == root) {
+        return shared.status(stored);
+    }
+    if let Some(overlap) = existing
+        .iter()
+        .find(|r| Path::new(&root).starts_with(&r.path) || Path::new(&r.path).starts_with(&root))
+    {
+        return Err(FileManagerError::InvalidPath(format!(
+            "{root} overlaps indexed root {}",
+            overlap.path
+        )));
+    }
+
+    let stored = shared.store()?.add_root(&root)?;
+    state.activate_root(&stored)?;
+    shared.status(&stored)
+}
+
+/// Adds a directory to the index and starts scanning it in the background.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_index_root(
+    path: String,
+    app: AppHandle,
+) -> std::result::Result<IndexRootStatus, String> {
+    spawn_blocking(move || add_index_root_sync(&path, &app.state::<IndexState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn remove_index_root_sync(path: &str, state: &IndexState) -> Result<bool> {
+    let root: PathBuf = Path::new(path.trim()).components().collect();
+    let root = root.to_string_lossy();
+    let shared = &state.shared;
+
+    if let Some(runtime) = shared.roots()?.remove(&*root) {
+        runtime.cancelled.store(true, Ordering::Relaxed);
+    }
+    shared.content.remove_tree(&root)?;
+    shared.content.commit()?;
+    shared.store()?.remove_root(&root)
+}
+
+/// Removes a directory and its entries from the index. Returns `false` if it wasn't indexed.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_index_root(path: String, app: AppHandle) -> std::result::Result<bool, String> {
+    spawn_blocking(move || remove_index_root_sync(&path, &app.state::<IndexState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn get_index_status_sync(state: &IndexState) -> Result<IndexStatus> {
+    let shared = &state.shared;
+    let stored = shared.store()?.roots()?;
+
+    Ok(IndexStatus {
+        roots: stored
+            .iter()
+            .map(|root| shared.status(root))
+            .collect::<Result<_>>()?,
+        total_entries: shared.store()?.count(None)?,
+        content_documents: shared.content.num_docs(),
+        database_path: shared
+            .database_path
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+/// Returns the state of every indexed root.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_index_status(app: AppHandle) -> std::result::Result<IndexStatus, String> {
+    spawn_blocking(move || get_index_status_sync(&app.state::<IndexState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn rebuild_index_sync(path: Option<&str>, state: &IndexState) -> Result<()> {
+    let roots: Vec<String> = {
+        let roots = state.shared.roots()?;
+        match path {
+            Some(path) => {
+                let root: PathBuf = Path::new(path.trim()).components().collect();
+                let root = root.to_string_lossy().to_string();
+                if !roots.contains_key(&root) {
+                    return Err(FileManagerError::InvalidPath(format!(
+                        "Not an indexed root: {path}"
+                    )));
+                }
+                vec![root]
+            }
+            None => roots.keys().cloned().collect(),
+        }
+    };
+
+    for root in roots {
+        state.jobs.send(Job::Scan {
+            root,
+            rebuild: true,
+        });
+    }
+    Ok(())
+}
+
+/// Discards and rescans one indexed root, or all of them when `path` is omitted.
+#[tauri::command]
+#[specta::specta]
+pub async fn rebuild_index(
+    path: Option<String>,
+    app: AppHandle,
+) -> std::result::Result<(), String> {
+    rebuild_index_sync(path.as_deref(), &app.state::<IndexState>()).map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn search_index_sync(query: &IndexQuery, state: &IndexState) -> Result<Vec<FileEntry>> {
+    let limit = query
+        .max_results
+        .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS as u32) as usize;
+
+    let mut query = query.clone();
+    query.roots = query.roots.map(|roots| {
+        roots
+            .iter()
+            .map(|root| {
+                let root: PathBuf = Path::new(root.trim()).components().collect();
+                root.to_string_lossy().to_string()
+            })
+            .collect()
+    });
+
+    // Predicates the index doesn't store are checked against the filesystem.
+    let disk_filter = match query.filters {
+        Some(ref f) if f.empty.is_some() || f.executable.is_some() || f.owner.is_some() => {
+            Some(EntryFilter::compile(&SearchFilters {
+                empty: f.empty,
+                executable: f.executable,
+                owner: f.owner.clone(),
+                ..Default::default()
+            })?)
+        }
+        _ => None,
+    };
+
+    state.shared.store()?.search(&query, limit, |entry| {
+        disk_filter.as_ref().is_none_or(|filter| {
+            let path = Path::new(&entry.path);
+            fs::symlink_metadata(path).is_ok_and(|meta| filter.matches_metadata(path, &meta))
+        })
+    })
+}
+
+/// Locate-style search over indexed roots by name and metadata.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_index(
+    query: IndexQuery,
+    app: AppHandle,
+) -> std::result::Result<Vec<FileEntry>, String> {
+    spawn_blocking(move || search_index_sync(&query, &app.state::<IndexState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn search_content_index_sync(
+    query: &ContentQuery,
+    state: &IndexState,
+) -> Result<Vec<ContentHit>> {
+    state.shared.content.search(query)
+}
+
+/// Ranked full-text search over the contents of indexed files.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_content_index(
+    query: ContentQuery,
+    app: AppHandle,
+) -> std::result::Result<Vec<ContentHit>, String> {
+    spawn_blocking(move || search_content_index_sync(&query, &app.state::<IndexState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
@@ -0,0 +1,512 @@
+//! SQLite storage for the file index.
+
+use std::fs::Metadata;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension};
+
+use crate::error::Result;
+use crate::models::{EntryKind, FileEntry, IndexQuery};
+use crate::utils::{get_extension, get_filename, is_hidden, system_time_to_timestamp};
+
+use crate::commands::search::filters::resolve_time_range;
+
+use super::content::FileVersion;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS roots (
+    id          INTEGER PRIMARY KEY,
+    path        TEXT NOT NULL UNIQUE,
+    generation  INTEGER NOT NULL DEFAULT 0,
+    last_scan   INTEGER
+);
+CREATE TABLE IF NOT EXISTS entries (
+    path        TEXT PRIMARY KEY,
+    root_id     INTEGER NOT NULL REFERENCES roots(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    name_lower  TEXT NOT NULL,
+    kind        INTEGER NOT NULL,
+    size        INTEGER NOT NULL,
+    modified    INTEGER,
+    created     INTEGER,
+    hidden      INTEGER NOT NULL,
+    generation  INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS entries_root ON entries(root_id, generation);
+CREATE INDEX IF NOT EXISTS entries_name ON entries(name_lower);
+CREATE INDEX IF NOT EXISTS entries_modified ON entries(modified);
+
+-- Trigram index over names for substring lookups, kept in sync by triggers.
+CREATE VIRTUAL TABLE IF NOT EXISTS entry_names USING fts5(
+    name_lower, content = 'entries', tokenize = 'trigram'
+);
+CREATE TRIGGER IF NOT EXISTS entry_names_insert AFTER INSERT ON entries BEGIN
+    INSERT INTO entry_names (rowid, name_lower) VALUES (new.rowid, new.name_lower);
+END;
+CREATE TRIGGER IF NOT EXISTS entry_names_delete AFTER DELETE ON entries BEGIN
+    INSERT INTO entry_names (entry_names, rowid, name_lower)
+        VALUES ('delete', old.rowid, old.name_lower);
+END;
+CREATE TRIGGER IF NOT EXISTS entry_names_update AFTER UPDATE OF name_lower ON entries
+WHEN old.name_lower IS NOT new.name_lower BEGIN
+    INSERT INTO entry_names (entry_names, rowid, name_lower)
+        VALUES ('delete', old.rowid, old.name_lower);
+    INSERT INTO entry_names (rowid, name_lower) VALUES (new.rowid, new.name_lower);
+END;
+";
+
+/// Shortest needle the trigram index can answer; shorter ones scan names.
+const MIN_TRIGRAM_NEEDLE: usize = 3;
+
+/// A root row.
+#[derive(Debug, Clone)]
+pub(super) struct StoredRoot {
+    pub id: i64,
+    pub path: String,
+    pub last_scan: Option<i64>,
+}
+
+/// An entry ready to be written to the index.
+#[derive(Debug, Clone)]
+pub(super) struct IndexRecord {
+    path: String,
+    name: String,
+    kind: EntryKind,
+    size: u64,
+    modified: Option<i64>,
+    created: Option<i64>,
+    hidden: bool,
+}
+
+impl IndexRecord {
+    /// Builds a record from `symlink_metadata`, so symlinks are stored as such.
+    pub(super) fn new(path: &Path, meta: &Metadata) -> Self {
+        let kind = if meta.file_type().is_symlink() {
+            EntryKind::Symlink
+        } else if meta.is_dir() {
+            EntryKind::Directory
+        } else {
+            EntryKind::File
+        };
+
+        Self {
+            path: path.to_string_lossy().to_string(),
+            name: get_filename(path),
+            kind,
+            size: if meta.is_file() { meta.len() } else { 0 },
+            modified: meta.modified().ok().and_then(system_time_to_timestamp),
+            created: meta.created().ok().and_then(system_time_to_timestamp),
+            hidden: is_hidden(path),
+        }
+    }
+
+    /// The version the content index compares against; `None` for non-files.
+    pub(super) fn file_version(&self) -> Option<FileVersion> {
+        (self.kind == EntryKind::File).then(|| FileVersion {
+            path: PathBuf::from(&self.path),
+            modified: self.modified.unwrap_or(0),
+            size: self.size,
+        })
+    }
+}
+
+fn kind_to_sql(kind: EntryKind) -> i64 {
+    match kind {
+        EntryKind::File => 0,
+        EntryKind::Directory => 1,
+        EntryKind::Symlink => 2,
+    }
+}
+
+/// Escapes `%`, `_` and `\` for a `LIKE ... ESCAPE '\'` pattern.
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Bounds of the paths strictly below `path`, for `path >= ?lower AND path < ?upper`.
+///
+/// Unlike `LIKE`, the comparison is case-sensitive and can use the primary key.
+fn descendants_range(path: &str) -> (String, String) {
+    let base = path.strip_suffix(MAIN_SEPARATOR).unwrap_or(path);
+    let lower = format!("{base}{MAIN_SEPARATOR}");
+    // The separator is ASCII, so the next character ends the range.
+    let upper = format!("{base}{}", char::from(MAIN_SEPARATOR as u8 + 1));
+    (lower, upper)
+}
+
+/// FTS5 phrase matching `needle` as a substring under the trigram tokenizer.
+fn trigram_phrase(needle: &str) -> String {
+    format!("\"{}\"", needle.replace('"', "\"\""))
+}
+
+pub(super) struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub(super) fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Self::init(conn)
+    }
+
+    pub(super) fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        let had_names = conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE name = 'entry_names'",
+                [],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        conn.execute_batch(SCHEMA)?;
+        if !had_names {
+            // Indexes created before the name table existed need it filled.
+            conn.execute(
+                "INSERT INTO entry_names (entry_names) VALUES ('rebuild')",
+                [],
+            )?;
+        }
+        Ok(Self { conn })
+    }
+
+    pub(super) fn roots(&self) -> Result<Vec<StoredRoot>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, path, last_scan FROM roots ORDER BY path")?;
+        let roots = stmt
+            .query_map([], |row| {
+                Ok(StoredRoot {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    last_scan: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(roots)
+    }
+
+    pub(super) fn root(&self, path: &str) -> Result<Option<StoredRoot>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, path, last_scan FROM roots WHERE path = ?1",
+                [path],
+                |row| {
+                    Ok(StoredRoot {
+                        id: row.get(0)?,
+                        path: row.get(1)?,
+                        last_scan: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    pub(super) fn add_root(&self, path: &str) -> Result<StoredRoot> {
+        self.conn
+            .execute("INSERT OR IGNORE INTO roots (path) VALUES (?1)", [path])?;
+        self.root(path)?
+            .ok_or_else(|| rusqlite::Error::QueryReturnedNoRows.into())
+    }
+
+    /// Removes a root and all of its entries.
+    pub(super) fn remove_root(&self, path: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM roots WHERE path = ?1", [path])?
+            > 0)
+    }
+
+    /// Starts a full scan of a root and returns its generation.
+    ///
+    /// Rows written during the scan carry the new generation; anything older is
+    /// stale once the scan finishes.
+    pub(super) fn begin_scan(&self, root_id: i64, rebuild: bool) -> Result<i64> {
+        if rebuild {
+            self.conn
+                .execute("DELETE FROM entries WHERE root_id = ?1", [root_id])?;
+        }
+        Ok(self.conn.query_row(
+            "UPDATE roots SET generation = generation + 1 WHERE id = ?1 RETURNING generation",
+            [root_id],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Drops entries not seen by the scan of `generation` and records the scan time.
+    /// Returns the paths of the dropped files.
+    pub(super) fn finish_scan(
+        &self,
+        root_id: i64,
+        generation: i64,
+        timestamp: i64,
+    ) -> Result<Vec<String>> {
+        let stale = self
+            .conn
+            .prepare(
+                "SELECT path FROM entries WHERE root_id = ?1 AND generation < ?2 AND kind = ?3",
+            )?
+            .query_map(
+                params![root_id, generation, kind_to_sql(EntryKind::File)],
+                |row| row.get(0),
+            )?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        self.conn.execute(
+            "DELETE FROM entries WHERE root_id = ?1 AND generation < ?2",
+            params![root_id, generation],
+        )?;
+        self.conn.execute(
+            "UPDATE roots SET last_scan = ?2 WHERE id = ?1",
+            params![root_id, timestamp],
+        )?;
+        Ok(stale)
+    }
+
+    pub(super) fn generation(&self, root_id: i64) -> Result<i64> {
+        Ok(self.conn.query_row(
+            "SELECT generation FROM roots WHERE id = ?1",
+            [root_id],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Inserts or updates records in one transaction.
+    pub(super) fn upsert(
+        &mut self,
+        root_id: i64,
+        generation: i64,
+        records: &[IndexRecord],
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO entries
+                    (path, root_id, name, name_lower, kind, size, modified, created, hidden, generation)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT(path) DO UPDATE SET
+                    root_id = excluded.root_id,
+                    name = excluded.name,
+                    name_lower = excluded.name_lower,
+                    kind = excluded.kind,
+                    size = excluded.size,
+                    modified = excluded.modified,
+                    created = excluded.created,
+                    hidden = excluded.hidden,
+                    generation = excluded.generation",
+            )?;
+            for record in records {
+                stmt.execute(params![
+                    record.path,
+                    root_id,
+                    record.name,
+                    record.name.to_lowercase(),
+                    kind_to_sql(record.kind),
+                    i64::try_from(record.size).unwrap_or(i64::MAX),
+                    record.modified,
+                    record.created,
+                    record.hidden,
+                    generation,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Removes `path` and everything below it.
+    pub(super) fn delete_tree(&self, path: &str) -> Result<usize> {
+        let (lower, upper) = descendants_range(path);
+        Ok(self.conn.execute(
+            "DELETE FROM entries WHERE path = ?1 OR (path >= ?2 AND path < ?3)",
+            params![path, lower, upper],
+        )?)
+    }
+
+    pub(super) fn contains(&self, path: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT 1 FROM entries WHERE path = ?1", [path], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    pub(super) fn count(&self, root_id: Option<i64>) -> Result<u64> {
+        let count: i64 = match root_id {
+            Some(id) => self.conn.query_row(
+                "SELECT COUNT(*) FROM entries WHERE root_id = ?1",
+                [id],
+                |row| row.get(0),
+            )?,
+            None => self
+                .conn
+                .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))?,
+        };
+        Ok(count as u64)
+    }
+
+    /// Runs a name/metadata query, calling `accept` on each candidate in rank order
+    /// until it has accepted `limit` entries.
+    ///
+    /// Ranking favors exact names, then name prefixes, then shorter paths.
+    /// Needles of `MIN_TRIGRAM_NEEDLE` or more characters are looked up in the
+    /// trigram index instead of scanning every name.
+    pub(super) fn search(
+        &self,
+        query: &IndexQuery,
+        limit: usize,
+        mut accept: impl FnMut(&FileEntry) -> bool,
+    ) -> Result<Vec<FileEntry>> {
+        let needle = query.query.trim().to_lowercase();
+        let mut sql = String::from(
+            "SELECT path, name, kind, size, modified, created, hidden FROM entries WHERE 1 = 1",
+        );
+        let mut args: Vec<Value> = Vec::new();
+
+        if needle.chars().count() >= MIN_TRIGRAM_NEEDLE {
+            sql.push_str(" AND rowid IN (SELECT rowid FROM entry_names WHERE entry_names MATCH ?)");
+            args.push(Value::Text(trigram_phrase(&needle)));
+        } else if !needle.is_empty() {
+            sql.push_str(" AND name_lower LIKE ? ESCAPE '\\'");
+            args.push(Value::Text(format!("%{}%", escape_like(&needle))));
+        }
+
+        if let Some(ref roots) = query.roots {
+            let clauses: Vec<&str> = roots
+                .iter()
+                .map(|_| "(path = ? OR (path >= ? AND path < ?))")
+                .collect();
+            if clauses.is_empty() {
+                return Ok(Vec::new());
+            }
+            sql.push_str(&format!(" AND ({})", clauses.join(" OR ")));
+            for root in roots {
+                let (lower, upper) = descendants_range(root);
+                args.push(Value::Text(root.clone()));
+                args.push(Value::Text(lower));
+                args.push(Value::Text(upper));
+            }
+        }
+
+        if let Some(ref filters) = query.filters {
+            let mut bound = |clause: &str, value: Option<i64>| {
+                if let Some(value) = value {
+                    sql.push_str(clause);
+                    args.push(Value::Integer(value));
+                }
+            };
+            let to_sql = |size: u64| i64::try_from(size).unwrap_or(i64::MAX);
+
+            bound(" AND size >= ?", filters.min_size.map(to_sql));
+            bound(" AND size <= ?", filters.max_size.map(to_sql));
+            if let Some(ref range) = filters.modified {
+                let (after, before) = resolve_time_range(range);
+                bound(" AND modified >= ?", after);
+                bound(" AND modified <= ?", before);
+            }
+            if let Some(ref range) = filters.created {
+                let (after, before) = resolve_time_range(range);
+                bound(" AND created >= ?", after);
+                bound(" AND created <= ?", before);
+            }
+            bound(" AND kind = ?", filters.kind.map(kind_to_sql));
+            bound(" AND hidden = ?", filters.hidden.map(i64::from));
+        }
+
+        sql.push_str(
+            " ORDER BY CASE WHEN name_lower = ? THEN 0 WHEN name_lower LIKE ? ESCAPE '\\' THEN 1 ELSE 2 END,
+               length(path), path",
+        );
+        args.push(Value::Text(needle.clone()));
+        args.push(Value::Text(format!("{}%", escape_like(&needle))));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut rows = stmt.query(params_from_iter(args))?;
+        let mut results = Vec::new();
+
+        while results.len() < limit {
+            let Some(row) = rows.next()? else {
+                break;
+            };
+
+            let entry = entry_from_row(row)?;
+            if accept(&entry) {
+                results.push(entry);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Files under `root` modified or created at or after `since`, newest first,
+    /// calling `accept` on each until it has accepted `limit` entries.
+    pub(super) fn recent(
+        &self,
+        root: &str,
+        since: i64,
+        limit: usize,
+        mut accept: impl FnMut(&FileEntry) -> bool,
+    ) -> Result<Vec<FileEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, name, kind, size, modified, created, hidden,
+                    max(coalesce(modified, 0), coalesce(created, 0)) AS changed
+             FROM entries
+             WHERE kind = ?1 AND (path = ?2 OR (path >= ?3 AND path < ?4)) AND changed >= ?5
+             ORDER BY changed DESC, path",
+        )?;
+        let (lower, upper) = descendants_range(root);
+        let mut rows = stmt.query(params![
+            kind_to_sql(EntryKind::File),
+            root,
+            lower,
+            upper,
+            since
+        ])?;
+        let mut results = Vec::new();
+
+        while results.len() < limit {
+            let Some(row) = rows.next()? else {
+                break;
+            };
+            let entry = entry_from_row(row)?;
+            if accept(&entry) {
+                results.push(entry);
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+/// Reads `path, name, kind, size, modified, created, hidden` columns.
+fn entry_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<FileEntry> {
+    let path: String = row.get(0)?;
+    let kind: i64 = row.get(2)?;
+    let size: i64 = row.get(3)?;
+    Ok(FileEntry {
+        name: row.get(1)?,
+        extension: get_extension(Path::new(&path)),
+        path,
+        is_dir: kind == kind_to_sql(EntryKind::Directory),
+        is_hidden: row.get(6)?,
+        size: size.max(0) as u64,
+        modified: row.get(4)?,
+        created: row.get(5)?,
+    })
+}
@@ -1,7 +1,11 @@
 //! Tauri command handlers.
 
 pub mod file_ops;
+pub mod index;
+mod office;
 pub mod preview;
+pub mod recent;
+pub mod saved_search;
 pub mod search;
 pub mod watcher;
 
@@ -17,10 +21,30 @@ pub use file_ops::{
     read_directory_sync, rename_entry_sync,
 };
 #[doc(hidden)]
-pub use preview::{get_file_preview_sync, get_thumbnail_sync};
+pub use index::{
+    add_index_root_sync, get_index_status_sync, rebuild_index_sync, remove_index_root_sync,
+    search_content_index_sync, search_index_sync,
+};
+#[doc(hidden)]
+pub use preview::{
+    get_file_preview_sync, get_image_metadata_sync, get_image_preview_sync, get_thumbnail_sync,
+    read_bytes_range_sync, read_sheet_range_sync, read_text_range_sync, search_bytes_sync,
+};
+#[doc(hidden)]
+pub use recent::recent_changes_sync;
 #[doc(hidden)]
-pub use search::{search_by_name_sync, search_content_sync, search_files_sync};
+pub use saved_search::{
+    delete_saved_search_sync, list_saved_searches_sync, open_saved_search_sync, save_search_sync,
+    unwatch_saved_search_sync, watch_saved_search_sync,
+};
+#[doc(hidden)]
+pub use search::{
+    cancel_search_sync, fuzzy_search_sync, parse_search_query_sync, replace_in_files_sync,
+    search_by_name_sync, search_content_sync, search_files_cancellable_sync, search_files_sync,
+    undo_replace_sync,
+};
 #[doc(hidden)]
 pub use watcher::{
-    unwatch_all_sync, unwatch_directory_sync, validate_watch_directory_path, watch_directory_sync,
+    tail_file_sync, untail_file_sync, unwatch_all_sync, unwatch_directory_sync,
+    validate_watch_directory_path, watch_directory_sync,
 };
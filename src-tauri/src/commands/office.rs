@@ -0,0 +1,587 @@
+//! XML and archive helpers shared by document previews and content search.
+//!
+//! OOXML (DOCX, XLSX, PPTX) and OpenDocument files are zip archives of XML
+//! parts; the readers here locate parts and stream their text so previews and
+//! search agree on what a document contains and in which order.
+
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::ops::ControlFlow;
+
+use quick_xml::events::{BytesStart, Event};
+use zip::ZipArchive;
+
+use crate::models::preview::DocFootnote;
+
+/// Returns the local name of an XML tag, stripping the namespace prefix.
+/// e.g. b"w:p" -> b"p", b"a:t" -> b"t"
+pub(crate) fn local_name(full: &[u8]) -> &[u8] {
+    match full.iter().position(|&b| b == b':') {
+        Some(pos) => &full[pos + 1..],
+        None => full,
+    }
+}
+
+/// Value of the attribute with the given local name, e.g. `b"val"` for `w:val`.
+pub(crate) fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| local_name(attr.key.as_ref()) == name)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Reads an archive entry as text, or `None` when it is missing or not UTF-8.
+pub(crate) fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+/// A relationship target from an OOXML `_rels/*.rels` part.
+pub(crate) struct Relationship {
+    pub(crate) target: String,
+    /// Relationship type URI, e.g. `.../relationships/image`.
+    pub(crate) kind: String,
+    pub(crate) external: bool,
+}
+
+pub(crate) fn relationships(xml: &str) -> HashMap<String, Relationship> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut rels = HashMap::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e) | Event::Empty(ref e))
+                if local_name(e.name().as_ref()) == b"Relationship" =>
+            {
+                if let (Some(id), Some(target)) = (attribute(e, b"Id"), attribute(e, b"Target")) {
+                    let kind = attribute(e, b"Type").unwrap_or_default();
+                    let external = attribute(e, b"TargetMode").as_deref() == Some("External");
+                    rels.insert(
+                        id,
+                        Relationship {
+                            target,
+                            kind,
+                            external,
+                        },
+                    );
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    rels
+}
+
+/// Archive path of a part referenced from a part in `dir`, e.g.
+/// `("ppt/slides", "../media/image1.png")` -> `ppt/media/image1.png`.
+pub(crate) fn part_path(dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut parts: Vec<&str> = dir.split('/').filter(|part| !part.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Relationships of a part, read from the `_rels` folder next to it.
+pub(crate) fn part_relationships<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    path: &str,
+) -> (String, HashMap<String, Relationship>) {
+    let (dir, file) = path.rsplit_once('/').unwrap_or(("", path));
+    let rels = read_entry(archive, &format!("{dir}/_rels/{file}.rels"))
+        .map(|xml| relationships(&xml))
+        .unwrap_or_default();
+    (dir.to_string(), rels)
+}
+
+/// Slide part paths of a PPTX deck in the order listed by `ppt/presentation.xml`.
+///
+/// Falls back to file name order for decks without a readable slide list.
+pub(crate) fn slide_paths<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Vec<String> {
+    let rels = read_entry(archive, "ppt/_rels/presentation.xml.rels")
+        .map(|xml| relationships(&xml))
+        .unwrap_or_default();
+    let mut ordered = Vec::new();
+    if let Some(xml) = read_entry(archive, "ppt/presentation.xml") {
+        let mut reader = quick_xml::Reader::from_str(&xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e) | Event::Empty(ref e))
+                    if local_name(e.name().as_ref()) == b"sldId" =>
+                {
+                    // `id` is the slide id; the relationship is the prefixed `r:id`.
+                    let rel_id = e
+                        .attributes()
+                        .flatten()
+                        .find(|attr| attr.key.as_ref().ends_with(b":id"))
+                        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned());
+                    if let Some(rel) = rel_id.and_then(|id| rels.get(&id)) {
+                        ordered.push(part_path("ppt", &rel.target));
+                    }
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+    }
+    ordered.retain(|path| archive.index_for_name(path).is_some());
+    if !ordered.is_empty() {
+        return ordered;
+    }
+
+    let mut by_name: Vec<(u32, String)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let number = name
+                .strip_prefix("ppt/slides/slide")?
+                .strip_suffix(".xml")?
+                .parse()
+                .ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect();
+    by_name.sort_unstable();
+    by_name.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Footnote text by id, skipping the separator notes Word adds to every document.
+pub(crate) fn footnotes(xml: &str) -> Vec<DocFootnote> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut notes = Vec::new();
+    let mut current: Option<(u32, Vec<String>)> = None;
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => match local_name(e.name().as_ref()) {
+                b"footnote" if attribute(e, b"type").is_none() => {
+                    current = attribute(e, b"id")
+                        .and_then(|id| id.parse().ok())
+                        .map(|id| (id, Vec::new()));
+                }
+                b"p" => {
+                    if let Some((_, paragraphs)) = current.as_mut() {
+                        paragraphs.push(String::new());
+                    }
+                }
+                b"t" => in_text = true,
+                _ => {}
+            },
+            Ok(Event::End(ref e)) => match local_name(e.name().as_ref()) {
+                b"footnote" => {
+                    if let Some((id, paragraphs)) = current.take() {
+                        notes.push(DocFootnote {
+                            id,
+                            text: paragraphs.join("\n").trim().to_string(),
+                        });
+                    }
+                }
+                b"t" => in_text = false,
+                _ => {}
+            },
+            Ok(Event::Text(ref e)) if in_text => {
+                if let (Some((_, paragraphs)), Ok(text)) = (current.as_mut(), e.unescape()) {
+                    if let Some(paragraph) = paragraphs.last_mut() {
+                        paragraph.push_str(&text);
+                    }
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    notes
+}
+
+/// Text shapes, tables and pictures read from a PPTX slide or notes page.
+#[derive(Default)]
+pub(crate) struct SlideXml {
+    /// Paragraphs of each text shape, with its placeholder type if it has one.
+    pub(crate) shapes: Vec<(Option<String>, Vec<String>)>,
+    pub(crate) tables: Vec<Vec<Vec<String>>>,
+    /// Relationship ids of embedded pictures, in document order.
+    pub(crate) pictures: Vec<String>,
+}
+
+impl SlideXml {
+    /// The title placeholder text, and the paragraphs of every other shape.
+    pub(crate) fn title_and_texts(&mut self) -> (Option<String>, Vec<String>) {
+        let mut title = None;
+        let mut texts = Vec::new();
+        for (placeholder, paragraphs) in self.shapes.drain(..) {
+            let is_title = matches!(placeholder.as_deref(), Some("title" | "ctrTitle"));
+            if is_title && title.is_none() && !paragraphs.is_empty() {
+                title = Some(paragraphs.join(" "));
+            } else if !is_title {
+                texts.extend(paragraphs);
+            }
+        }
+        (title, texts)
+    }
+
+    /// Text of the body placeholder on a notes page; the slide image and
+    /// slide number placeholders are left out.
+    pub(crate) fn notes(&self) -> Option<String> {
+        let text = self
+            .shapes
+            .iter()
+            .filter(|(placeholder, _)| placeholder.as_deref() == Some("body"))
+            .flat_map(|(_, paragraphs)| paragraphs.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n");
+        (!text.trim().is_empty()).then_some(text)
+    }
+}
+
+/// Streaming reader of a slide or notes page.
+#[derive(Default)]
+struct SlideParser {
+    slide: SlideXml,
+    shape: Option<(Option<String>, Vec<String>)>,
+    /// Finished rows and the row being read of the open table.
+    table: Option<(Vec<Vec<String>>, Vec<String>)>,
+    cell: Option<Vec<String>>,
+    paragraph: Option<String>,
+    in_text: bool,
+}
+
+impl SlideParser {
+    fn start(&mut self, e: &BytesStart, empty: bool) {
+        match local_name(e.name().as_ref()) {
+            b"sp" if !empty => self.shape = Some((None, Vec::new())),
+            // A placeholder without a type is a content ("obj") placeholder.
+            b"ph" => {
+                if let Some((placeholder, _)) = self.shape.as_mut() {
+                    *placeholder = Some(attribute(e, b"type").unwrap_or_else(|| "obj".to_string()));
+                }
+            }
+            b"tbl" if !empty => self.table = Some((Vec::new(), Vec::new())),
+            b"tc" if self.table.is_some() => {
+                if empty {
+                    self.finish_cell(Vec::new());
+                } else {
+                    self.cell = Some(Vec::new());
+                }
+            }
+            b"p" if !empty && (self.shape.is_some() || self.cell.is_some()) => {
+                self.paragraph = Some(String::new());
+            }
+            b"t" if !empty && self.paragraph.is_some() => self.in_text = true,
+            b"br" => {
+                if let Some(paragraph) = self.paragraph.as_mut() {
+                    paragraph.push('\n');
+                }
+            }
+            b"blip" => {
+                if let Some(id) = attribute(e, b"embed") {
+                    self.slide.pictures.push(id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, local: &[u8]) {
+        match local {
+            b"t" => self.in_text = false,
+            b"p" => {
+                let Some(text) = self.paragraph.take().filter(|text| !text.is_empty()) else {
+                    return;
+                };
+                if let Some(cell) = self.cell.as_mut() {
+                    cell.push(text);
+                } else if let Some((_, paragraphs)) = self.shape.as_mut() {
+                    paragraphs.push(text);
+                }
+            }
+            b"tc" => {
+                if let Some(cell) = self.cell.take() {
+                    self.finish_cell(cell);
+                }
+            }
+            b"tr" => {
+                if let Some((rows, row)) = self.table.as_mut() {
+                    rows.push(std::mem::take(row));
+                }
+            }
+            b"tbl" => {
+                if let Some((rows, _)) = self.table.take().filter(|(rows, _)| !rows.is_empty()) {
+                    self.slide.tables.push(rows);
+                }
+            }
+            b"sp" => {
+                if let Some(shape) = self.shape.take() {
+                    self.slide.shapes.push(shape);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish_cell(&mut self, paragraphs: Vec<String>) {
+        if let Some((_, row)) = self.table.as_mut() {
+            row.push(paragraphs.join("\n"));
+        }
+    }
+}
+
+pub(crate) fn parse_slide(xml: &str) -> Result<SlideXml, String> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut parser = SlideParser::default();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => parser.start(e, false),
+            Ok(Event::Empty(ref e)) => parser.start(e, true),
+            Ok(Event::End(ref e)) => parser.end(local_name(e.name().as_ref())),
+            Ok(Event::Text(ref e)) if parser.in_text => {
+                let text = e
+                    .unescape()
+                    .map_err(|e| format!("Error parsing PPTX XML: {e}"))?;
+                if let Some(paragraph) = parser.paragraph.as_mut() {
+                    paragraph.push_str(&text);
+                }
+            }
+            Ok(Event::Eof) => return Ok(parser.slide),
+            Err(e) => return Err(format!("Error parsing PPTX XML: {e}")),
+            _ => {}
+        }
+    }
+}
+
+/// What [`walk_odf`] reports to its caller.
+pub(crate) enum OdfEvent<'a> {
+    /// A finished, non-empty paragraph; `heading` is the outline level of a `text:h`.
+    Paragraph {
+        text: String,
+        heading: Option<u32>,
+    },
+    Start(&'a BytesStart<'a>),
+    End(&'a [u8]),
+}
+
+/// Appends the text an empty OpenDocument spacing element stands for.
+fn push_spacing(element: &BytesStart, text: &mut String) {
+    match local_name(element.name().as_ref()) {
+        b"s" => {
+            let count = attribute(element, b"c")
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(1);
+            text.extend(std::iter::repeat_n(' ', count));
+        }
+        b"tab" => text.push('\t'),
+        b"line-break" => text.push('\n'),
+        _ => {}
+    }
+}
+
+/// Streams OpenDocument `xml`, collecting paragraph text and passing other
+/// elements through. The subtrees of `skipped` elements are ignored.
+///
+/// Paragraphs can nest (a text box inside a paragraph), so they are kept on a
+/// stack and reported innermost first.
+pub(crate) fn walk_odf(
+    xml: &str,
+    skipped: &[&[u8]],
+    mut visit: impl FnMut(OdfEvent) -> ControlFlow<()>,
+) -> Result<(), String> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut skip_depth = 0usize;
+    let mut open: Vec<(Option<u32>, String)> = Vec::new();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Error parsing OpenDocument XML: {e}"))?;
+        let flow = match event {
+            Event::Start(ref e) => {
+                let name = e.name();
+                let local = local_name(name.as_ref());
+                if skip_depth > 0 || skipped.contains(&local) {
+                    skip_depth += 1;
+                    continue;
+                }
+                match local {
+                    b"p" => open.push((None, String::new())),
+                    b"h" => {
+                        let level = attribute(e, b"outline-level")
+                            .and_then(|l| l.parse().ok())
+                            .unwrap_or(1);
+                        open.push((Some(level), String::new()));
+                    }
+                    _ => {}
+                }
+                visit(OdfEvent::Start(e))
+            }
+            Event::Empty(ref e) if skip_depth == 0 => {
+                if let Some((_, text)) = open.last_mut() {
+                    push_spacing(e, text);
+                }
+                ControlFlow::Continue(())
+            }
+            Event::End(ref e) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                    continue;
+                }
+                let name = e.name();
+                let local = local_name(name.as_ref());
+                match local {
+                    b"p" | b"h" => match open.pop() {
+                        Some((heading, text)) if !text.trim().is_empty() => {
+                            visit(OdfEvent::Paragraph {
+                                text: text.trim().to_string(),
+                                heading,
+                            })
+                        }
+                        _ => ControlFlow::Continue(()),
+                    },
+                    _ => visit(OdfEvent::End(local)),
+                }
+            }
+            Event::Text(ref e) if skip_depth == 0 => {
+                if let Some((_, text)) = open.last_mut() {
+                    let unescaped = e
+                        .unescape()
+                        .map_err(|e| format!("Error parsing OpenDocument XML: {e}"))?;
+                    text.push_str(&unescaped);
+                }
+                ControlFlow::Continue(())
+            }
+            Event::Eof => break,
+            _ => ControlFlow::Continue(()),
+        };
+        if flow.is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Collects the text of every OOXML paragraph (`w:p`, `a:p`) in document
+/// order, empty ones included. Only text inside `w:t`/`a:t` counts; a
+/// paragraph nested in another (e.g. in a text box) ends the outer one's
+/// text so far.
+pub(crate) fn ooxml_paragraphs(xml: &str) -> Result<Vec<String>, String> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut in_text = false;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Error parsing OOXML: {e}"))?;
+        match event {
+            Event::Start(ref e) => match local_name(e.name().as_ref()) {
+                b"p" => {
+                    if depth > 0 {
+                        paragraphs.push(std::mem::take(&mut current));
+                    }
+                    depth += 1;
+                }
+                b"t" => in_text = true,
+                _ => {}
+            },
+            Event::Empty(ref e) => match local_name(e.name().as_ref()) {
+                b"p" if depth == 0 => paragraphs.push(String::new()),
+                b"tab" | b"br" if depth > 0 && !current.is_empty() => current.push(' '),
+                _ => {}
+            },
+            Event::End(ref e) => match local_name(e.name().as_ref()) {
+                b"p" if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        paragraphs.push(std::mem::take(&mut current));
+                    }
+                }
+                b"t" => in_text = false,
+                _ => {}
+            },
+            Event::Text(ref e) if depth > 0 && in_text => {
+                if let Ok(text) = e.unescape() {
+                    current.push_str(&text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(paragraphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slide_xml_separates_titles_tables_and_notes() {
+        let xml = r#"<p:sld><p:cSld><p:spTree>
+            <p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr>
+                <p:txBody><a:p><a:r><a:t>Q3</a:t></a:r></a:p></p:txBody></p:sp>
+            <p:sp><p:nvSpPr><p:nvPr><p:ph idx="1"/></p:nvPr></p:nvSpPr>
+                <p:txBody><a:p><a:r><a:t>Revenue</a:t></a:r><a:br/><a:r><a:t>up</a:t></a:r></a:p><a:p/></p:txBody></p:sp>
+            <p:graphicFrame><a:graphic><a:graphicData><a:tbl>
+                <a:tr><a:tc><a:txBody><a:p><a:r><a:t>Region</a:t></a:r></a:p></a:txBody></a:tc><a:tc/></a:tr>
+                <a:tr><a:tc><a:txBody><a:p><a:r><a:t>EU</a:t></a:r></a:p></a:txBody></a:tc><a:tc><a:txBody><a:p><a:r><a:t>4</a:t></a:r></a:p></a:txBody></a:tc></a:tr>
+            </a:tbl></a:graphicData></a:graphic></p:graphicFrame>
+            <p:pic><p:blipFill><a:blip r:embed="rId2"/></p:blipFill></p:pic>
+        </p:spTree></p:cSld></p:sld>"#;
+
+        let mut slide = parse_slide(xml).unwrap();
+        assert_eq!(
+            slide.tables,
+            vec![vec![
+                vec!["Region".to_string(), String::new()],
+                vec!["EU".to_string(), "4".to_string()],
+            ]]
+        );
+        assert_eq!(slide.pictures, vec!["rId2"]);
+        assert_eq!(slide.notes(), None);
+        assert_eq!(
+            slide.title_and_texts(),
+            (Some("Q3".to_string()), vec!["Revenue\nup".to_string()])
+        );
+    }
+
+    #[test]
+    fn part_paths_resolve_relative_targets() {
+        assert_eq!(
+            part_path("ppt/slides", "../media/image1.png"),
+            "ppt/media/image1.png"
+        );
+        assert_eq!(
+            part_path("ppt", "slides/slide2.xml"),
+            "ppt/slides/slide2.xml"
+        );
+        assert_eq!(part_path("ppt/slides", "/ppt/x.xml"), "ppt/x.xml");
+    }
+
+    #[test]
+    fn ooxml_paragraphs_keep_their_position() {
+        let xml = r#"<w:document><w:body>
+            <w:p><w:r><w:t>First </w:t></w:r><w:r><w:t>line</w:t></w:r></w:p>
+            <w:p/>
+            <w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Third &amp; last</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+
+        let paragraphs = ooxml_paragraphs(xml).unwrap();
+        assert_eq!(paragraphs, vec!["First line", "", "Third & last"]);
+    }
+}
@@ -0,0 +1,506 @@
+//! DOCX preview: paragraphs with formatted runs, tables, hyperlinks,
+//! footnotes and downscaled embedded images.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Cursor, Read};
+
+use image::imageops::FilterType;
+use image::io::Reader as ImageReader;
+use quick_xml::events::{BytesStart, Event};
+use zip::ZipArchive;
+
+use crate::constants::{
+    DOCUMENT_IMAGE_MAX_SIDE, MAX_DOCUMENT_IMAGES, MAX_DOCUMENT_PARAGRAPHS, MAX_OFFICE_FILE_SIZE,
+    MAX_THUMBNAIL_FILE_SIZE, MAX_THUMBNAIL_PIXELS,
+};
+use crate::models::preview::{DocImage, DocParagraph, DocRun, DocTableCell};
+use crate::models::FilePreview;
+
+use super::{encode_preview, is_open_document, odf, too_large_mime};
+use crate::commands::office::{
+    attribute, footnotes, local_name, part_path, read_entry, relationships, Relationship,
+};
+
+/// Whether a toggle property like `<w:b/>` or `<w:b w:val="0"/>` is on.
+fn toggle_on(element: &BytesStart) -> bool {
+    !matches!(
+        attribute(element, b"val").as_deref(),
+        Some("0" | "false" | "off" | "none")
+    )
+}
+
+/// Classifies a DOCX w:pStyle value into a simpler style name.
+fn classify_docx_style(val: &str) -> String {
+    if val.starts_with("heading1") || val == "title" {
+        "heading1".to_string()
+    } else if val.starts_with("heading2") || val == "subtitle" {
+        "heading2".to_string()
+    } else if val.starts_with("heading") {
+        "heading3".to_string()
+    } else if val.contains("list") || val.contains("bullet") {
+        "listItem".to_string()
+    } else {
+        "normal".to_string()
+    }
+}
+
+/// Decodes an embedded picture and shrinks it to `max_side`.
+///
+/// Formats the image crate can't decode (EMF, WMF) are skipped.
+pub(super) fn load_image(
+    archive: &mut ZipArchive<File>,
+    path: &str,
+    max_side: u32,
+    description: Option<String>,
+) -> Option<DocImage> {
+    let mut entry = archive.by_name(path).ok()?;
+    if entry.size() > MAX_THUMBNAIL_FILE_SIZE {
+        return None;
+    }
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data).ok()?;
+
+    let (w, h) = ImageReader::new(Cursor::new(&data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()?;
+    if u64::from(w) * u64::from(h) > MAX_THUMBNAIL_PIXELS {
+        return None;
+    }
+
+    let mut img = image::load_from_memory(&data).ok()?;
+    if w.max(h) > max_side {
+        img = img.resize(max_side, max_side, FilterType::Lanczos3);
+    }
+    let (base64, mime) = encode_preview(&img).ok()?;
+    Some(DocImage {
+        base64,
+        mime: mime.to_string(),
+        description,
+    })
+}
+
+/// Rows of a table being read, and the cell currently open.
+#[derive(Default)]
+struct TableBuilder {
+    rows: Vec<Vec<DocTableCell>>,
+    row: Vec<DocTableCell>,
+    cell_paragraphs: Vec<String>,
+    cell_runs: Vec<DocRun>,
+}
+
+impl TableBuilder {
+    fn add_paragraph(&mut self, text: String, runs: Vec<DocRun>) {
+        if !self.cell_runs.is_empty() && !runs.is_empty() {
+            self.cell_runs.push(DocRun {
+                text: "\n".to_string(),
+                ..DocRun::default()
+            });
+        }
+        self.cell_paragraphs.push(text);
+        self.cell_runs.extend(runs);
+    }
+
+    fn finish_cell(&mut self) {
+        self.row.push(DocTableCell {
+            text: std::mem::take(&mut self.cell_paragraphs).join("\n"),
+            runs: std::mem::take(&mut self.cell_runs),
+        });
+    }
+
+    /// Cells separated by tabs and rows by newlines.
+    fn text(rows: &[Vec<DocTableCell>]) -> String {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.text.replace('\n', " "))
+                    .collect::<Vec<_>>()
+                    .join("\t")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Paragraph state of the text around a text box, restored when the box closes.
+struct TextBoxFrame {
+    paragraph: Option<(String, Vec<DocRun>)>,
+    format: DocRun,
+    in_run: bool,
+    link: Option<String>,
+    tables: Vec<TableBuilder>,
+    pending: Vec<DocParagraph>,
+    /// Blocks read inside the box.
+    blocks: Vec<DocParagraph>,
+}
+
+/// Streaming reader of `word/document.xml`.
+struct DocxParser<'a> {
+    archive: &'a mut ZipArchive<File>,
+    rels: HashMap<String, Relationship>,
+    paragraphs: Vec<DocParagraph>,
+    images: Vec<DocImage>,
+    /// Image index by relationship id; `None` for pictures that couldn't be loaded.
+    image_ids: HashMap<String, Option<u32>>,
+    tables: Vec<TableBuilder>,
+    paragraph: Option<(String, Vec<DocRun>)>,
+    /// Formatting of the open run.
+    format: DocRun,
+    in_run: bool,
+    in_run_props: bool,
+    in_text: bool,
+    link: Option<String>,
+    drawing_description: Option<String>,
+    /// Open text boxes, innermost last.
+    text_boxes: Vec<TextBoxFrame>,
+    /// Text box blocks that follow the open paragraph once it closes.
+    pending: Vec<DocParagraph>,
+    /// Depth inside subtrees whose content would duplicate or nest paragraphs.
+    skip_depth: usize,
+}
+
+impl<'a> DocxParser<'a> {
+    fn new(archive: &'a mut ZipArchive<File>, rels: HashMap<String, Relationship>) -> Self {
+        Self {
+            archive,
+            rels,
+            paragraphs: Vec::new(),
+            images: Vec::new(),
+            image_ids: HashMap::new(),
+            tables: Vec::new(),
+            paragraph: None,
+            format: DocRun::default(),
+            in_run: false,
+            in_run_props: false,
+            in_text: false,
+            link: None,
+            drawing_description: None,
+            text_boxes: Vec::new(),
+            pending: Vec::new(),
+            skip_depth: 0,
+        }
+    }
+
+    /// Appends a run to the open paragraph, merging it into the last one when
+    /// both are plain text with the same formatting.
+    fn push_run(&mut self, run: DocRun) {
+        let Some((_, runs)) = self.paragraph.as_mut() else {
+            return;
+        };
+        match runs.last_mut() {
+            Some(last)
+                if run.image.is_none()
+                    && run.footnote.is_none()
+                    && last.image.is_none()
+                    && last.footnote.is_none()
+                    && (last.bold, last.italic, last.underline, &last.link)
+                        == (run.bold, run.italic, run.underline, &run.link) =>
+            {
+                last.text.push_str(&run.text);
+            }
+            _ => runs.push(run),
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        self.push_run(DocRun {
+            text: text.to_string(),
+            link: self.link.clone(),
+            ..self.format.clone()
+        });
+    }
+
+    fn image_index(&mut self, id: &str) -> Option<u32> {
+        if let Some(&index) = self.image_ids.get(id) {
+            return index;
+        }
+        let index = self
+            .rels
+            .get(id)
+            .filter(|rel| !rel.external && self.images.len() < MAX_DOCUMENT_IMAGES)
+            .map(|rel| part_path("word", &rel.target))
+            .and_then(|path| {
+                load_image(
+                    self.archive,
+                    &path,
+                    DOCUMENT_IMAGE_MAX_SIDE,
+                    self.drawing_description.clone(),
+                )
+            })
+            .map(|image| {
+                self.images.push(image);
+                self.images.len() as u32 - 1
+            });
+        self.image_ids.insert(id.to_string(), index);
+        index
+    }
+
+    /// Adds a finished top-level block; returns false once the limit is reached.
+    fn push_block(&mut self, paragraph: DocParagraph) -> bool {
+        self.paragraphs.push(paragraph);
+        self.paragraphs.len() < MAX_DOCUMENT_PARAGRAPHS
+    }
+
+    /// Adds a finished block to the open text box, or to the document.
+    fn finish_block(&mut self, paragraph: DocParagraph) -> bool {
+        match self.text_boxes.last_mut() {
+            Some(frame) => {
+                frame.blocks.push(paragraph);
+                true
+            }
+            None => self.push_block(paragraph),
+        }
+    }
+
+    /// Sets the surrounding paragraph aside while a text box is read.
+    fn open_text_box(&mut self) {
+        self.text_boxes.push(TextBoxFrame {
+            paragraph: self.paragraph.take(),
+            format: std::mem::take(&mut self.format),
+            in_run: std::mem::take(&mut self.in_run),
+            link: self.link.take(),
+            tables: std::mem::take(&mut self.tables),
+            pending: std::mem::take(&mut self.pending),
+            blocks: Vec::new(),
+        });
+        self.in_run_props = false;
+    }
+
+    /// Restores the surrounding paragraph and queues the box's blocks after it.
+    fn close_text_box(&mut self) {
+        let Some(frame) = self.text_boxes.pop() else {
+            return;
+        };
+        self.paragraph = frame.paragraph;
+        self.format = frame.format;
+        self.in_run = frame.in_run;
+        self.in_run_props = false;
+        self.in_text = false;
+        self.link = frame.link;
+        self.tables = frame.tables;
+        self.pending = frame.pending;
+        self.pending.extend(frame.blocks);
+    }
+
+    fn start(&mut self, e: &BytesStart, empty: bool) {
+        let name = e.name();
+        let local = local_name(name.as_ref());
+        // The VML fallback repeats the text boxes of the DrawingML choice.
+        if self.skip_depth > 0 || local == b"Fallback" {
+            if !empty {
+                self.skip_depth += 1;
+            }
+            return;
+        }
+
+        match local {
+            b"p" if !empty => self.paragraph = Some(("normal".to_string(), Vec::new())),
+            b"pStyle" => {
+                if let (Some((style, _)), Some(val)) =
+                    (self.paragraph.as_mut(), attribute(e, b"val"))
+                {
+                    *style = classify_docx_style(&val.to_lowercase());
+                }
+            }
+            b"numPr" => {
+                if let Some((style, _)) = self.paragraph.as_mut() {
+                    if style == "normal" {
+                        *style = "listItem".to_string();
+                    }
+                }
+            }
+            b"r" if !empty => {
+                self.in_run = true;
+                self.format = DocRun::default();
+            }
+            b"rPr" if self.in_run && !empty => self.in_run_props = true,
+            b"b" if self.in_run_props => self.format.bold = toggle_on(e),
+            b"i" if self.in_run_props => self.format.italic = toggle_on(e),
+            b"u" if self.in_run_props => self.format.underline = toggle_on(e),
+            b"t" if self.in_run && !empty => self.in_text = true,
+            b"tab" if self.in_run => self.push_text("\t"),
+            b"br" | b"cr" if self.in_run => self.push_text("\n"),
+            b"hyperlink" if !empty => {
+                self.link = attribute(e, b"id")
+                    .and_then(|id| self.rels.get(&id))
+                    .map(|rel| rel.target.clone())
+                    .or_else(|| attribute(e, b"anchor").map(|anchor| format!("#{anchor}")));
+            }
+            b"footnoteReference" => {
+                if let Some(id) = attribute(e, b"id").and_then(|id| id.parse::<u32>().ok()) {
+                    self.push_run(DocRun {
+                        text: id.to_string(),
+                        footnote: Some(id),
+                        ..self.format.clone()
+                    });
+                }
+            }
+            b"docPr" => {
+                self.drawing_description = attribute(e, b"descr").filter(|d| !d.is_empty());
+            }
+            b"blip" => {
+                if let Some(index) = attribute(e, b"embed").and_then(|id| self.image_index(&id)) {
+                    self.push_run(DocRun {
+                        image: Some(index),
+                        link: self.link.clone(),
+                        ..DocRun::default()
+                    });
+                }
+            }
+            b"tbl" if !empty => self.tables.push(TableBuilder::default()),
+            b"txbxContent" if !empty => self.open_text_box(),
+            _ => {}
+        }
+    }
+
+    /// Handles a closing tag; returns false once enough blocks were read.
+    fn end(&mut self, local: &[u8]) -> bool {
+        if self.skip_depth > 0 {
+            self.skip_depth -= 1;
+            return true;
+        }
+
+        match local {
+            b"p" => {
+                let Some((style, runs)) = self.paragraph.take() else {
+                    return true;
+                };
+                let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+                let pending = std::mem::take(&mut self.pending);
+                if let Some(table) = self.tables.last_mut() {
+                    table.add_paragraph(text, runs);
+                    for block in pending {
+                        table.add_paragraph(block.text, block.runs);
+                    }
+                    return true;
+                }
+                if !text.trim().is_empty() || runs.iter().any(|run| run.image.is_some()) {
+                    let block = DocParagraph {
+                        text,
+                        style,
+                        runs,
+                        table: None,
+                    };
+                    if !self.finish_block(block) {
+                        return false;
+                    }
+                }
+                for block in pending {
+                    if !self.finish_block(block) {
+                        return false;
+                    }
+                }
+            }
+            b"r" => {
+                self.in_run = false;
+                self.in_run_props = false;
+                self.in_text = false;
+            }
+            b"rPr" => self.in_run_props = false,
+            b"t" => self.in_text = false,
+            b"hyperlink" => self.link = None,
+            b"drawing" => self.drawing_description = None,
+            b"txbxContent" => self.close_text_box(),
+            b"tc" => {
+                if let Some(table) = self.tables.last_mut() {
+                    table.finish_cell();
+                }
+            }
+            b"tr" => {
+                if let Some(table) = self.tables.last_mut() {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
+            }
+            b"tbl" => {
+                let Some(table) = self.tables.pop() else {
+                    return true;
+                };
+                let text = TableBuilder::text(&table.rows);
+                // Nested tables are flattened into the enclosing cell.
+                if let Some(parent) = self.tables.last_mut() {
+                    parent.add_paragraph(text, Vec::new());
+                } else {
+                    return self.finish_block(DocParagraph {
+                        text,
+                        style: "table".to_string(),
+                        runs: Vec::new(),
+                        table: Some(table.rows),
+                    });
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Parses the document body; returns whether it was cut off at the paragraph limit.
+    fn parse(&mut self, xml: &str) -> Result<bool, String> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => self.start(e, false),
+                Ok(Event::Empty(ref e)) => self.start(e, true),
+                Ok(Event::End(ref e)) => {
+                    let more = self.end(local_name(e.name().as_ref()));
+                    if !more {
+                        return Ok(true);
+                    }
+                }
+                Ok(Event::Text(ref e)) if self.in_text && self.skip_depth == 0 => {
+                    let text = e
+                        .unescape()
+                        .map_err(|e| format!("Error parsing DOCX XML: {e}"))?;
+                    self.push_text(&text);
+                }
+                Ok(Event::Eof) => return Ok(false),
+                Err(e) => return Err(format!("Error parsing DOCX XML: {e}")),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Generates a preview for a .docx file from word/document.xml and its parts.
+///
+/// .odt files are handed to the OpenDocument parser.
+pub(super) fn generate_document_preview(path: &str) -> Result<FilePreview, String> {
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    if meta.len() > MAX_OFFICE_FILE_SIZE {
+        return Ok(FilePreview::Unsupported {
+            mime: too_large_mime(path),
+        });
+    }
+    if is_open_document(path) {
+        return odf::generate_odt_preview(path);
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Invalid DOCX: {e}"))?;
+    let xml = {
+        let mut entry = archive
+            .by_name("word/document.xml")
+            .map_err(|e| format!("Missing word/document.xml: {e}"))?;
+        let mut buf = String::new();
+        entry.read_to_string(&mut buf).map_err(|e| e.to_string())?;
+        buf
+    };
+    let rels = read_entry(&mut archive, "word/_rels/document.xml.rels")
+        .map(|xml| relationships(&xml))
+        .unwrap_or_default();
+    let footnotes = read_entry(&mut archive, "word/footnotes.xml")
+        .map(|xml| footnotes(&xml))
+        .unwrap_or_default();
+
+    let mut parser = DocxParser::new(&mut archive, rels);
+    let truncated = parser.parse(&xml)?;
+
+    Ok(FilePreview::Document {
+        paragraphs: parser.paragraphs,
+        truncated,
+        footnotes,
+        images: parser.images,
+    })
+}
@@ -0,0 +1,164 @@
+//! Images stored inside archive-based files, used as their thumbnails:
+//! the preview saved with OOXML and OpenDocument files, EPUB covers and
+//! the first page of CBZ comics.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use quick_xml::events::Event;
+use zip::ZipArchive;
+
+use crate::constants::{IMAGE_EXTENSIONS, MAX_THUMBNAIL_FILE_SIZE};
+use crate::utils::get_extension;
+
+use crate::commands::office::{attribute, local_name, part_path, read_entry, relationships};
+
+/// Reads the embedded thumbnail or cover image of an archive-based file.
+///
+/// Returns `Ok(None)` when the file has no usable image.
+pub(super) fn embedded_image(path: &str, extension: &str) -> Result<Option<Vec<u8>>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Invalid archive: {e}"))?;
+
+    let entry = match extension {
+        "epub" => epub_cover(&mut archive),
+        "cbz" => first_page(&archive),
+        ext if ext.starts_with("od") => Some("Thumbnails/thumbnail.png".to_string()),
+        _ => ooxml_thumbnail(&mut archive),
+    };
+    Ok(entry.and_then(|name| read_image_entry(&mut archive, &name)))
+}
+
+fn read_image_entry(archive: &mut ZipArchive<File>, name: &str) -> Option<Vec<u8>> {
+    let entry = archive.by_name(name).ok()?;
+    if entry.size() > MAX_THUMBNAIL_FILE_SIZE {
+        return None;
+    }
+    let mut data = Vec::new();
+    entry
+        .take(MAX_THUMBNAIL_FILE_SIZE)
+        .read_to_end(&mut data)
+        .ok()?;
+    Some(data)
+}
+
+/// The thumbnail part named by the package relationships, usually
+/// `docProps/thumbnail.jpeg`.
+fn ooxml_thumbnail(archive: &mut ZipArchive<File>) -> Option<String> {
+    read_entry(archive, "_rels/.rels")
+        .map(|xml| relationships(&xml))
+        .and_then(|rels| {
+            rels.into_values()
+                .find(|rel| rel.kind.ends_with("/metadata/thumbnail") && !rel.external)
+        })
+        .map(|rel| part_path("", &rel.target))
+        .or_else(|| {
+            archive
+                .file_names()
+                .find(|name| name.starts_with("docProps/thumbnail."))
+                .map(str::to_string)
+        })
+}
+
+/// A manifest entry of an EPUB package document.
+struct ManifestItem {
+    id: String,
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+/// The cover image of an EPUB.
+///
+/// Tries the EPUB 3 `cover-image` property, then the EPUB 2 `<meta name="cover">`,
+/// then any image whose id or file name mentions "cover".
+fn epub_cover(archive: &mut ZipArchive<File>) -> Option<String> {
+    let container = read_entry(archive, "META-INF/container.xml")?;
+    let package_path = first_attribute(&container, b"rootfile", b"full-path")?;
+    let package = read_entry(archive, &package_path)?;
+
+    let mut cover_id = None;
+    let mut items = Vec::new();
+    let mut reader = quick_xml::Reader::from_str(&package);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e) | Event::Empty(ref e)) => match local_name(e.name().as_ref()) {
+                b"meta" if attribute(e, b"name").as_deref() == Some("cover") => {
+                    cover_id = attribute(e, b"content");
+                }
+                b"item" => {
+                    if let (Some(id), Some(href)) = (attribute(e, b"id"), attribute(e, b"href")) {
+                        items.push(ManifestItem {
+                            id,
+                            href,
+                            media_type: attribute(e, b"media-type").unwrap_or_default(),
+                            properties: attribute(e, b"properties").unwrap_or_default(),
+                        });
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    let images: Vec<&ManifestItem> = items
+        .iter()
+        .filter(|item| item.media_type.starts_with("image/"))
+        .collect();
+    let cover = images
+        .iter()
+        .find(|item| {
+            item.properties
+                .split_whitespace()
+                .any(|p| p == "cover-image")
+        })
+        .or_else(|| {
+            images
+                .iter()
+                .find(|item| Some(&item.id) == cover_id.as_ref())
+        })
+        .or_else(|| {
+            images.iter().find(|item| {
+                item.id.to_lowercase().contains("cover")
+                    || item.href.to_lowercase().contains("cover")
+            })
+        })?;
+
+    let dir = package_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    Some(part_path(dir, &cover.href.replace("%20", " ")))
+}
+
+/// Value of the `name` attribute on the first `element` in an XML document.
+fn first_attribute(xml: &str, element: &[u8], name: &[u8]) -> Option<String> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e) | Event::Empty(ref e))
+                if local_name(e.name().as_ref()) == element =>
+            {
+                return attribute(e, name);
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+/// The first image of a comic book archive in file name order, skipping
+/// hidden files and macOS resource forks.
+fn first_page(archive: &ZipArchive<File>) -> Option<String> {
+    archive
+        .file_names()
+        .filter(|name| {
+            !name
+                .split('/')
+                .any(|part| part.starts_with('.') || part == "__MACOSX")
+                && get_extension(Path::new(name))
+                    .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+        })
+        .min_by_key(|name| name.to_lowercase())
+        .map(str::to_string)
+}
@@ -0,0 +1,238 @@
+//! Hex dumps and byte pattern search for binary files of any size.
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::constants::{
+    DEFAULT_BYTE_SEARCH_MATCHES, HEX_BYTES_PER_ROW, HEX_PREVIEW_BYTES, MAX_HEX_RANGE_BYTES,
+};
+use crate::error::{FileManagerError, Result};
+use crate::models::{BytePattern, ByteSearchResult, FilePreview, HexChunk, HexRow};
+use crate::utils::validate_absolute_path;
+
+/// Bytes read per step while searching.
+const SEARCH_CHUNK: usize = 1024 * 1024;
+
+fn hex_rows(offset: u64, data: &[u8]) -> Vec<HexRow> {
+    data.chunks(HEX_BYTES_PER_ROW)
+        .enumerate()
+        .map(|(i, row)| HexRow {
+            offset: offset + (i * HEX_BYTES_PER_ROW) as u64,
+            hex: row
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            ascii: row
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn open_file(path: &str) -> Result<(File, u64)> {
+    validate_absolute_path(path)?;
+    let metadata = fs::metadata(path)?;
+    if !metadata.is_file() {
+        return Err(FileManagerError::InvalidPath(format!("Not a file: {path}")));
+    }
+    Ok((File::open(path)?, metadata.len()))
+}
+
+/// Reads up to `length` bytes at `offset`, aligned down to a whole row.
+#[doc(hidden)]
+pub fn read_bytes_range_sync(path: &str, offset: u64, length: u32) -> Result<HexChunk> {
+    let (mut file, size) = open_file(path)?;
+    let row = HEX_BYTES_PER_ROW as u64;
+    let offset = (offset.min(size) / row) * row;
+    let length = length.min(MAX_HEX_RANGE_BYTES);
+
+    let mut data = Vec::with_capacity(length as usize);
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(u64::from(length)).read_to_end(&mut data)?;
+
+    Ok(HexChunk {
+        offset,
+        rows: hex_rows(offset, &data),
+        file_size: size,
+    })
+}
+
+/// Hex preview of the start of a binary file.
+pub(super) fn generate_hex_preview(path: &Path, mime: String) -> Result<FilePreview> {
+    let chunk = read_bytes_range_sync(&path.to_string_lossy(), 0, HEX_PREVIEW_BYTES)?;
+    Ok(FilePreview::Hex {
+        mime,
+        file_size: chunk.file_size,
+        rows: chunk.rows,
+    })
+}
+
+/// A pattern compiled to bytes, where `None` matches any byte.
+struct Needle {
+    bytes: Vec<Option<u8>>,
+    ignore_case: bool,
+}
+
+impl Needle {
+    fn parse(pattern: &BytePattern) -> Result<Self> {
+        let needle = match pattern {
+            BytePattern::Hex { value } => {
+                let digits: String = value
+                    .trim()
+                    .trim_start_matches("0x")
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect();
+                if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+                    return Err(FileManagerError::InvalidPattern(format!(
+                        "Not a sequence of hex bytes: {value}"
+                    )));
+                }
+                let bytes = (0..digits.len())
+                    .step_by(2)
+                    .map(|i| match &digits[i..i + 2] {
+                        "??" => Ok(None),
+                        pair => u8::from_str_radix(pair, 16).map(Some).map_err(|_| {
+                            FileManagerError::InvalidPattern(format!("Not a hex byte: {pair}"))
+                        }),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Self {
+                    bytes,
+                    ignore_case: false,
+                }
+            }
+            BytePattern::Text {
+                value,
+                case_sensitive,
+            } => Self {
+                bytes: value.bytes().map(Some).collect(),
+                ignore_case: !case_sensitive,
+            },
+        };
+
+        if needle.bytes.is_empty() {
+            return Err(FileManagerError::InvalidPattern(
+                "Empty byte pattern".to_string(),
+            ));
+        }
+        if needle.bytes.iter().all(Option::is_none) {
+            return Err(FileManagerError::InvalidPattern(
+                "Pattern has only wildcards".to_string(),
+            ));
+        }
+        Ok(needle)
+    }
+
+    fn matches_at(&self, haystack: &[u8]) -> bool {
+        self.bytes
+            .iter()
+            .zip(haystack)
+            .all(|(want, &got)| match want {
+                None => true,
+                Some(want) if self.ignore_case => want.eq_ignore_ascii_case(&got),
+                Some(want) => *want == got,
+            })
+    }
+}
+
+/// Finds `pattern` in `path` from byte `from` on, returning at most `limit` offsets.
+///
+/// The file is streamed, so any size can be searched; matches may overlap.
+#[doc(hidden)]
+pub fn search_bytes_sync(
+    path: &str,
+    pattern: &BytePattern,
+    from: Option<u64>,
+    limit: Option<u32>,
+) -> Result<ByteSearchResult> {
+    let needle = Needle::parse(pattern)?;
+    let (mut file, size) = open_file(path)?;
+    let limit = limit.unwrap_or(DEFAULT_BYTE_SEARCH_MATCHES).max(1) as usize;
+    let len = needle.bytes.len();
+
+    let mut buf_start = from.unwrap_or(0).min(size);
+    file.seek(SeekFrom::Start(buf_start))?;
+    let mut buf: Vec<u8> = Vec::new();
+    let mut chunk = vec![0; SEARCH_CHUNK];
+    let mut offsets = Vec::new();
+
+    loop {
+        let n = file.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        for start in 0..(buf.len() + 1).saturating_sub(len) {
+            if needle.matches_at(&buf[start..start + len]) {
+                let offset = buf_start + start as u64;
+                if offsets.len() == limit {
+                    return Ok(ByteSearchResult {
+                        offsets,
+                        pattern_length: len as u32,
+                        next_offset: Some(offset),
+                    });
+                }
+                offsets.push(offset);
+            }
+        }
+
+        // Keep the bytes a match could still start in.
+        let scanned = buf.len().saturating_sub(len - 1);
+        buf.drain(..scanned);
+        buf_start += scanned as u64;
+    }
+
+    Ok(ByteSearchResult {
+        offsets,
+        pattern_length: len as u32,
+        next_offset: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_show_hex_and_printable_ascii() {
+        let rows = hex_rows(32, b"\x89PNG\r\n\x1a\n0123456789abcdefXY");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].offset, 32);
+        assert_eq!(
+            rows[0].hex,
+            "89 50 4e 47 0d 0a 1a 0a 30 31 32 33 34 35 36 37"
+        );
+        assert_eq!(rows[0].ascii, ".PNG....01234567");
+        assert_eq!(rows[1].offset, 48);
+        assert_eq!(rows[1].ascii, "89abcdefXY");
+    }
+
+    #[test]
+    fn hex_patterns_parse_with_wildcards() {
+        let hex = |value: &str| {
+            Needle::parse(&BytePattern::Hex {
+                value: value.to_string(),
+            })
+        };
+        assert_eq!(
+            hex("0xDE ad ?? ef").unwrap().bytes,
+            vec![Some(0xde), Some(0xad), None, Some(0xef)]
+        );
+        assert!(hex("abc").is_err());
+        assert!(hex("\u{e9}\u{e9}").is_err());
+        assert!(hex("zz").is_err());
+        assert!(hex("").is_err());
+        assert!(hex("?? ??").is_err());
+    }
+}
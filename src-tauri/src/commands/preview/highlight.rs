@@ -0,0 +1,250 @@
+//! Syntax highlighting for text previews.
+//!
+//! The language comes from a vim/emacs modeline, the file name or extension,
+//! or the first line (shebangs, `<?xml`, ...), in that order. Highlighting
+//! yields tokens classed by their innermost non-`meta` scope, so the frontend
+//! can style them with CSS for either theme.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use syntect::easy::ScopeRegionIterator;
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet};
+
+use crate::models::preview::HighlightToken;
+
+/// Lines at each end of a file searched for a modeline, as in vim.
+const MODELINE_LINES: usize = 5;
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn is_plain_text(syntax: &SyntaxReference) -> bool {
+    syntax.name == "Plain Text"
+}
+
+/// Finds a syntax by language name, alias or extension, e.g. `python`, `sh`, `C++`.
+fn syntax_for_token(token: &str) -> Option<&'static SyntaxReference> {
+    let token = token
+        .trim()
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let token = match token.to_ascii_lowercase().as_str() {
+        "" => return None,
+        "bash" | "zsh" | "ksh" | "dash" => "sh",
+        "node" | "nodejs" | "javascript" => "js",
+        "c++" | "cplusplus" => "cpp",
+        "makefile" => "make",
+        _ => token,
+    };
+    syntaxes().find_syntax_by_token(token)
+}
+
+/// Looks up the syntax for a file name: full names like `Makefile` first, then the extension.
+pub(super) fn syntax_for_name(path: &Path) -> Option<&'static SyntaxReference> {
+    let set = syntaxes();
+    let name = path.file_name()?.to_string_lossy();
+    set.find_syntax_by_extension(&name)
+        .or_else(|| {
+            let (_, ext) = name.rsplit_once('.')?;
+            set.find_syntax_by_extension(ext)
+                .or_else(|| set.find_syntax_by_extension(&ext.to_ascii_lowercase()))
+        })
+        .filter(|syntax| !is_plain_text(syntax))
+}
+
+/// Language named by a vim (`vim: set ft=python:`) or emacs (`-*- mode: ruby -*-`) modeline.
+fn modeline_language(line: &str) -> Option<String> {
+    static VIM: OnceLock<Regex> = OnceLock::new();
+    static EMACS: OnceLock<Regex> = OnceLock::new();
+
+    let vim = VIM.get_or_init(|| {
+        Regex::new(r"\b(?:vi|vim|ex):.*?\b(?:ft|filetype|syntax)=([\w+#-]+)").unwrap()
+    });
+    if let Some(captures) = vim.captures(line) {
+        return Some(captures[1].to_string());
+    }
+
+    let emacs = EMACS.get_or_init(|| Regex::new(r"-\*-(.*?)-\*-").unwrap());
+    let inner = emacs.captures(line)?.get(1)?.as_str().trim();
+    if !inner.contains(':') {
+        return Some(inner.to_string());
+    }
+    inner.split(';').find_map(|pair| {
+        let (key, value) = pair.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("mode")
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// Interpreter of a `#!` line, skipping `env` and its flags.
+fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        words.find(|word| !word.starts_with('-') && !word.contains('='))
+    } else {
+        Some(program)
+    }
+}
+
+/// Picks the syntax for a file from its name and the start of its content.
+pub(super) fn detect_syntax(path: &Path, content: &str) -> Option<&'static SyntaxReference> {
+    let lines: Vec<&str> = content.lines().collect();
+    let head = &lines[..lines.len().min(MODELINE_LINES)];
+    let tail = &lines[lines.len().saturating_sub(MODELINE_LINES)..];
+
+    head.iter()
+        .chain(tail)
+        .find_map(|line| modeline_language(line))
+        .and_then(|language| syntax_for_token(&language))
+        .or_else(|| syntax_for_name(path))
+        .or_else(|| {
+            let first = lines.first()?;
+            syntaxes()
+                .find_syntax_by_first_line(first)
+                .or_else(|| syntax_for_token(shebang_interpreter(first)?))
+        })
+        .filter(|syntax| !is_plain_text(syntax))
+}
+
+/// CSS classes for a scope stack, e.g. `keyword control rust`; empty for plain text.
+fn scope_class(stack: &ScopeStack) -> String {
+    let meta = Scope::new("meta").ok();
+    stack
+        .as_slice()
+        .iter()
+        .rev()
+        // The bottom scope (`source.rust`) only names the language.
+        .take(stack.len().saturating_sub(1))
+        .find(|scope| meta.is_none_or(|meta| !meta.is_prefix_of(**scope)))
+        .map(|scope| scope.build_string().replace('.', " "))
+        .unwrap_or_default()
+}
+
+/// Splits `content` into lines of tokens highlighted with `syntax`.
+///
+/// Returns `None` if the grammar fails on the input.
+pub(super) fn highlight(
+    content: &str,
+    syntax: &SyntaxReference,
+) -> Option<Vec<Vec<HighlightToken>>> {
+    let set = syntaxes();
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut lines = Vec::new();
+
+    for line in content.split_inclusive('\n') {
+        let ops = state.parse_line(line, set).ok()?;
+        let mut tokens: Vec<HighlightToken> = Vec::new();
+
+        for (text, op) in ScopeRegionIterator::new(&ops, line) {
+            stack.apply(op).ok()?;
+            let text = text.trim_end_matches(['\n', '\r']);
+            if text.is_empty() {
+                continue;
+            }
+
+            let class = scope_class(&stack);
+            match tokens.last_mut() {
+                Some(last) if last.class == class => last.text.push_str(text),
+                _ => tokens.push(HighlightToken {
+                    text: text.to_string(),
+                    class,
+                }),
+            }
+        }
+        lines.push(tokens);
+    }
+
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modelines_name_the_language() {
+        assert_eq!(
+            modeline_language("# vim: set ft=python ts=4:").as_deref(),
+            Some("python")
+        );
+        assert_eq!(
+            modeline_language("// vi: filetype=rust").as_deref(),
+            Some("rust")
+        );
+        assert_eq!(
+            modeline_language("# -*- mode: ruby; coding: utf-8 -*-").as_deref(),
+            Some("ruby")
+        );
+        assert_eq!(
+            modeline_language(";; -*- lisp -*-").as_deref(),
+            Some("lisp")
+        );
+        assert_eq!(modeline_language("# -*- coding: utf-8 -*-"), None);
+        assert_eq!(modeline_language("evil: ft=nope"), None);
+    }
+
+    #[test]
+    fn shebangs_skip_env() {
+        assert_eq!(shebang_interpreter("#!/bin/bash"), Some("bash"));
+        assert_eq!(
+            shebang_interpreter("#!/usr/bin/env -S python3 -u"),
+            Some("python3")
+        );
+        assert_eq!(shebang_interpreter("#!/usr/bin/env"), None);
+        assert_eq!(shebang_interpreter("echo"), None);
+    }
+
+    #[test]
+    fn detection_prefers_modeline_then_name_then_first_line() {
+        let name = |path: &str, content: &str| {
+            detect_syntax(Path::new(path), content).map(|s| s.name.as_str())
+        };
+
+        assert_eq!(name("/x/main.rs", "fn main() {}"), Some("Rust"));
+        assert_eq!(name("/x/Makefile", "all:\n"), Some("Makefile"));
+        assert_eq!(
+            name("/x/run", "#!/usr/bin/env python3\nprint(1)\n"),
+            Some("Python")
+        );
+        assert_eq!(
+            name("/x/run", "#!/bin/zsh\necho hi\n"),
+            Some("Bourne Again Shell (bash)")
+        );
+        assert_eq!(
+            name("/x/notes.txt", "# vim: ft=ruby\nputs 1\n"),
+            Some("Ruby")
+        );
+        assert_eq!(name("/x/notes.txt", "just text"), None);
+    }
+
+    #[test]
+    fn tokens_cover_every_line_and_carry_classes() {
+        let syntax = syntax_for_token("rust").unwrap();
+        let content = "// hi\nfn main() {\n    let s = \"x\";\n}\n";
+        let lines = highlight(content, syntax).unwrap();
+
+        assert_eq!(lines.len(), 4);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|tokens| tokens.iter().map(|t| t.text.as_str()).collect())
+            .collect();
+        assert_eq!(text, content.lines().collect::<Vec<_>>());
+
+        let has_class =
+            |token: &HighlightToken, class: &str| token.class.split(' ').any(|c| c == class);
+        // Classes are scope atoms, so `.comment` styles the comment's punctuation too.
+        assert!(lines[0].iter().all(|t| has_class(t, "comment")));
+        assert!(lines[2]
+            .iter()
+            .any(|t| t.class.starts_with("string") && t.text.contains('x')));
+        assert!(lines[1]
+            .iter()
+            .any(|t| t.text == "fn" && t.class.contains("storage")));
+    }
+}
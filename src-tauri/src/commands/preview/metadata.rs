@@ -0,0 +1,353 @@
+//! Image metadata: EXIF camera settings, capture time, GPS position and
+//! orientation, XMP descriptions, and the pixel format.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use exif::{Exif, In, Tag, Value};
+use image::codecs::{bmp, gif, ico, jpeg, png, tiff, webp};
+use image::io::Reader as ImageReader;
+use image::{ColorType, DynamicImage, ImageDecoder, ImageFormat};
+use quick_xml::events::{BytesStart, Event};
+
+use crate::constants::{MAX_THUMBNAIL_PIXELS, XMP_SCAN_LENGTH};
+use crate::error::{FileManagerError, Result};
+use crate::models::{GpsPosition, ImageMetadata};
+use crate::utils::get_extension;
+
+use super::raster::{self, ImageKind};
+use crate::commands::office::{attribute, local_name};
+
+/// EXIF orientation of an image held in memory; `None` when it has no EXIF.
+pub(super) fn exif_orientation(data: &[u8]) -> Option<u32> {
+    read_exif(&mut Cursor::new(data)).and_then(|exif| orientation(&exif))
+}
+
+fn read_exif<R: BufRead + Seek>(reader: &mut R) -> Option<Exif> {
+    exif::Reader::new().read_from_container(reader).ok()
+}
+
+fn orientation(exif: &Exif) -> Option<u32> {
+    exif.get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .filter(|value| (1..=8).contains(value))
+}
+
+/// Rotates and mirrors a decoded image so it displays upright.
+pub(super) fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+fn exif_text(exif: &Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Ascii(ref parts) = field.value else {
+        return None;
+    };
+    let text = String::from_utf8_lossy(parts.first()?).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn exif_number(exif: &Exif, tag: Tag) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let number = match field.value {
+        Value::Rational(ref values) => values.first()?.to_f64(),
+        Value::SRational(ref values) => values.first()?.to_f64(),
+        _ => f64::from(field.value.get_uint(0)?),
+    };
+    number.is_finite().then_some(number)
+}
+
+/// Capture time as `YYYY-MM-DDTHH:MM:SS`, followed by the UTC offset when the
+/// camera recorded one.
+fn capture_time(exif: &Exif) -> Option<String> {
+    let field = exif
+        .get_field(Tag::DateTimeOriginal, In::PRIMARY)
+        .or_else(|| exif.get_field(Tag::DateTime, In::PRIMARY))?;
+    let Value::Ascii(ref parts) = field.value else {
+        return None;
+    };
+    let mut time = exif::DateTime::from_ascii(parts.first()?).ok()?;
+    if let Some(offset) = exif.get_field(Tag::OffsetTimeOriginal, In::PRIMARY) {
+        if let Value::Ascii(ref parts) = offset.value {
+            if let Some(data) = parts.first() {
+                let _ = time.parse_offset(data);
+            }
+        }
+    }
+
+    let mut text = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    );
+    if let Some(offset) = time.offset {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let offset = offset.unsigned_abs();
+        text.push_str(&format!("{sign}{:02}:{:02}", offset / 60, offset % 60));
+    }
+    Some(text)
+}
+
+/// A GPS coordinate in decimal degrees from degree/minute/second rationals.
+fn gps_degrees(exif: &Exif, tag: Tag, reference: Tag, negative: &str) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Rational(ref parts) = field.value else {
+        return None;
+    };
+    let degrees = parts
+        .iter()
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(part, scale)| part.to_f64() / scale)
+        .sum::<f64>();
+    let sign = match exif_text(exif, reference) {
+        Some(r) if r.eq_ignore_ascii_case(negative) => -1.0,
+        _ => 1.0,
+    };
+    degrees.is_finite().then_some(sign * degrees)
+}
+
+fn gps_position(exif: &Exif) -> Option<GpsPosition> {
+    let latitude = gps_degrees(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?;
+    let longitude = gps_degrees(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?;
+    let below_sea_level = exif
+        .get_field(Tag::GPSAltitudeRef, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        == Some(1);
+    let altitude =
+        exif_number(exif, Tag::GPSAltitude)
+            .map(|altitude| if below_sea_level { -altitude } else { altitude });
+    Some(GpsPosition {
+        latitude,
+        longitude,
+        altitude,
+    })
+}
+
+/// Fills the camera fields of `metadata` from EXIF.
+fn apply_exif(metadata: &mut ImageMetadata, exif: &Exif) {
+    metadata.orientation = orientation(exif);
+    metadata.camera_make = exif_text(exif, Tag::Make);
+    metadata.camera_model = exif_text(exif, Tag::Model);
+    metadata.lens = exif_text(exif, Tag::LensModel);
+    metadata.exposure_time = exif
+        .get_field(Tag::ExposureTime, In::PRIMARY)
+        .map(|field| field.display_value().to_string());
+    metadata.f_number = exif_number(exif, Tag::FNumber);
+    metadata.iso = exif
+        .get_field(Tag::PhotographicSensitivity, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0));
+    metadata.focal_length = exif_number(exif, Tag::FocalLength);
+    metadata.captured_at = capture_time(exif);
+    metadata.gps = gps_position(exif);
+}
+
+/// Descriptive fields of an XMP packet.
+#[derive(Default)]
+struct XmpReader {
+    /// Local name of the `dc:` or `xmp:` property being read.
+    property: Option<&'static str>,
+    title: Option<String>,
+    description: Option<String>,
+    creator: Option<String>,
+    keywords: Vec<String>,
+    rating: Option<i32>,
+}
+
+impl XmpReader {
+    fn property(local: &[u8]) -> Option<&'static str> {
+        match local {
+            b"title" => Some("title"),
+            b"description" => Some("description"),
+            b"creator" => Some("creator"),
+            b"subject" => Some("subject"),
+            b"Rating" => Some("Rating"),
+            _ => None,
+        }
+    }
+
+    /// `xmp:Rating` is often written as an attribute of `rdf:Description`.
+    fn description_attributes(&mut self, e: &BytesStart) {
+        if let Some(rating) = attribute(e, b"Rating") {
+            self.value("Rating", &rating);
+        }
+    }
+
+    fn value(&mut self, property: &str, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        match property {
+            "title" => {
+                self.title.get_or_insert_with(|| text.to_string());
+            }
+            "description" => {
+                self.description.get_or_insert_with(|| text.to_string());
+            }
+            "creator" => {
+                self.creator.get_or_insert_with(|| text.to_string());
+            }
+            "subject" => self.keywords.push(text.to_string()),
+            "Rating" => self.rating = text.parse::<f64>().ok().map(|r| r.round() as i32),
+            _ => {}
+        }
+    }
+
+    /// Reads the packet; properties hold a plain value or an `rdf:Alt`,
+    /// `rdf:Seq` or `rdf:Bag` of `rdf:li` items.
+    fn read(mut self, xml: &str) -> Self {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => match local_name(e.name().as_ref()) {
+                    b"Description" => self.description_attributes(e),
+                    local => {
+                        if let Some(property) = Self::property(local) {
+                            self.property = Some(property);
+                        }
+                    }
+                },
+                Ok(Event::Empty(ref e)) if local_name(e.name().as_ref()) == b"Description" => {
+                    self.description_attributes(e);
+                }
+                Ok(Event::End(ref e)) => match local_name(e.name().as_ref()) {
+                    local if Self::property(local).is_some() => self.property = None,
+                    _ => {}
+                },
+                Ok(Event::Text(ref e)) => {
+                    if let (Some(property), Ok(text)) = (self.property, e.unescape()) {
+                        self.value(property, &text);
+                    }
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+/// Finds an XMP packet in the first `XMP_SCAN_LENGTH` bytes of a file.
+fn find_xmp(data: &[u8]) -> Option<&str> {
+    let start = find(data, b"<x:xmpmeta")?;
+    let end = start + find(&data[start..], b"</x:xmpmeta>")? + b"</x:xmpmeta>".len();
+    std::str::from_utf8(&data[start..end]).ok()
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn apply_xmp(metadata: &mut ImageMetadata, xml: &str) {
+    let xmp = XmpReader::default().read(xml);
+    metadata.title = xmp.title;
+    metadata.description = xmp.description;
+    metadata.creator = xmp.creator;
+    metadata.keywords = xmp.keywords;
+    metadata.rating = xmp.rating;
+}
+
+/// Pixel format from the image header, decoding only formats without a
+/// header-only decoder.
+fn color_type(file: &mut BufReader<File>, format: ImageFormat) -> Option<ColorType> {
+    file.seek(SeekFrom::Start(0)).ok()?;
+    let color = match format {
+        ImageFormat::Png => png::PngDecoder::new(file).ok()?.color_type(),
+        ImageFormat::Jpeg => jpeg::JpegDecoder::new(file).ok()?.color_type(),
+        ImageFormat::Gif => gif::GifDecoder::new(file).ok()?.color_type(),
+        ImageFormat::Bmp => bmp::BmpDecoder::new(file).ok()?.color_type(),
+        ImageFormat::WebP => webp::WebPDecoder::new(file).ok()?.color_type(),
+        ImageFormat::Ico => ico::IcoDecoder::new(file).ok()?.color_type(),
+        ImageFormat::Tiff => tiff::TiffDecoder::new(file).ok()?.color_type(),
+        _ => ImageReader::with_format(file, format)
+            .decode()
+            .ok()?
+            .color(),
+    };
+    Some(color)
+}
+
+#[doc(hidden)]
+pub fn get_image_metadata_sync(path: &str) -> Result<ImageMetadata> {
+    let open = || File::open(path).map(BufReader::new);
+    let extension = get_extension(Path::new(path)).unwrap_or_default();
+    let Some(ImageKind::Raster(format)) = raster::sniff_file(Path::new(path), &extension) else {
+        return Err(FileManagerError::ReadFileError(format!(
+            "Not a raster image: {path}"
+        )));
+    };
+    let (width, height) = ImageReader::with_format(open()?, format)
+        .into_dimensions()
+        .map_err(|e| FileManagerError::ReadFileError(e.to_string()))?;
+
+    let mut file = open()?;
+    let color = if u64::from(width) * u64::from(height) <= MAX_THUMBNAIL_PIXELS {
+        color_type(&mut file, format)
+    } else {
+        None
+    };
+
+    let mut metadata = ImageMetadata {
+        format: format!("{format:?}"),
+        width,
+        height,
+        color_type: color.map(|c| format!("{c:?}")),
+        bit_depth: color.map(|c| (c.bits_per_pixel() / u16::from(c.channel_count())) as u8),
+        has_alpha: color.is_some_and(|c| c.has_alpha()),
+        ..ImageMetadata::default()
+    };
+
+    file.seek(SeekFrom::Start(0))?;
+    if let Some(exif) = read_exif(&mut file) {
+        apply_exif(&mut metadata, &exif);
+    }
+    // Orientations 5-8 swap the sides of the displayed image.
+    if metadata.orientation.is_some_and(|o| o >= 5) {
+        std::mem::swap(&mut metadata.width, &mut metadata.height);
+    }
+
+    let mut head = Vec::new();
+    open()?
+        .take(XMP_SCAN_LENGTH as u64)
+        .read_to_end(&mut head)?;
+    if let Some(xml) = find_xmp(&head) {
+        apply_xmp(&mut metadata, xml);
+    }
+
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xmp_reads_lists_alternatives_and_rating_attributes() {
+        let packet = br#"junk<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF>
+            <rdf:Description xmp:Rating="4">
+                <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Harbour</rdf:li></rdf:Alt></dc:title>
+                <dc:creator><rdf:Seq><rdf:li>Ana</rdf:li></rdf:Seq></dc:creator>
+                <dc:subject><rdf:Bag><rdf:li>boats</rdf:li><rdf:li>dusk</rdf:li></rdf:Bag></dc:subject>
+            </rdf:Description>
+        </rdf:RDF></x:xmpmeta>more junk"#;
+
+        let mut metadata = ImageMetadata::default();
+        apply_xmp(&mut metadata, find_xmp(packet).unwrap());
+        assert_eq!(metadata.title.as_deref(), Some("Harbour"));
+        assert_eq!(metadata.creator.as_deref(), Some("Ana"));
+        assert_eq!(metadata.keywords, vec!["boats", "dusk"]);
+        assert_eq!(metadata.rating, Some(4));
+        assert_eq!(metadata.description, None);
+    }
+}
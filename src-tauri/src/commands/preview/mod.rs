@@ -0,0 +1,441 @@
+//! File preview generation for text, images, and office documents.
+
+mod docx;
+mod embedded;
+mod hex;
+mod highlight;
+mod metadata;
+mod odf;
+mod paging;
+mod pdf;
+mod pptx;
+mod psd;
+mod raster;
+mod sniff;
+mod spreadsheet;
+mod svg;
+
+pub use hex::{read_bytes_range_sync, search_bytes_sync};
+pub use metadata::get_image_metadata_sync;
+pub use paging::{read_text_range_sync, TextIndexState};
+pub use spreadsheet::{read_sheet_range_sync, SheetCacheState};
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Manager};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::constants::{
+    DEFAULT_IMAGE_PREVIEW_SIDE, DOCUMENT_EXTENSIONS, IMAGE_PREVIEW_JPEG_QUALITY,
+    MAX_IMAGE_PREVIEW_FILE_SIZE, MAX_IMAGE_PREVIEW_SIDE, MAX_PREVIEW_FILE_SIZE,
+    MAX_TEXT_PREVIEW_LENGTH, MAX_THUMBNAIL_PIXELS, MIN_THUMBNAIL_SIDE, PDF_EXTENSIONS,
+    PRESENTATION_EXTENSIONS, SPREADSHEET_EXTENSIONS, TEXT_EXTENSIONS,
+};
+use crate::models::{
+    BytePattern, ByteSearchResult, FilePreview, HexChunk, ImageMetadata, SheetRange, TextChunk,
+    TextRangeRequest,
+};
+use crate::utils::get_extension;
+
+use sniff::Sniffed;
+
+/// Generates a preview for a file.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_file_preview(path: String) -> Result<FilePreview, String> {
+    let path_clone = path.clone();
+    spawn_blocking(move || get_file_preview_sync(&path_clone))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[doc(hidden)]
+pub fn get_file_preview_sync(path: &str) -> Result<FilePreview, String> {
+    let file_path = Path::new(path);
+    let extension = get_extension(file_path).unwrap_or_default();
+
+    // The head is read once for both the image and the text checks.
+    let head = sniff::read_head(file_path);
+
+    // Image files, recognized by content; ones that fail to decode are shown as binary
+    let image = head
+        .as_deref()
+        .ok()
+        .and_then(|head| raster::detect(head, &extension));
+    if let Some(kind) = image {
+        let viewport = (DEFAULT_IMAGE_PREVIEW_SIDE, DEFAULT_IMAGE_PREVIEW_SIDE);
+        if let Ok(preview) = generate_image_preview(path, kind, viewport) {
+            return Ok(preview);
+        }
+    }
+
+    // Office documents
+    if DOCUMENT_EXTENSIONS.contains(&extension.as_str()) {
+        return docx::generate_document_preview(path);
+    }
+
+    if SPREADSHEET_EXTENSIONS.contains(&extension.as_str()) {
+        return spreadsheet::generate_spreadsheet_preview(path);
+    }
+
+    if PRESENTATION_EXTENSIONS.contains(&extension.as_str()) {
+        return pptx::generate_presentation_preview(path);
+    }
+
+    if PDF_EXTENSIONS.contains(&extension.as_str()) {
+        return pdf::generate_pdf_preview(path);
+    }
+
+    if file_path.is_dir() {
+        return Ok(FilePreview::Unsupported {
+            mime: "inode/directory".to_string(),
+        });
+    }
+
+    // Everything else is previewed as text if its content (or name) says so
+    let named_text = TEXT_EXTENSIONS.contains(&extension.as_str())
+        || sniff::is_text_file_name(file_path)
+        || highlight::syntax_for_name(file_path).is_some();
+
+    let head = head.map_err(|e| e.to_string())?;
+    match sniff::sniff(file_path, &head, named_text) {
+        Sniffed::Text => generate_text_preview(path),
+        Sniffed::Binary { mime } => {
+            hex::generate_hex_preview(file_path, mime).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Reads a window of whole lines from a text file of any size.
+///
+/// Supports paging by byte offset, going to a line and jumping to the end.
+#[tauri::command]
+#[specta::specta]
+pub async fn read_text_range(
+    path: String,
+    range: TextRangeRequest,
+    app: AppHandle,
+) -> Result<TextChunk, String> {
+    spawn_blocking(move || read_text_range_sync(&path, &range, &app.state::<TextIndexState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+/// Reads raw bytes of any file as hex dump rows, for paging through binaries.
+#[tauri::command]
+#[specta::specta]
+pub async fn read_bytes_range(path: String, offset: u64, length: u32) -> Result<HexChunk, String> {
+    spawn_blocking(move || read_bytes_range_sync(&path, offset, length))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+/// Finds the offsets of a byte sequence or string in a file.
+///
+/// Pass the returned `next_offset` as `from` to continue past `limit` matches.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_bytes(
+    path: String,
+    pattern: BytePattern,
+    from: Option<u64>,
+    limit: Option<u32>,
+) -> Result<ByteSearchResult, String> {
+    spawn_blocking(move || search_bytes_sync(&path, &pattern, from, limit))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+/// Reads a page of rows from one sheet of a spreadsheet.
+///
+/// `row_offset` is a 0-based sheet row, as in `SpreadsheetSheet::row_offset`.
+#[tauri::command]
+#[specta::specta]
+pub async fn read_sheet_range(
+    path: String,
+    sheet: String,
+    row_offset: u32,
+    rows: u32,
+    app: AppHandle,
+) -> Result<SheetRange, String> {
+    spawn_blocking(move || {
+        read_sheet_range_sync(
+            &path,
+            &sheet,
+            row_offset,
+            rows,
+            &app.state::<SheetCacheState>(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(Into::into)
+}
+
+/// Reads the pixel format and EXIF/XMP metadata of an image.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_image_metadata(path: String) -> Result<ImageMetadata, String> {
+    spawn_blocking(move || get_image_metadata_sync(&path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+/// Generates a text preview with truncation.
+///
+/// Invalid UTF-8 is replaced rather than rejected, since sniffing only looked
+/// at the start of the file.
+fn generate_text_preview(path: &str) -> Result<FilePreview, String> {
+    // A character takes at most 4 bytes, so this covers the character budget.
+    let byte_limit = (MAX_TEXT_PREVIEW_LENGTH * 4) as u64;
+    let mut bytes = Vec::new();
+    fs::File::open(path)
+        .map_err(|e| e.to_string())?
+        .take(byte_limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    let more_bytes = bytes.len() as u64 > byte_limit;
+    bytes.truncate(byte_limit as usize);
+    let text = String::from_utf8_lossy(&bytes);
+
+    let mut chars = text.chars();
+    let content: String = chars.by_ref().take(MAX_TEXT_PREVIEW_LENGTH).collect();
+    let truncated = more_bytes || chars.next().is_some();
+
+    // Only the truncated content is highlighted, so the preview budget bounds the work.
+    let syntax = highlight::detect_syntax(Path::new(path), &content);
+    let highlighted = syntax.and_then(|syntax| highlight::highlight(&content, syntax));
+    let language = syntax
+        .filter(|_| highlighted.is_some())
+        .map(|syntax| syntax.name.clone());
+
+    Ok(FilePreview::Text {
+        language,
+        highlighted,
+        content,
+        truncated,
+    })
+}
+
+/// Generates an image preview fitted into a viewport of `max_width` x `max_height`.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_image_preview(
+    path: String,
+    max_width: u32,
+    max_height: u32,
+) -> Result<FilePreview, String> {
+    spawn_blocking(move || get_image_preview_sync(&path, max_width, max_height))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[doc(hidden)]
+pub fn get_image_preview_sync(
+    path: &str,
+    max_width: u32,
+    max_height: u32,
+) -> Result<FilePreview, String> {
+    let file_path = Path::new(path);
+    let extension = get_extension(file_path).unwrap_or_default();
+    let kind =
+        raster::sniff_file(file_path, &extension).ok_or_else(|| format!("Not an image: {path}"))?;
+    let viewport = (
+        max_width.clamp(MIN_THUMBNAIL_SIDE, MAX_IMAGE_PREVIEW_SIDE),
+        max_height.clamp(MIN_THUMBNAIL_SIDE, MAX_IMAGE_PREVIEW_SIDE),
+    );
+    generate_image_preview(path, kind, viewport)
+}
+
+/// Decodes an image and downscales it to fit `viewport`, so previews of large
+/// photos stay small over IPC.
+///
+/// GIFs that already fit are sent as they are to keep their animation.
+fn generate_image_preview(
+    path: &str,
+    kind: raster::ImageKind,
+    (max_width, max_height): (u32, u32),
+) -> Result<FilePreview, String> {
+    let too_large = || {
+        Ok(FilePreview::Unsupported {
+            mime: format!("{} (too large)", kind.mime()),
+        })
+    };
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_IMAGE_PREVIEW_FILE_SIZE {
+        return too_large();
+    }
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+
+    if let Some((w, h)) = raster::pixel_size(&bytes, kind)? {
+        if u64::from(w) * u64::from(h) > MAX_THUMBNAIL_PIXELS {
+            return too_large();
+        }
+        if kind == raster::ImageKind::Raster(image::ImageFormat::Gif)
+            && bytes.len() <= MAX_PREVIEW_FILE_SIZE
+            && w <= max_width
+            && h <= max_height
+        {
+            return Ok(FilePreview::Image {
+                base64: STANDARD.encode(&bytes),
+                mime: kind.mime().to_string(),
+            });
+        }
+    }
+
+    // Rotated photos are turned upright after scaling, so the viewport is
+    // turned the other way for them.
+    let orientation = metadata::exif_orientation(&bytes).filter(|&o| o > 1);
+    let (fit_width, fit_height) = match orientation {
+        Some(o) if o >= 5 => (max_height, max_width),
+        _ => (max_width, max_height),
+    };
+    let img = raster::decode(&bytes, kind, fit_width.max(fit_height))?;
+    let img = if img.width() > fit_width || img.height() > fit_height {
+        img.resize(fit_width, fit_height, image::imageops::FilterType::Triangle)
+    } else {
+        img
+    };
+    let img = match orientation {
+        Some(orientation) => metadata::apply_orientation(img, orientation),
+        None => img,
+    };
+
+    let (base64, mime) = encode_preview(&img)?;
+    Ok(FilePreview::Image {
+        base64,
+        mime: mime.to_string(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Encodes a downscaled image preview as lossless WebP when it has
+/// transparency and as JPEG otherwise.
+///
+/// Returns the base64 data and its MIME type.
+pub(super) fn encode_preview(img: &image::DynamicImage) -> Result<(String, &'static str), String> {
+    let mut buf: Vec<u8> = Vec::new();
+    let mime = if img.color().has_alpha() {
+        let rgba = img.to_rgba8();
+        image::codecs::webp::WebPEncoder::new_lossless(&mut buf)
+            .encode(&rgba, rgba.width(), rgba.height(), image::ColorType::Rgba8)
+            .map_err(|e| e.to_string())?;
+        "image/webp"
+    } else {
+        let rgb = img.to_rgb8();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, IMAGE_PREVIEW_JPEG_QUALITY)
+            .encode_image(&rgb)
+            .map_err(|e| e.to_string())?;
+        "image/jpeg"
+    };
+    Ok((STANDARD.encode(&buf), mime))
+}
+
+/// MIME type reported for an office file over `MAX_OFFICE_FILE_SIZE`.
+fn too_large_mime(path: &str) -> String {
+    format!(
+        "{} (too large)",
+        mime_guess::from_path(path).first_or_octet_stream()
+    )
+}
+
+/// True for OpenDocument files (.odt, .ods, .odp).
+fn is_open_document(path: &str) -> bool {
+    get_extension(Path::new(path)).is_some_and(|ext| ext.starts_with("od"))
+}
+
+/// Generates a thumbnail (resized image) as base64 with given max side length.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_thumbnail(
+    path: String,
+    max_side: u32,
+) -> Result<crate::models::Thumbnail, String> {
+    spawn_blocking(move || get_thumbnail_sync(&path, max_side))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[doc(hidden)]
+pub fn get_thumbnail_sync(path: &str, max_side: u32) -> Result<crate::models::Thumbnail, String> {
+    use image::imageops::FilterType;
+
+    use crate::constants::{
+        EMBEDDED_THUMBNAIL_EXTENSIONS, MAX_THUMBNAIL_FILE_SIZE, MAX_THUMBNAIL_SIDE,
+        MIN_THUMBNAIL_SIDE,
+    };
+
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("Empty path".to_string());
+    }
+
+    let file_path = Path::new(path);
+    let extension = get_extension(file_path).unwrap_or_default();
+
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    if !meta.is_file() {
+        return Err("Not a file".to_string());
+    }
+
+    // Archives are only opened to read the one image entry, so the size limit
+    // applies to that entry instead of the whole file.
+    let (data, kind) = if EMBEDDED_THUMBNAIL_EXTENSIONS.contains(&extension.as_str()) {
+        let data = embedded::embedded_image(path, &extension)?
+            .ok_or_else(|| "No embedded thumbnail".to_string())?;
+        let kind = raster::detect(&data, "");
+        (data, kind)
+    } else {
+        let Some(kind) = raster::sniff_file(file_path, &extension) else {
+            return Err(format!(
+                "Unsupported image format for thumbnail: {extension}"
+            ));
+        };
+        if meta.len() > MAX_THUMBNAIL_FILE_SIZE {
+            return Err("File is too large for thumbnail generation".to_string());
+        }
+        (fs::read(path).map_err(|e| e.to_string())?, Some(kind))
+    };
+    let kind = kind.ok_or_else(|| "Unsupported embedded thumbnail format".to_string())?;
+
+    let mut max_side = max_side.clamp(MIN_THUMBNAIL_SIDE, MAX_THUMBNAIL_SIDE);
+
+    let img = raster::decode(&data, kind, max_side)?;
+
+    let max_dim = img.width().max(img.height());
+    if max_side > max_dim {
+        max_side = max_dim.max(MIN_THUMBNAIL_SIDE);
+    }
+
+    let resized = img.resize(max_side, max_side, FilterType::Lanczos3);
+    let resized = match metadata::exif_orientation(&data) {
+        Some(orientation) => metadata::apply_orientation(resized, orientation),
+        None => resized,
+    };
+
+    let mut buf: Vec<u8> = Vec::new();
+    resized
+        .write_to(
+            &mut std::io::Cursor::new(&mut buf),
+            image::ImageOutputFormat::Png,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let base64 = STANDARD.encode(&buf);
+    let mime = "image/png".to_string();
+
+    Ok(crate::models::Thumbnail { base64, mime })
+}
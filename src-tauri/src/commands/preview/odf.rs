@@ -0,0 +1,197 @@
+//! OpenDocument (ODT, ODP) previews from an archive's `content.xml`.
+//!
+//! Both formats share the same text model, read by
+//! [`walk_odf`](crate::commands::office::walk_odf).
+
+use std::fs;
+use std::io::Read;
+use std::ops::ControlFlow;
+
+use crate::constants::{MAX_DOCUMENT_PARAGRAPHS, MAX_PRESENTATION_SLIDES};
+use crate::models::preview::{DocParagraph, PresentationSlide};
+use crate::models::FilePreview;
+
+use crate::commands::office::{attribute, local_name, walk_odf, OdfEvent};
+
+/// Elements whose text isn't body text: footnotes, comments, speaker notes
+/// and tracked deletions.
+const SKIPPED_ELEMENTS: &[&[u8]] = &[b"note", b"annotation", b"notes", b"tracked-changes"];
+
+/// Reads `content.xml` from an OpenDocument archive.
+fn content_xml(path: &str) -> Result<String, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Invalid OpenDocument file: {e}"))?;
+    let mut entry = archive
+        .by_name("content.xml")
+        .map_err(|e| format!("Missing content.xml: {e}"))?;
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml).map_err(|e| e.to_string())?;
+    Ok(xml)
+}
+
+fn heading_style(level: u32) -> String {
+    match level {
+        0 | 1 => "heading1",
+        2 => "heading2",
+        _ => "heading3",
+    }
+    .to_string()
+}
+
+/// Paragraphs of an ODT text document, and whether they were cut off.
+fn odt_paragraphs(xml: &str) -> Result<(Vec<DocParagraph>, bool), String> {
+    let mut paragraphs = Vec::new();
+    let mut truncated = false;
+    let mut list_depth = 0usize;
+
+    walk_odf(xml, SKIPPED_ELEMENTS, |event| {
+        match event {
+            OdfEvent::Start(e) if local_name(e.name().as_ref()) == b"list-item" => {
+                list_depth += 1;
+            }
+            OdfEvent::End(b"list-item") => list_depth = list_depth.saturating_sub(1),
+            OdfEvent::Paragraph { text, heading } => {
+                let style = match heading {
+                    Some(level) => heading_style(level),
+                    None if list_depth > 0 => "listItem".to_string(),
+                    None => "normal".to_string(),
+                };
+                paragraphs.push(DocParagraph {
+                    text,
+                    style,
+                    runs: Vec::new(),
+                    table: None,
+                });
+                if paragraphs.len() >= MAX_DOCUMENT_PARAGRAPHS {
+                    truncated = true;
+                    return ControlFlow::Break(());
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    })?;
+
+    Ok((paragraphs, truncated))
+}
+
+/// Slides of an ODP presentation, titled by their title placeholder frame.
+fn odp_slides(xml: &str) -> Result<Vec<PresentationSlide>, String> {
+    let mut slides: Vec<PresentationSlide> = Vec::new();
+    // One entry per open `draw:frame`: whether it holds the slide title.
+    let mut frames: Vec<bool> = Vec::new();
+    let mut title_parts: Vec<String> = Vec::new();
+
+    walk_odf(xml, SKIPPED_ELEMENTS, |event| {
+        match event {
+            OdfEvent::Start(e) => match local_name(e.name().as_ref()) {
+                b"page" => {
+                    if slides.len() >= MAX_PRESENTATION_SLIDES {
+                        return ControlFlow::Break(());
+                    }
+                    slides.push(PresentationSlide {
+                        number: slides.len() as u32 + 1,
+                        ..PresentationSlide::default()
+                    });
+                }
+                b"frame" => frames.push(attribute(e, b"class").as_deref() == Some("title")),
+                _ => {}
+            },
+            OdfEvent::End(b"frame") => {
+                let was_title = frames.pop() == Some(true);
+                if let Some(slide) = slides.last_mut().filter(|_| was_title) {
+                    if slide.title.is_none() && !title_parts.is_empty() {
+                        slide.title = Some(title_parts.join(" "));
+                    }
+                }
+                if was_title {
+                    title_parts.clear();
+                }
+            }
+            OdfEvent::Paragraph { text, .. } => {
+                if frames.iter().any(|&title| title) {
+                    title_parts.push(text);
+                } else if let Some(slide) = slides.last_mut() {
+                    slide.texts.push(text);
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    })?;
+
+    Ok(slides)
+}
+
+/// Generates a preview for an .odt file.
+pub(super) fn generate_odt_preview(path: &str) -> Result<FilePreview, String> {
+    let (paragraphs, truncated) = odt_paragraphs(&content_xml(path)?)?;
+    Ok(FilePreview::Document {
+        paragraphs,
+        truncated,
+        footnotes: Vec::new(),
+        images: Vec::new(),
+    })
+}
+
+/// Generates a preview for an .odp file.
+pub(super) fn generate_odp_preview(path: &str) -> Result<FilePreview, String> {
+    Ok(FilePreview::Presentation {
+        slides: odp_slides(&content_xml(path)?)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odt_paragraphs_keep_headings_lists_and_spacing() {
+        let xml = r#"<office:document-content><office:body><office:text>
+            <text:h text:outline-level="2">Plan</text:h>
+            <text:p>Two<text:s text:c="2"/>spaces<text:tab/>and <text:span>spans</text:span></text:p>
+            <text:list><text:list-item><text:p>First item</text:p></text:list-item></text:list>
+            <text:p>Noted<text:note><text:note-body><text:p>Footnote</text:p></text:note-body></text:note></text:p>
+            <text:p/>
+        </office:text></office:body></office:document-content>"#;
+
+        let (paragraphs, truncated) = odt_paragraphs(xml).unwrap();
+        let got: Vec<_> = paragraphs
+            .iter()
+            .map(|p| (p.text.as_str(), p.style.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("Plan", "heading2"),
+                ("Two  spaces\tand spans", "normal"),
+                ("First item", "listItem"),
+                ("Noted", "normal"),
+            ]
+        );
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn odp_slides_split_titles_from_body_text() {
+        let xml = r#"<office:presentation>
+            <draw:page draw:name="page1">
+                <draw:frame presentation:class="title"><draw:text-box><text:p>Welcome</text:p></draw:text-box></draw:frame>
+                <draw:frame presentation:class="outline"><draw:text-box>
+                    <text:list><text:list-item><text:p>Point one</text:p></text:list-item></text:list>
+                </draw:text-box></draw:frame>
+                <presentation:notes><draw:frame><draw:text-box><text:p>Speaker notes</text:p></draw:text-box></draw:frame></presentation:notes>
+            </draw:page>
+            <draw:page draw:name="page2"><draw:frame><draw:text-box><text:p>Untitled body</text:p></draw:text-box></draw:frame></draw:page>
+        </office:presentation>"#;
+
+        let slides = odp_slides(xml).unwrap();
+        assert_eq!(slides.len(), 2);
+        assert_eq!(slides[0].title.as_deref(), Some("Welcome"));
+        assert_eq!(slides[0].texts, vec!["Point one"]);
+        assert_eq!(slides[1].number, 2);
+        assert_eq!(slides[1].title, None);
+        assert_eq!(slides[1].texts, vec!["Untitled body"]);
+    }
+}
@@ -0,0 +1,397 @@
+//! Paged reading of text files too large to preview at once.
+//!
+//! A sparse index records the byte offset of every `LINE_INDEX_INTERVAL`th
+//! line. It is only built as far as a request needs, so going to a line near
+//! the start of a multi-GB log stays cheap, and it is kept across requests.
+//! A kept index is reused while the file is the same one (same device and
+//! inode on unix) and has only been appended to, i.e. it didn't shrink and the
+//! bytes just before the indexed part are unchanged. A rotated, truncated or
+//! rewritten file is reindexed.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::constants::{LINE_INDEX_INTERVAL, MAX_CACHED_LINE_INDEXES, MAX_TEXT_RANGE_BYTES};
+use crate::error::{FileManagerError, Result};
+use crate::models::{TextChunk, TextRangeRequest};
+use crate::utils::validate_absolute_path;
+
+/// Bytes read per step when scanning for newlines.
+const SCAN_CHUNK: usize = 64 * 1024;
+
+/// Bytes before the end of the indexed part compared to detect rewrites.
+const TAIL_CHECK_LEN: u64 = 64;
+
+/// Which file an index was built for, and its last known modification time.
+#[derive(PartialEq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    /// Device and inode; rotation replaces the file at a path with another one.
+    #[cfg(unix)]
+    id: (u64, u64),
+}
+
+impl FileStamp {
+    fn of(metadata: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            id: (metadata.dev(), metadata.ino()),
+        }
+    }
+
+    #[cfg(unix)]
+    fn same_file(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+
+    #[cfg(not(unix))]
+    fn same_file(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+struct LineIndex {
+    /// File size when the index was last used.
+    size: u64,
+    stamp: FileStamp,
+    /// The `TAIL_CHECK_LEN` bytes before `scanned_to` when the index was last used.
+    tail: Vec<u8>,
+    /// `checkpoints[k]` is the offset of 0-based line `k * LINE_INDEX_INTERVAL`.
+    checkpoints: Vec<u64>,
+    /// Everything before this offset has been scanned.
+    scanned_to: u64,
+    /// Newlines seen before `scanned_to`.
+    lines: u64,
+    ends_with_newline: bool,
+    last_used: u64,
+}
+
+impl LineIndex {
+    fn new(size: u64, stamp: FileStamp) -> Self {
+        Self {
+            size,
+            stamp,
+            tail: Vec::new(),
+            checkpoints: vec![0],
+            scanned_to: 0,
+            lines: 0,
+            ends_with_newline: false,
+            last_used: 0,
+        }
+    }
+
+    /// Scans forward until `done` holds or the whole file is indexed.
+    fn scan(&mut self, file: &mut File, mut done: impl FnMut(&Self) -> bool) -> io::Result<()> {
+        if self.scanned_to >= self.size || done(self) {
+            return Ok(());
+        }
+        file.seek(SeekFrom::Start(self.scanned_to))?;
+        let mut buf = vec![0; SCAN_CHUNK];
+
+        while self.scanned_to < self.size && !done(self) {
+            let want = ((self.size - self.scanned_to) as usize).min(SCAN_CHUNK);
+            let n = file.read(&mut buf[..want])?;
+            if n == 0 {
+                // Truncated while scanning; the next request reindexes.
+                self.size = self.scanned_to;
+                break;
+            }
+            for (i, _) in buf[..n].iter().enumerate().filter(|(_, &b)| b == b'\n') {
+                self.lines += 1;
+                if self.lines.is_multiple_of(LINE_INDEX_INTERVAL) {
+                    self.checkpoints.push(self.scanned_to + i as u64 + 1);
+                }
+            }
+            self.ends_with_newline = buf[n - 1] == b'\n';
+            self.scanned_to += n as u64;
+        }
+        Ok(())
+    }
+
+    /// Whether the index still describes `file`: unchanged, or only appended to.
+    fn is_current(&self, file: &mut File, size: u64, stamp: &FileStamp) -> io::Result<bool> {
+        if !self.stamp.same_file(stamp) || size < self.size {
+            return Ok(false);
+        }
+        if self.stamp == *stamp && size == self.size {
+            return Ok(true);
+        }
+        Ok(read_tail(file, self.scanned_to)? == self.tail)
+    }
+
+    fn complete(&self) -> bool {
+        self.scanned_to >= self.size
+    }
+
+    fn total_lines(&self) -> Option<u64> {
+        self.complete()
+            .then(|| self.lines + u64::from(self.size > 0 && !self.ends_with_newline))
+    }
+
+    /// Offset where 0-based `line` starts, or `None` past the end of the file.
+    fn line_offset(&mut self, file: &mut File, line: u64) -> io::Result<Option<u64>> {
+        self.scan(file, |index| index.lines >= line)?;
+        // With exactly `line` newlines seen, the line exists if anything follows the last one.
+        let exists = line < self.lines
+            || (line == self.lines
+                && !(self.complete() && (self.size == 0 || self.ends_with_newline)));
+        if !exists {
+            return Ok(None);
+        }
+        let checkpoint = self.checkpoints[(line / LINE_INDEX_INTERVAL) as usize];
+        skip_lines(file, checkpoint, line % LINE_INDEX_INTERVAL, self.size)
+    }
+
+    /// 1-based number of the line starting at `offset`, if the index reaches it.
+    fn line_number_at(&self, file: &mut File, offset: u64) -> io::Result<Option<u64>> {
+        if offset > self.scanned_to {
+            return Ok(None);
+        }
+        let k = self.checkpoints.partition_point(|&c| c <= offset) - 1;
+        let newlines = count_newlines(file, self.checkpoints[k], offset)?;
+        Ok(Some(k as u64 * LINE_INDEX_INTERVAL + newlines + 1))
+    }
+}
+
+/// Up to `TAIL_CHECK_LEN` bytes ending at `end`.
+fn read_tail(file: &mut File, end: u64) -> io::Result<Vec<u8>> {
+    let start = end.saturating_sub(TAIL_CHECK_LEN);
+    let mut tail = Vec::with_capacity((end - start) as usize);
+    file.seek(SeekFrom::Start(start))?;
+    file.take(end - start).read_to_end(&mut tail)?;
+    Ok(tail)
+}
+
+/// Offset just past the `n`th newline at or after `from`, or `None` if the file ends first.
+fn skip_lines(file: &mut File, from: u64, n: u64, size: u64) -> io::Result<Option<u64>> {
+    if n == 0 {
+        return Ok(Some(from));
+    }
+    file.seek(SeekFrom::Start(from))?;
+    let mut reader = BufReader::with_capacity(SCAN_CHUNK, file);
+    let mut offset = from;
+    let mut remaining = n;
+
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        let len = buf.len();
+        for (i, _) in buf.iter().enumerate().filter(|(_, &b)| b == b'\n') {
+            remaining -= 1;
+            if remaining == 0 {
+                let start = offset + i as u64 + 1;
+                return Ok((start < size).then_some(start));
+            }
+        }
+        offset += len as u64;
+        reader.consume(len);
+    }
+}
+
+fn count_newlines(file: &mut File, from: u64, to: u64) -> io::Result<u64> {
+    file.seek(SeekFrom::Start(from))?;
+    let mut reader = BufReader::with_capacity(SCAN_CHUNK, Read::by_ref(file).take(to - from));
+    let mut count = 0;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(count);
+        }
+        let len = buf.len();
+        count += buf.iter().filter(|&&b| b == b'\n').count() as u64;
+        reader.consume(len);
+    }
+}
+
+/// Reads whole lines from `offset` until `max_lines` lines or at least `soft_bytes` bytes.
+///
+/// A line longer than `MAX_TEXT_RANGE_BYTES` is cut off there.
+fn read_lines(
+    file: &mut File,
+    offset: u64,
+    max_lines: u64,
+    soft_bytes: u64,
+) -> io::Result<(Vec<u8>, u32)> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(Read::by_ref(file).take(MAX_TEXT_RANGE_BYTES));
+    let mut data = Vec::new();
+    let mut lines = 0;
+
+    while lines < max_lines && (data.len() as u64) < soft_bytes {
+        if reader.read_until(b'\n', &mut data)? == 0 {
+            break;
+        }
+        lines += 1;
+    }
+    Ok((data, lines as u32))
+}
+
+/// Offset of the first line starting at or after `offset`.
+fn snap_to_line(file: &mut File, offset: u64, size: u64) -> io::Result<u64> {
+    if offset == 0 || offset >= size {
+        return Ok(offset.min(size));
+    }
+    let mut previous = [0u8];
+    file.seek(SeekFrom::Start(offset - 1))?;
+    file.read_exact(&mut previous)?;
+    if previous[0] == b'\n' {
+        return Ok(offset);
+    }
+    Ok(skip_lines(file, offset, 1, size)?.unwrap_or(size))
+}
+
+/// Offset where the last `count` lines start, going back at most `MAX_TEXT_RANGE_BYTES`.
+fn tail_offset(file: &mut File, size: u64, count: u32) -> io::Result<u64> {
+    if count == 0 {
+        return Ok(size);
+    }
+    let floor = size.saturating_sub(MAX_TEXT_RANGE_BYTES);
+    let mut remaining = count;
+    let mut end = size;
+    let mut buf = vec![0; SCAN_CHUNK];
+
+    while end > floor {
+        let start = end.saturating_sub(SCAN_CHUNK as u64).max(floor);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+
+        for (i, &b) in chunk.iter().enumerate().rev() {
+            let at = start + i as u64;
+            // A trailing newline ends the last line rather than starting another.
+            if b == b'\n' && at + 1 < size {
+                remaining -= 1;
+                if remaining == 0 {
+                    return Ok(at + 1);
+                }
+            }
+        }
+        end = start;
+    }
+
+    if floor == 0 {
+        Ok(0)
+    } else {
+        snap_to_line(file, floor, size)
+    }
+}
+
+/// Line-offset indexes of recently paged files.
+#[derive(Default)]
+pub struct TextIndexState {
+    indexes: Mutex<HashMap<PathBuf, LineIndex>>,
+    tick: AtomicU64,
+}
+
+impl TextIndexState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the index for `path` out of the cache, so scans don't hold the lock.
+    ///
+    /// A fresh index is returned when the cached one no longer describes the file.
+    fn take(&self, path: &Path, file: &mut File, metadata: &fs::Metadata) -> Result<LineIndex> {
+        let cached = self
+            .indexes
+            .lock()
+            .map_err(|e| FileManagerError::IoError(e.to_string()))?
+            .remove(path);
+        let (size, stamp) = (metadata.len(), FileStamp::of(metadata));
+        Ok(match cached {
+            Some(mut index) if index.is_current(file, size, &stamp)? => {
+                index.size = size;
+                index.stamp = stamp;
+                index
+            }
+            _ => LineIndex::new(size, stamp),
+        })
+    }
+
+    fn put_back(&self, path: PathBuf, file: &mut File, mut index: LineIndex) -> Result<()> {
+        index.tail = read_tail(file, index.scanned_to)?;
+        index.last_used = self.tick.fetch_add(1, Ordering::Relaxed);
+        let mut indexes = self
+            .indexes
+            .lock()
+            .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+        indexes.insert(path, index);
+
+        if indexes.len() > MAX_CACHED_LINE_INDEXES {
+            let oldest = indexes
+                .iter()
+                .min_by_key(|(_, index)| index.last_used)
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                indexes.remove(&oldest);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn read_text_range_sync(
+    path: &str,
+    range: &TextRangeRequest,
+    state: &TextIndexState,
+) -> Result<TextChunk> {
+    validate_absolute_path(path)?;
+    let file_path = Path::new(path);
+    let metadata = fs::metadata(file_path)?;
+    if !metadata.is_file() {
+        return Err(FileManagerError::InvalidPath(format!("Not a file: {path}")));
+    }
+    let size = metadata.len();
+    let mut file = File::open(file_path)?;
+    let mut index = state.take(file_path, &mut file, &metadata)?;
+
+    let (start, data, line_count, first_line) = match *range {
+        TextRangeRequest::Bytes { offset, length } => {
+            let start = snap_to_line(&mut file, offset, size)?;
+            let (data, lines) = read_lines(&mut file, start, u64::MAX, u64::from(length).max(1))?;
+            let first_line = index.line_number_at(&mut file, start)?;
+            (start, data, lines, first_line)
+        }
+        TextRangeRequest::Lines { start, count } => {
+            let line = start.max(1);
+            match index.line_offset(&mut file, line - 1)? {
+                Some(offset) => {
+                    let (data, lines) =
+                        read_lines(&mut file, offset, u64::from(count), MAX_TEXT_RANGE_BYTES)?;
+                    (offset, data, lines, Some(line))
+                }
+                None => (size, Vec::new(), 0, None),
+            }
+        }
+        TextRangeRequest::End { count } => {
+            let start = tail_offset(&mut file, size, count)?;
+            let (data, lines) = read_lines(&mut file, start, u64::MAX, MAX_TEXT_RANGE_BYTES)?;
+            let first_line = index.line_number_at(&mut file, start)?;
+            (start, data, lines, first_line)
+        }
+    };
+
+    let chunk = TextChunk {
+        content: String::from_utf8_lossy(&data).into_owned(),
+        start_offset: start,
+        end_offset: start + data.len() as u64,
+        first_line,
+        line_count,
+        file_size: size,
+        total_lines: index.total_lines(),
+    };
+    state.put_back(file_path.to_path_buf(), &mut file, index)?;
+    Ok(chunk)
+}
@@ -0,0 +1,88 @@
+//! PDF preview: document info, outline and per-page text, via lopdf.
+
+use std::fs;
+
+use lopdf::{decode_text_string, Dictionary, Document, Object};
+
+use crate::constants::{MAX_OFFICE_FILE_SIZE, MAX_PDF_PAGES, MAX_PDF_PAGE_TEXT_LENGTH};
+use crate::models::preview::{PdfOutlineItem, PdfPage};
+use crate::models::FilePreview;
+
+/// The trailer's `/Info` dictionary, if any.
+fn info_dictionary(document: &Document) -> Option<&Dictionary> {
+    let info = document.trailer.get(b"Info").ok()?;
+    document.dereference(info).ok()?.1.as_dict().ok()
+}
+
+fn info_string(info: Option<&Dictionary>, key: &[u8]) -> Option<String> {
+    let value = info?.get(key).ok()?;
+    let text = match value {
+        Object::String(..) => decode_text_string(value).ok()?,
+        _ => return None,
+    };
+    let text = text.trim().trim_matches('\0').to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn outline(document: &Document) -> Vec<PdfOutlineItem> {
+    // Documents without bookmarks report an error rather than an empty outline.
+    document
+        .get_toc()
+        .map(|toc| {
+            toc.toc
+                .into_iter()
+                .map(|entry| PdfOutlineItem {
+                    title: entry.title.trim().to_string(),
+                    level: entry.level as u32,
+                    page: entry.page as u32,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Generates a preview for a .pdf file.
+pub(super) fn generate_pdf_preview(path: &str) -> Result<FilePreview, String> {
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    if meta.len() > MAX_OFFICE_FILE_SIZE {
+        return Ok(FilePreview::Unsupported {
+            mime: "application/pdf (too large)".to_string(),
+        });
+    }
+
+    let document = Document::load(path).map_err(|e| format!("Invalid PDF: {e}"))?;
+    let page_numbers = document.get_pages();
+    let info = info_dictionary(&document);
+
+    let mut truncated = page_numbers.len() > MAX_PDF_PAGES;
+    let pages = page_numbers
+        .keys()
+        .take(MAX_PDF_PAGES)
+        .map(|&number| {
+            // Pages using unsupported fonts or encodings are shown without text.
+            let text = document.extract_text(&[number]).unwrap_or_default();
+            let text = text.trim();
+            let text = match text.char_indices().nth(MAX_PDF_PAGE_TEXT_LENGTH) {
+                Some((cut, _)) => {
+                    truncated = true;
+                    &text[..cut]
+                }
+                None => text,
+            };
+            PdfPage {
+                number,
+                text: text.to_string(),
+            }
+        })
+        .collect();
+
+    Ok(FilePreview::Pdf {
+        page_count: page_numbers.len() as u32,
+        title: info_string(info, b"Title"),
+        author: info_string(info, b"Author"),
+        producer: info_string(info, b"Producer"),
+        pages,
+        outline: outline(&document),
+        truncated,
+    })
+}
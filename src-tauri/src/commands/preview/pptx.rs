@@ -0,0 +1,109 @@
+//! PPTX preview: slides in presentation order with their text, tables,
+//! speaker notes and downscaled pictures.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+
+use zip::ZipArchive;
+
+use crate::constants::{
+    MAX_OFFICE_FILE_SIZE, MAX_PRESENTATION_IMAGES, MAX_PRESENTATION_SLIDES, SLIDE_IMAGE_MAX_SIDE,
+};
+use crate::models::preview::{DocImage, PresentationSlide};
+use crate::models::FilePreview;
+
+use super::docx::load_image;
+use super::{is_open_document, odf, too_large_mime};
+use crate::commands::office::{
+    parse_slide, part_path, part_relationships, read_entry, slide_paths,
+};
+
+/// Reads slides and their notes, decoding each picture once even when it
+/// appears on several slides.
+struct DeckReader<'a> {
+    archive: &'a mut ZipArchive<File>,
+    images: HashMap<String, Option<DocImage>>,
+    loaded: usize,
+}
+
+impl DeckReader<'_> {
+    fn image(&mut self, path: String) -> Option<DocImage> {
+        if let Some(image) = self.images.get(&path) {
+            return image.clone();
+        }
+        let image = (self.loaded < MAX_PRESENTATION_IMAGES)
+            .then(|| load_image(self.archive, &path, SLIDE_IMAGE_MAX_SIDE, None))
+            .flatten();
+        if image.is_some() {
+            self.loaded += 1;
+        }
+        self.images.insert(path, image.clone());
+        image
+    }
+
+    fn slide(&mut self, number: u32, path: &str) -> Result<Option<PresentationSlide>, String> {
+        let Some(xml) = read_entry(self.archive, path) else {
+            return Ok(None);
+        };
+        let mut content = parse_slide(&xml)?;
+        let (dir, rels) = part_relationships(self.archive, path);
+        let (title, texts) = content.title_and_texts();
+
+        let notes = rels
+            .values()
+            .find(|rel| rel.kind.ends_with("/notesSlide"))
+            .map(|rel| part_path(&dir, &rel.target))
+            .and_then(|notes_path| read_entry(self.archive, &notes_path))
+            .and_then(|xml| parse_slide(&xml).ok())
+            .and_then(|notes| notes.notes());
+
+        let mut images = Vec::new();
+        for id in &content.pictures {
+            let Some(rel) = rels.get(id).filter(|rel| !rel.external) else {
+                continue;
+            };
+            images.extend(self.image(part_path(&dir, &rel.target)));
+        }
+
+        Ok(Some(PresentationSlide {
+            number,
+            title,
+            texts,
+            notes,
+            tables: content.tables,
+            images,
+        }))
+    }
+}
+
+/// Generates a preview for a .pptx file.
+///
+/// .odp files are handed to the OpenDocument parser.
+pub(super) fn generate_presentation_preview(path: &str) -> Result<FilePreview, String> {
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    if meta.len() > MAX_OFFICE_FILE_SIZE {
+        return Ok(FilePreview::Unsupported {
+            mime: too_large_mime(path),
+        });
+    }
+    if is_open_document(path) {
+        return odf::generate_odp_preview(path);
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Invalid PPTX: {e}"))?;
+    let paths = slide_paths(&mut archive);
+
+    let mut deck = DeckReader {
+        archive: &mut archive,
+        images: HashMap::new(),
+        loaded: 0,
+    };
+    let mut slides = Vec::new();
+    for slide_path in paths.iter().take(MAX_PRESENTATION_SLIDES) {
+        let number = slides.len() as u32 + 1;
+        slides.extend(deck.slide(number, slide_path)?);
+    }
+
+    Ok(FilePreview::Presentation { slides })
+}
@@ -0,0 +1,251 @@
+//! Flattened composite image of Photoshop (.psd/.psb) files.
+//!
+//! Photoshop stores a merged copy of all visible layers after the layer data
+//! when "Maximize compatibility" is on (the default). Only that composite is
+//! read; layers, masks and effects are ignored.
+
+use image::{DynamicImage, RgbaImage};
+
+use crate::constants::MAX_THUMBNAIL_PIXELS;
+
+const SIGNATURE: &[u8] = b"8BPS";
+
+/// Color modes from the file header.
+const MODE_GRAYSCALE: u16 = 1;
+const MODE_INDEXED: u16 = 2;
+const MODE_RGB: u16 = 3;
+const MODE_CMYK: u16 = 4;
+
+pub(super) fn is_psd(head: &[u8]) -> bool {
+    head.starts_with(SIGNATURE)
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| "Truncated PSD file".to_string())?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// A length-prefixed section.
+    fn section(&mut self, wide: bool) -> Result<&'a [u8], String> {
+        let len = if wide {
+            self.u64()?
+        } else {
+            u64::from(self.u32()?)
+        };
+        self.take(usize::try_from(len).map_err(|_| "Invalid PSD section".to_string())?)
+    }
+}
+
+struct Header {
+    /// PSB ("large document") files use 64-bit lengths and counts.
+    large: bool,
+    channels: usize,
+    width: u32,
+    height: u32,
+    depth: u16,
+    mode: u16,
+}
+
+fn header(cursor: &mut Cursor) -> Result<Header, String> {
+    if cursor.take(4)? != SIGNATURE {
+        return Err("Not a PSD file".to_string());
+    }
+    let large = match cursor.u16()? {
+        1 => false,
+        2 => true,
+        version => return Err(format!("Unsupported PSD version {version}")),
+    };
+    cursor.take(6)?;
+    let channels = usize::from(cursor.u16()?);
+    let height = cursor.u32()?;
+    let width = cursor.u32()?;
+    let depth = cursor.u16()?;
+    let mode = cursor.u16()?;
+    Ok(Header {
+        large,
+        channels,
+        width,
+        height,
+        depth,
+        mode,
+    })
+}
+
+/// Width and height from the header.
+pub(super) fn dimensions(data: &[u8]) -> Result<(u32, u32), String> {
+    let header = header(&mut Cursor { data, pos: 0 })?;
+    Ok((header.width, header.height))
+}
+
+/// Expands PackBits-compressed `packed` into `row`.
+fn unpack_bits(mut packed: &[u8], row: &mut [u8]) -> Result<(), String> {
+    let mut out = 0;
+    while out < row.len() {
+        let (&n, rest) = packed
+            .split_first()
+            .ok_or_else(|| "Truncated PSD row".to_string())?;
+        packed = rest;
+        let n = n as i8;
+        if n >= 0 {
+            let len = n as usize + 1;
+            if packed.len() < len || out + len > row.len() {
+                return Err("Invalid PSD row".to_string());
+            }
+            row[out..out + len].copy_from_slice(&packed[..len]);
+            packed = &packed[len..];
+            out += len;
+        } else if n != -128 {
+            let len = (1 - isize::from(n)) as usize;
+            let (&value, rest) = packed
+                .split_first()
+                .ok_or_else(|| "Truncated PSD row".to_string())?;
+            packed = rest;
+            if out + len > row.len() {
+                return Err("Invalid PSD row".to_string());
+            }
+            row[out..out + len].fill(value);
+            out += len;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the composite and converts it to RGBA.
+pub(super) fn composite(data: &[u8]) -> Result<DynamicImage, String> {
+    let mut cursor = Cursor { data, pos: 0 };
+    let header = header(&mut cursor)?;
+    if header.width == 0 || header.height == 0 {
+        return Err("Invalid image dimensions".to_string());
+    }
+    if u64::from(header.width) * u64::from(header.height) > MAX_THUMBNAIL_PIXELS {
+        return Err("Image is too large for thumbnail generation".to_string());
+    }
+    if !matches!(header.depth, 8 | 16) {
+        return Err(format!("Unsupported PSD bit depth {}", header.depth));
+    }
+
+    let palette = cursor.section(false)?;
+    cursor.section(false)?;
+    cursor.section(header.large)?;
+
+    // Color channels of the mode, followed by an optional alpha channel.
+    let color_channels = match header.mode {
+        MODE_GRAYSCALE | MODE_INDEXED => 1,
+        MODE_RGB => 3,
+        MODE_CMYK => 4,
+        mode => return Err(format!("Unsupported PSD color mode {mode}")),
+    };
+    if header.channels < color_channels || (header.mode == MODE_INDEXED && palette.len() < 768) {
+        return Err("Invalid PSD channels".to_string());
+    }
+    let channels = header.channels.min(color_channels + 1);
+    let has_alpha = channels > color_channels;
+
+    let (width, height) = (header.width as usize, header.height as usize);
+    let bytes_per_sample = usize::from(header.depth / 8);
+    let row_len = width * bytes_per_sample;
+    let compression = cursor.u16()?;
+
+    // Planar samples, reduced to 8 bits by keeping the high byte.
+    let mut planes = vec![vec![0u8; width * height]; channels];
+    let mut row = vec![0u8; row_len];
+    let mut rle_lengths = Vec::new();
+    if compression == 1 {
+        // Lengths are listed for every channel in the file, not just the ones read.
+        for _ in 0..header.channels * height {
+            rle_lengths.push(if header.large {
+                cursor.u32()? as usize
+            } else {
+                usize::from(cursor.u16()?)
+            });
+        }
+    }
+    for (channel, plane) in planes.iter_mut().enumerate() {
+        for y in 0..height {
+            match compression {
+                0 => row.copy_from_slice(cursor.take(row_len)?),
+                1 => unpack_bits(cursor.take(rle_lengths[channel * height + y])?, &mut row)?,
+                _ => return Err(format!("Unsupported PSD compression {compression}")),
+            }
+            let out = &mut plane[y * width..(y + 1) * width];
+            for (x, sample) in out.iter_mut().enumerate() {
+                *sample = row[x * bytes_per_sample];
+            }
+        }
+    }
+
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for i in 0..width * height {
+        let mut samples = [0u8; 5];
+        for (sample, plane) in samples.iter_mut().zip(&planes) {
+            *sample = plane[i];
+        }
+        let sample = |channel: usize| samples[channel];
+        let [r, g, b] = match header.mode {
+            MODE_GRAYSCALE => [sample(0); 3],
+            MODE_INDEXED => {
+                let index = usize::from(sample(0));
+                [palette[index], palette[256 + index], palette[512 + index]]
+            }
+            // CMYK samples are stored inverted, so 255 means no ink.
+            MODE_CMYK => {
+                let k = u16::from(sample(3));
+                [0, 1, 2].map(|c| (u16::from(sample(c)) * k / 255) as u8)
+            }
+            _ => [sample(0), sample(1), sample(2)],
+        };
+        let a = if has_alpha {
+            sample(color_channels)
+        } else {
+            255
+        };
+        // The composite is matted against white where it's transparent.
+        let unmatte = |c: u8| match a {
+            0 => 0,
+            255 => c,
+            a => ((i32::from(c) + i32::from(a) - 255).max(0) * 255 / i32::from(a)) as u8,
+        };
+        pixels.extend([unmatte(r), unmatte(g), unmatte(b), a]);
+    }
+
+    RgbaImage::from_raw(header.width, header.height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| "Invalid PSD image".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_bits_expands_literal_and_repeated_runs() {
+        let mut row = [0u8; 7];
+        unpack_bits(&[2, 1, 2, 3, 0xFD, 9, 0x80], &mut row).unwrap();
+        assert_eq!(row, [1, 2, 3, 9, 9, 9, 9]);
+        assert!(unpack_bits(&[5, 1], &mut row).is_err());
+    }
+}
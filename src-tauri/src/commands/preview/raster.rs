@@ -0,0 +1,154 @@
+//! Recognizing image files by content and decoding them: formats the image
+//! crate reads, SVG through resvg and Photoshop composites.
+
+use std::io::Cursor;
+use std::path::Path;
+
+use image::io::Reader as ImageReader;
+use image::{DynamicImage, ImageFormat};
+
+use crate::constants::MAX_THUMBNAIL_PIXELS;
+
+use super::{psd, sniff, svg};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ImageKind {
+    Raster(ImageFormat),
+    Svg,
+    Psd,
+}
+
+impl ImageKind {
+    pub(super) fn mime(self) -> &'static str {
+        match self {
+            ImageKind::Raster(ImageFormat::Ico) => "image/x-icon",
+            ImageKind::Raster(format) => format.to_mime_type(),
+            ImageKind::Svg => "image/svg+xml",
+            ImageKind::Psd => "image/vnd.adobe.photoshop",
+        }
+    }
+}
+
+/// Image format named by an extension, for formats without reliable magic bytes.
+fn format_from_extension(extension: &str) -> Option<ImageFormat> {
+    match extension {
+        "pnm" => Some(ImageFormat::Pnm),
+        ext => ImageFormat::from_extension(ext),
+    }
+}
+
+/// Recognizes an image from the start of its content.
+///
+/// Magic bytes decide, except for signatures short enough to start ordinary
+/// files (`P1`-`P7` for PNM, `BM`, `RIFF`, the ICO header), which also need a
+/// matching extension or header field. TGA has no signature at all and is
+/// recognized by extension alone.
+pub(super) fn detect(head: &[u8], extension: &str) -> Option<ImageKind> {
+    if psd::is_psd(head) {
+        return Some(ImageKind::Psd);
+    }
+    if let Ok(format) = image::guess_format(head) {
+        let confirmed = match format {
+            ImageFormat::WebP => head.get(8..12) == Some(b"WEBP".as_slice()),
+            // The size of the DIB header that follows the file header.
+            ImageFormat::Bmp => head
+                .get(14..18)
+                .map(|size| u32::from_le_bytes(size.try_into().unwrap()))
+                .is_some_and(|size| matches!(size, 12 | 40 | 52 | 56 | 64 | 108 | 124)),
+            ImageFormat::Pnm | ImageFormat::Ico => format_from_extension(extension) == Some(format),
+            format => format.can_read(),
+        };
+        if confirmed {
+            return Some(ImageKind::Raster(format));
+        }
+    }
+    if svg::is_svg(head) || (extension == "svgz" && head.starts_with(&[0x1f, 0x8b])) {
+        return Some(ImageKind::Svg);
+    }
+    (extension == "tga").then_some(ImageKind::Raster(ImageFormat::Tga))
+}
+
+/// Reads the start of a file and recognizes it as an image.
+pub(super) fn sniff_file(path: &Path, extension: &str) -> Option<ImageKind> {
+    detect(&sniff::read_head(path).ok()?, extension)
+}
+
+/// Rejects empty images and ones too large to decode.
+pub(super) fn check_dimensions(w: u32, h: u32) -> Result<(), String> {
+    if w == 0 || h == 0 {
+        return Err("Invalid image dimensions".to_string());
+    }
+    let pixels = (w as u64).saturating_mul(h as u64);
+    if pixels > MAX_THUMBNAIL_PIXELS {
+        return Err("Image is too large for thumbnail generation".to_string());
+    }
+    Ok(())
+}
+
+/// Decodes an image after checking its size; SVGs are rendered to fit `svg_side`.
+pub(super) fn decode(data: &[u8], kind: ImageKind, svg_side: u32) -> Result<DynamicImage, String> {
+    if let Some((w, h)) = pixel_size(data, kind)? {
+        check_dimensions(w, h)?;
+    }
+    match kind {
+        ImageKind::Svg => svg::rasterize(data, svg_side),
+        ImageKind::Psd => psd::composite(data),
+        ImageKind::Raster(format) => ImageReader::with_format(Cursor::new(data), format)
+            .decode()
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Pixel dimensions from the header, or `None` for SVGs, which scale freely.
+pub(super) fn pixel_size(data: &[u8], kind: ImageKind) -> Result<Option<(u32, u32)>, String> {
+    match kind {
+        ImageKind::Svg => Ok(None),
+        ImageKind::Psd => psd::dimensions(data).map(Some),
+        ImageKind::Raster(format) => dimensions(data, format).map(Some),
+    }
+}
+
+/// Width and height from the image header.
+pub(super) fn dimensions(data: &[u8], format: ImageFormat) -> Result<(u32, u32), String> {
+    ImageReader::with_format(Cursor::new(data), format)
+        .into_dimensions()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_needs_more_than_a_short_signature() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(
+            detect(png, "txt"),
+            Some(ImageKind::Raster(ImageFormat::Png))
+        );
+        assert_eq!(detect(b"P1 is the first phase", "txt"), None);
+        assert_eq!(
+            detect(b"P6\n2 2\n255\n", "ppm"),
+            Some(ImageKind::Raster(ImageFormat::Pnm))
+        );
+        assert_eq!(detect(b"BMW service log", "log"), None);
+        assert_eq!(detect(b"RIFF\0\0\0\0WAVEfmt ", "wav"), None);
+        assert_eq!(
+            detect(
+                b"<?xml version=\"1.0\"?>\n<!-- logo -->\n<!DOCTYPE svg [<!ENTITY a \"b\">]>\n<svg xmlns=\"x\">",
+                "xml"
+            ),
+            Some(ImageKind::Svg)
+        );
+        assert_eq!(
+            detect(b"<!DOCTYPE html><html><body><svg></svg>", "html"),
+            None
+        );
+        assert_eq!(detect(b"8BPS\0\x01", "bin"), Some(ImageKind::Psd));
+        assert_eq!(
+            detect(b"\0\0\x02\0", "tga"),
+            Some(ImageKind::Raster(ImageFormat::Tga))
+        );
+        assert_eq!(detect(b"plain text", "md"), None);
+    }
+}
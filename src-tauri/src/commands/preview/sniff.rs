@@ -0,0 +1,107 @@
+//! Content sniffing: deciding whether a file is text and naming binary types.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use infer::MatcherType;
+
+use crate::constants::{CONTENT_SNIFF_LENGTH, TEXT_FILE_NAMES};
+
+/// What the first bytes of a file say about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Sniffed {
+    Text,
+    Binary { mime: String },
+}
+
+/// True for names that are text by convention, like `Dockerfile` or `.env.local`.
+pub(super) fn is_text_file_name(path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let name = name.to_string_lossy().to_lowercase();
+    TEXT_FILE_NAMES.contains(&name.as_str())
+        || name.starts_with(".env.")
+        || name.starts_with("dockerfile.")
+}
+
+/// Heuristic text check: no NUL, valid UTF-8 and hardly any control characters.
+///
+/// A multi-byte character cut off at the end of `head` is allowed.
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => {}
+        Err(e) if e.error_len().is_none() => {}
+        Err(_) => return false,
+    }
+
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    control * 100 <= head.len()
+}
+
+/// Classifies a file from `head`, the start of its content.
+///
+/// Recognized magic bytes win, so a `.ts` video isn't shown as TypeScript.
+/// Otherwise files with a text name (`named_text`) or text-like content are
+/// text, and anything else is binary with the MIME type its extension suggests.
+pub(super) fn sniff(path: &Path, head: &[u8], named_text: bool) -> Sniffed {
+    if let Some(kind) = infer::get(head) {
+        return if kind.matcher_type() == MatcherType::Text {
+            Sniffed::Text
+        } else {
+            Sniffed::Binary {
+                mime: kind.mime_type().to_string(),
+            }
+        };
+    }
+
+    if named_text || looks_like_text(head) {
+        return Sniffed::Text;
+    }
+
+    Sniffed::Binary {
+        mime: mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .to_string(),
+    }
+}
+
+/// Reads the first `CONTENT_SNIFF_LENGTH` bytes of a file.
+pub(super) fn read_head(path: &Path) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(CONTENT_SNIFF_LENGTH);
+    File::open(path)?
+        .take(CONTENT_SNIFF_LENGTH as u64)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_heuristic_rejects_binary_and_accepts_cut_utf8() {
+        assert!(looks_like_text(b"plain\ttext\r\n"));
+        assert!(looks_like_text("caf\u{e9}".as_bytes()));
+        assert!(looks_like_text(&"caf\u{e9}".as_bytes()[..4]));
+        assert!(looks_like_text(b""));
+        assert!(!looks_like_text(b"abc\0def"));
+        assert!(!looks_like_text(b"\xff\xfe\xfa latin"));
+        assert!(!looks_like_text(b"\x01\x02\x03\x04 mostly control"));
+    }
+
+    #[test]
+    fn conventional_names_are_text() {
+        for name in ["Dockerfile", "makefile", ".env", ".env.local", ".gitignore"] {
+            assert!(is_text_file_name(Path::new(name)), "{name}");
+        }
+        assert!(!is_text_file_name(Path::new("photo.png")));
+    }
+}
@@ -0,0 +1,547 @@
+//! Spreadsheet previews and paging through calamine (XLSX, XLSB, XLS, ODS).
+//!
+//! Cells are shown as text; cells that aren't plain text are also listed with
+//! their typed value, formula and, for XLSX, number format. Number formats
+//! aren't exposed by calamine, so they are read from the XLSX parts directly.
+//!
+//! Paged sheets are kept in `SheetCacheState` until the file changes, so
+//! scrolling doesn't reparse the workbook for every page.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::ops::Range as RowRange;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use calamine::{open_workbook_auto, Data, Range, Reader, SheetVisible, Sheets};
+use quick_xml::events::Event;
+
+use crate::constants::{
+    MAX_CACHED_SHEETS, MAX_OFFICE_FILE_SIZE, MAX_SHEET_RANGE_ROWS, MAX_SPREADSHEET_ROWS,
+};
+use crate::error::{FileManagerError, Result};
+use crate::models::preview::SpreadsheetSheet;
+use crate::models::{CellValue, FilePreview, MergedRange, SheetCell, SheetRange};
+use crate::utils::{get_extension, validate_absolute_path};
+
+use super::too_large_mime;
+use crate::commands::office::{attribute, local_name, part_path, read_entry, relationships};
+
+type Workbook = Sheets<BufReader<File>>;
+
+fn open(path: &str) -> Result<Workbook> {
+    open_workbook_auto(path)
+        .map_err(|e| FileManagerError::ReadFileError(format!("Cannot open spreadsheet: {e}")))
+}
+
+fn format_float(f: f64) -> String {
+    if f == (f as i64) as f64 {
+        format!("{}", f as i64)
+    } else {
+        format!("{f}")
+    }
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) => s.clone(),
+        Data::Float(f) => format_float(*f),
+        Data::Int(n) => format!("{n}"),
+        Data::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Data::Error(e) => e.to_string(),
+        Data::DateTime(dt) => match cell_value(cell) {
+            CellValue::Date { value } | CellValue::Duration { value } => value,
+            _ => format_float(dt.as_f64()),
+        },
+        Data::DateTimeIso(s) => s.clone(),
+        Data::DurationIso(s) => s.clone(),
+    }
+}
+
+fn cell_value(cell: &Data) -> CellValue {
+    match cell {
+        Data::Empty => CellValue::Empty,
+        Data::String(s) => CellValue::Text { value: s.clone() },
+        Data::Float(f) => CellValue::Number { value: *f },
+        Data::Int(n) => CellValue::Number { value: *n as f64 },
+        Data::Bool(b) => CellValue::Bool { value: *b },
+        Data::Error(e) => CellValue::Error {
+            value: e.to_string(),
+        },
+        Data::DateTime(dt) if dt.is_duration() => CellValue::Duration {
+            value: dt
+                .as_duration()
+                .map_or_else(|| format_float(dt.as_f64()), |d| d.to_string()),
+        },
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(datetime) => CellValue::Date {
+                value: datetime.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            },
+            None => CellValue::Number { value: dt.as_f64() },
+        },
+        Data::DateTimeIso(s) => CellValue::Date { value: s.clone() },
+        Data::DurationIso(s) => CellValue::Duration { value: s.clone() },
+    }
+}
+
+fn merged_ranges(workbook: &mut Workbook, name: &str) -> Vec<MergedRange> {
+    let dimensions = match workbook {
+        Sheets::Xlsx(xlsx) => xlsx.worksheet_merge_cells(name).and_then(|r| r.ok()),
+        Sheets::Xls(xls) => xls.worksheet_merge_cells(name),
+        _ => None,
+    };
+    dimensions
+        .unwrap_or_default()
+        .into_iter()
+        .map(|d| MergedRange {
+            start_row: d.start.0,
+            start_column: d.start.1,
+            end_row: d.end.0,
+            end_column: d.end.1,
+        })
+        .collect()
+}
+
+/// True when the first row holds only text, like column titles, and data follows.
+fn looks_like_header(range: &Range<Data>) -> bool {
+    let mut rows = range.rows();
+    let Some(first) = rows.next() else {
+        return false;
+    };
+    rows.next().is_some()
+        && first.iter().any(|cell| matches!(cell, Data::String(_)))
+        && first
+            .iter()
+            .all(|cell| matches!(cell, Data::String(_) | Data::Empty))
+}
+
+/// One sheet's cells, formulas and merged ranges.
+struct SheetData {
+    range: Range<Data>,
+    formulas: Option<Range<String>>,
+    merged: Vec<MergedRange>,
+    /// Whether number formats can be read from the XLSX parts.
+    xlsx: bool,
+}
+
+impl SheetData {
+    fn read(workbook: &mut Workbook, name: &str) -> Result<Self> {
+        let range = workbook
+            .worksheet_range(name)
+            .map_err(|e| FileManagerError::ReadFileError(format!("Cannot read sheet: {e}")))?;
+        // Formulas are optional; a reader that can't produce them just omits them.
+        let formulas = workbook.worksheet_formula(name).ok();
+        Ok(Self {
+            range,
+            formulas,
+            merged: merged_ranges(workbook, name),
+            xlsx: matches!(workbook, Sheets::Xlsx(_)),
+        })
+    }
+
+    /// Number formats of the cells in sheet rows `rows`; empty unless XLSX.
+    fn number_formats(
+        &self,
+        path: &str,
+        name: &str,
+        rows: &RowRange<u32>,
+    ) -> HashMap<(u32, u32), String> {
+        if !self.xlsx {
+            return HashMap::new();
+        }
+        xlsx_number_formats(path, name, rows).unwrap_or_default()
+    }
+
+    /// Text of sheet rows `rows`, and the cells among them that aren't plain text.
+    fn window(
+        &self,
+        rows: RowRange<u32>,
+        formats: &HashMap<(u32, u32), String>,
+    ) -> (Vec<Vec<String>>, Vec<SheetCell>) {
+        let (Some(start), Some(end)) = (self.range.start(), self.range.end()) else {
+            return (Vec::new(), Vec::new());
+        };
+        let mut texts = Vec::new();
+        let mut cells = Vec::new();
+
+        for row in rows.start.max(start.0)..rows.end.min(end.0 + 1) {
+            let mut line = Vec::with_capacity((end.1 - start.1 + 1) as usize);
+            for column in start.1..=end.1 {
+                let data = self.range.get_value((row, column)).unwrap_or(&Data::Empty);
+                line.push(cell_text(data));
+
+                let formula = self
+                    .formulas
+                    .as_ref()
+                    .and_then(|f| f.get_value((row, column)))
+                    .filter(|f| !f.is_empty())
+                    .cloned();
+                let value = cell_value(data);
+                if formula.is_some() || !matches!(value, CellValue::Text { .. } | CellValue::Empty)
+                {
+                    cells.push(SheetCell {
+                        row,
+                        column,
+                        value,
+                        formula,
+                        number_format: formats.get(&(row, column)).cloned(),
+                    });
+                }
+            }
+            texts.push(line);
+        }
+        (texts, cells)
+    }
+}
+
+/// A sheet read for paging, with the file state it was read from.
+struct CachedSheet {
+    data: Arc<SheetData>,
+    modified: Option<SystemTime>,
+    size: u64,
+    last_used: u64,
+}
+
+/// Sheets recently paged with `read_sheet_range`.
+#[derive(Default)]
+pub struct SheetCacheState {
+    sheets: Mutex<HashMap<(PathBuf, String), CachedSheet>>,
+    tick: AtomicU64,
+}
+
+impl SheetCacheState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sheet `name` of the workbook at `path`, read again when the file has changed.
+    fn sheet(&self, path: &str, name: &str, metadata: &fs::Metadata) -> Result<Arc<SheetData>> {
+        let key = (PathBuf::from(path), name.to_string());
+        let (modified, size) = (metadata.modified().ok(), metadata.len());
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut sheets = self
+                .sheets
+                .lock()
+                .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+            if let Some(cached) = sheets.get_mut(&key) {
+                if modified.is_some() && cached.modified == modified && cached.size == size {
+                    cached.last_used = tick;
+                    return Ok(Arc::clone(&cached.data));
+                }
+            }
+        }
+
+        // The workbook is parsed without holding the lock.
+        let mut workbook = open(path)?;
+        if !workbook.sheet_names().iter().any(|sheet| sheet == name) {
+            return Err(FileManagerError::ReadFileError(format!(
+                "Sheet not found: {name}"
+            )));
+        }
+        let data = Arc::new(SheetData::read(&mut workbook, name)?);
+
+        let mut sheets = self
+            .sheets
+            .lock()
+            .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+        sheets.insert(
+            key,
+            CachedSheet {
+                data: Arc::clone(&data),
+                modified,
+                size,
+                last_used: tick,
+            },
+        );
+        if sheets.len() > MAX_CACHED_SHEETS {
+            let oldest = sheets
+                .iter()
+                .min_by_key(|(_, sheet)| sheet.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                sheets.remove(&oldest);
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Generates a preview for a spreadsheet.
+///
+/// The reader is picked by extension: XLSX/XLSM, XLSB, legacy XLS or ODS.
+pub(super) fn generate_spreadsheet_preview(path: &str) -> std::result::Result<FilePreview, String> {
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    if meta.len() > MAX_OFFICE_FILE_SIZE {
+        return Ok(FilePreview::Unsupported {
+            mime: too_large_mime(path),
+        });
+    }
+
+    let mut workbook = open(path)?;
+    let metadata = workbook.sheets_metadata().to_vec();
+    let mut sheets = Vec::new();
+
+    for sheet in metadata {
+        let Ok(data) = SheetData::read(&mut workbook, &sheet.name) else {
+            continue;
+        };
+        let first_row = data.range.start().map_or(0, |s| s.0);
+        let header = looks_like_header(&data.range);
+        let count = MAX_SPREADSHEET_ROWS as u32 + u32::from(header);
+        let window = first_row..first_row.saturating_add(count);
+        let formats = data.number_formats(path, &sheet.name, &window);
+
+        let (mut rows, cells) = data.window(window, &formats);
+        let headers = if header && !rows.is_empty() {
+            rows.remove(0)
+        } else {
+            Vec::new()
+        };
+        let total_rows = data.range.height() as u64;
+
+        sheets.push(SpreadsheetSheet {
+            name: sheet.name.clone(),
+            headers,
+            truncated: (rows.len() as u64 + u64::from(header)) < total_rows,
+            rows,
+            total_rows,
+            row_offset: first_row + u32::from(header),
+            column_offset: data.range.start().map_or(0, |s| s.1),
+            cells,
+            merged: data.merged,
+            hidden: sheet.visible != SheetVisible::Visible,
+        });
+    }
+
+    Ok(FilePreview::Spreadsheet { sheets })
+}
+
+/// Reads `rows` rows of `sheet` starting at 0-based sheet row `row_offset`.
+#[doc(hidden)]
+pub fn read_sheet_range_sync(
+    path: &str,
+    sheet: &str,
+    row_offset: u32,
+    rows: u32,
+    state: &SheetCacheState,
+) -> Result<SheetRange> {
+    validate_absolute_path(path)?;
+    let metadata = fs::metadata(path)?;
+    if metadata.len() > MAX_OFFICE_FILE_SIZE {
+        return Err(FileManagerError::FileTooLarge(
+            metadata.len(),
+            MAX_OFFICE_FILE_SIZE,
+        ));
+    }
+
+    let data = state.sheet(path, sheet, &metadata)?;
+    let window = row_offset..row_offset.saturating_add(rows.min(MAX_SHEET_RANGE_ROWS));
+    let formats = data.number_formats(path, sheet, &window);
+    let (texts, cells) = data.window(window.clone(), &formats);
+    let start = data.range.start().unwrap_or_default();
+    let row_offset = row_offset.max(start.0);
+
+    Ok(SheetRange {
+        rows: texts,
+        row_offset,
+        column_offset: start.1,
+        cells,
+        merged: data
+            .merged
+            .iter()
+            .filter(|m| m.end_row >= row_offset && m.start_row < window.end)
+            .cloned()
+            .collect(),
+        end_row: data.range.end().map_or(0, |end| end.0 + 1),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// XLSX number formats
+// ---------------------------------------------------------------------------
+
+/// Format codes of Excel's built-in number formats; `None` for General.
+fn builtin_number_format(id: u32) -> Option<&'static str> {
+    Some(match id {
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        12 => "# ?/?",
+        13 => "# ??/??",
+        14 => "mm-dd-yy",
+        15 => "d-mmm-yy",
+        16 => "d-mmm",
+        17 => "mmm-yy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "m/d/yy h:mm",
+        37 => "#,##0 ;(#,##0)",
+        38 => "#,##0 ;[Red](#,##0)",
+        39 => "#,##0.00;(#,##0.00)",
+        40 => "#,##0.00;[Red](#,##0.00)",
+        45 => "mm:ss",
+        46 => "[h]:mm:ss",
+        47 => "mmss.0",
+        48 => "##0.0E+0",
+        49 => "@",
+        _ => return None,
+    })
+}
+
+/// Archive path of the worksheet part for sheet `name`.
+fn xlsx_sheet_path(archive: &mut zip::ZipArchive<File>, name: &str) -> Option<String> {
+    let workbook = read_entry(archive, "xl/workbook.xml")?;
+    let mut reader = quick_xml::Reader::from_str(&workbook);
+    let id = loop {
+        match reader.read_event().ok()? {
+            Event::Start(ref e) | Event::Empty(ref e)
+                if local_name(e.name().as_ref()) == b"sheet"
+                    && attribute(e, b"name").as_deref() == Some(name) =>
+            {
+                break attribute(e, b"id")?;
+            }
+            Event::Eof => return None,
+            _ => {}
+        }
+    };
+
+    let rels = relationships(&read_entry(archive, "xl/_rels/workbook.xml.rels")?);
+    rels.get(&id).map(|rel| part_path("xl", &rel.target))
+}
+
+/// Number format code of each cell style (`cellXfs` entry) in `xl/styles.xml`.
+fn xlsx_style_formats(styles: &str) -> Vec<Option<String>> {
+    let mut reader = quick_xml::Reader::from_str(styles);
+    let mut custom: HashMap<u32, String> = HashMap::new();
+    let mut formats = Vec::new();
+    let mut in_cell_xfs = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e) | Event::Empty(ref e)) => match local_name(e.name().as_ref()) {
+                b"numFmt" => {
+                    if let (Some(id), Some(code)) = (
+                        attribute(e, b"numFmtId").and_then(|id| id.parse().ok()),
+                        attribute(e, b"formatCode"),
+                    ) {
+                        custom.insert(id, code);
+                    }
+                }
+                b"cellXfs" => in_cell_xfs = true,
+                b"xf" if in_cell_xfs => {
+                    let id: u32 = attribute(e, b"numFmtId")
+                        .and_then(|id| id.parse().ok())
+                        .unwrap_or(0);
+                    formats.push(
+                        custom
+                            .get(&id)
+                            .cloned()
+                            .or_else(|| builtin_number_format(id).map(str::to_string)),
+                    );
+                }
+                _ => {}
+            },
+            Ok(Event::End(ref e)) if local_name(e.name().as_ref()) == b"cellXfs" => {
+                in_cell_xfs = false;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    formats
+}
+
+/// 0-based (row, column) of an A1-style reference.
+fn parse_cell_reference(reference: &str) -> Option<(u32, u32)> {
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    let column = letters.bytes().try_fold(0u32, |acc, b| {
+        b.is_ascii_alphabetic()
+            .then(|| acc * 26 + u32::from(b.to_ascii_uppercase() - b'A') + 1)
+    })?;
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, column.checked_sub(1)?))
+}
+
+/// Number formats of the cells of sheet `name` in `rows` that aren't General.
+fn xlsx_number_formats(
+    path: &str,
+    name: &str,
+    rows: &RowRange<u32>,
+) -> Option<HashMap<(u32, u32), String>> {
+    if get_extension(Path::new(path)).as_deref() == Some("xlsb") {
+        return None;
+    }
+    let mut archive = zip::ZipArchive::new(File::open(path).ok()?).ok()?;
+    let styles = xlsx_style_formats(&read_entry(&mut archive, "xl/styles.xml")?);
+    let sheet_path = xlsx_sheet_path(&mut archive, name)?;
+    let sheet = read_entry(&mut archive, &sheet_path)?;
+
+    let mut formats = HashMap::new();
+    let mut reader = quick_xml::Reader::from_str(&sheet);
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(ref e) | Event::Empty(ref e) if local_name(e.name().as_ref()) == b"c" => {
+                let Some((row, column)) = attribute(e, b"r").and_then(|r| parse_cell_reference(&r))
+                else {
+                    continue;
+                };
+                // Rows are stored in order, so nothing after the window matters.
+                if row >= rows.end {
+                    break;
+                }
+                let style = attribute(e, b"s").and_then(|s| s.parse::<usize>().ok());
+                if let Some(format) = style.and_then(|s| styles.get(s)).and_then(Option::clone) {
+                    if rows.contains(&row) {
+                        formats.insert((row, column), format);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Some(formats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_references_parse_to_zero_based_positions() {
+        assert_eq!(parse_cell_reference("A1"), Some((0, 0)));
+        assert_eq!(parse_cell_reference("C7"), Some((6, 2)));
+        assert_eq!(parse_cell_reference("AB10"), Some((9, 27)));
+        assert_eq!(parse_cell_reference("7"), None);
+        assert_eq!(parse_cell_reference("A0"), None);
+    }
+
+    #[test]
+    fn style_formats_resolve_custom_and_builtin_codes() {
+        let styles = r#"<styleSheet>
+            <numFmts count="1"><numFmt numFmtId="164" formatCode="yyyy-mm-dd"/></numFmts>
+            <cellStyleXfs count="1"><xf numFmtId="3"/></cellStyleXfs>
+            <cellXfs count="3"><xf numFmtId="0"/><xf numFmtId="10"/><xf numFmtId="164"/></cellXfs>
+        </styleSheet>"#;
+        assert_eq!(
+            xlsx_style_formats(styles),
+            vec![
+                None,
+                Some("0.00%".to_string()),
+                Some("yyyy-mm-dd".to_string())
+            ]
+        );
+    }
+}
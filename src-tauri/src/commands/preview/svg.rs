@@ -0,0 +1,115 @@
+//! SVG rasterization with resvg.
+
+use std::sync::{Arc, OnceLock};
+
+use image::{DynamicImage, RgbaImage};
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{fontdb, Options, Size, Tree};
+
+use crate::constants::MAX_THUMBNAIL_PIXELS;
+
+/// System fonts for SVG text, loaded on first use.
+fn fonts() -> Arc<fontdb::Database> {
+    static FONTS: OnceLock<Arc<fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut db = fontdb::Database::new();
+            db.load_system_fonts();
+            Arc::new(db)
+        })
+        .clone()
+}
+
+/// True when the root element at the start of a file is `<svg>`, so HTML
+/// pages with inline SVG don't count.
+pub(super) fn is_svg(head: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(head);
+    let mut rest = text.trim_start_matches('\u{feff}');
+    loop {
+        rest = rest.trim_start();
+        let skipped = if rest.starts_with("<?") {
+            rest.split_once("?>")
+        } else if rest.starts_with("<!--") {
+            rest.split_once("-->")
+        } else if rest.starts_with("<!") {
+            // A doctype, possibly with an internal subset in brackets.
+            match (rest.find('['), rest.find('>')) {
+                (Some(open), Some(close)) if open < close => rest.split_once("]>"),
+                _ => rest.split_once('>'),
+            }
+        } else {
+            let root = rest.strip_prefix("<svg:svg").or(rest.strip_prefix("<svg"));
+            return root.is_some_and(|after| {
+                after.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            });
+        };
+        match skipped {
+            Some((_, after)) => rest = after,
+            None => return false,
+        }
+    }
+}
+
+/// Pixel size and scale for rendering a drawing of `size` with its longer side
+/// at `max_side`, shrunk further to stay within `max_pixels`.
+fn fit(size: Size, max_side: u32, max_pixels: u64) -> (u32, u32, f32) {
+    let (w, h) = (f64::from(size.width()), f64::from(size.height()));
+    let mut scale = f64::from(max_side) / w.max(h);
+    let area = w * h * scale * scale;
+    if area > max_pixels as f64 {
+        scale *= (max_pixels as f64 / area).sqrt();
+    }
+    let width = (w * scale).floor().max(1.0) as u32;
+    let height = (h * scale).floor().max(1.0) as u32;
+    (width, height, scale as f32)
+}
+
+/// Renders an SVG (or gzipped SVGZ) scaled so its longer side is `max_side`.
+///
+/// The size comes from the drawing's viewBox (or width and height), and the
+/// pixmap is kept within `MAX_THUMBNAIL_PIXELS` like decoded images.
+pub(super) fn rasterize(data: &[u8], max_side: u32) -> Result<DynamicImage, String> {
+    let options = Options {
+        fontdb: fonts(),
+        ..Options::default()
+    };
+    let tree = Tree::from_data(data, &options).map_err(|e| format!("Invalid SVG: {e}"))?;
+
+    let (width, height, scale) = fit(tree.size(), max_side, MAX_THUMBNAIL_PIXELS);
+    let mut pixmap = Pixmap::new(width, height).ok_or("Invalid SVG size")?;
+    resvg::render(
+        &tree,
+        Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // tiny-skia keeps premultiplied alpha; the image crate expects straight alpha.
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(width, height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| "Invalid SVG size".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_scales_to_the_longer_side_within_the_pixel_budget() {
+        let size = Size::from_wh(100.0, 50.0).unwrap();
+        assert_eq!(fit(size, 400, 1_000_000), (400, 200, 4.0));
+        // A tiny drawing asked for a huge side is held to the budget.
+        let (width, height, _) = fit(size, 8192, 20_000);
+        assert_eq!((width, height), (200, 100));
+
+        let sliver = Size::from_wh(10_000.0, 1.0).unwrap();
+        assert_eq!(fit(sliver, 100, 1_000_000).1, 1);
+    }
+}
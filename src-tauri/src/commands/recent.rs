@@ -0,0 +1,172 @@
+//! Timeline of recently modified and created files.
+//!
+//! Roots covered by the file index are answered from the database; other roots
+//! are walked, pruning hidden and cache directories unless asked not to.
+
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{Local, TimeZone};
+use ignore::WalkBuilder;
+use tauri::{AppHandle, Manager};
+use tokio::task::spawn_blocking;
+
+use crate::constants::{
+    DEFAULT_RECENT_CHANGES_LIMIT, DEFAULT_RECENT_CHANGES_SECS, RECENT_CHANGES_EXCLUDED_DIRS,
+};
+use crate::error::{FileManagerError, Result};
+use crate::models::{FileEntry, RecentDay};
+use crate::utils::{system_time_to_timestamp, validate_absolute_path};
+
+use super::index::IndexState;
+
+/// When a file last changed: its modification or creation time, whichever is later.
+fn changed_at(entry: &FileEntry) -> i64 {
+    entry.modified.unwrap_or(0).max(entry.created.unwrap_or(0))
+}
+
+fn is_excluded_dir(name: &str) -> bool {
+    RECENT_CHANGES_EXCLUDED_DIRS.contains(&name)
+}
+
+/// True if `path` is hidden or sits in a hidden or cache directory below `root`.
+fn is_excluded(root: &Path, entry: &FileEntry) -> bool {
+    if entry.is_hidden {
+        return true;
+    }
+    let path = Path::new(&entry.path);
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    relative
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .any(|component| match component {
+            Component::Normal(name) => {
+                let name = name.to_string_lossy();
+                name.starts_with('.') || is_excluded_dir(&name)
+            }
+            _ => false,
+        })
+}
+
+/// Walks `root` for files changed at or after `since`.
+fn walk_recent(root: &Path, since: i64, show_all: bool) -> Vec<FileEntry> {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        .hidden(!show_all)
+        .follow_links(false);
+    if !show_all {
+        builder.filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|t| t.is_dir())
+                || !is_excluded_dir(&entry.file_name().to_string_lossy())
+        });
+    }
+
+    builder
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let file = FileEntry::from_path(entry.path(), &metadata);
+            (changed_at(&file) >= since).then_some(file)
+        })
+        .collect()
+}
+
+/// Buckets entries already sorted newest first by local calendar day.
+fn group_by_day(entries: Vec<FileEntry>) -> Vec<RecentDay> {
+    let mut days: Vec<RecentDay> = Vec::new();
+    for entry in entries {
+        let date = Local
+            .timestamp_opt(changed_at(&entry), 0)
+            .single()
+            .map(|time| time.date_naive().to_string())
+            .unwrap_or_default();
+
+        match days.last_mut() {
+            Some(day) if day.date == date => day.entries.push(entry),
+            _ => days.push(RecentDay {
+                date,
+                entries: vec![entry],
+            }),
+        }
+    }
+    days
+}
+
+#[doc(hidden)]
+pub fn recent_changes_sync(
+    roots: &[String],
+    since: Option<i64>,
+    limit: Option<u32>,
+    show_all: bool,
+    index: &IndexState,
+) -> Result<Vec<RecentDay>> {
+    let since = since.unwrap_or_else(|| {
+        system_time_to_timestamp(SystemTime::now()).unwrap_or(0) - DEFAULT_RECENT_CHANGES_SECS
+    });
+    let limit = limit.map_or(DEFAULT_RECENT_CHANGES_LIMIT, |l| l as usize);
+
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for root in roots {
+        validate_absolute_path(root)?;
+        let root: PathBuf = Path::new(root.trim()).components().collect();
+        if !root.is_dir() {
+            return Err(FileManagerError::DirectoryNotFound(
+                root.to_string_lossy().to_string(),
+            ));
+        }
+
+        let found = match index.recent_files(&root.to_string_lossy(), since, limit, |entry| {
+            show_all || !is_excluded(&root, entry)
+        })? {
+            Some(found) => found,
+            None => walk_recent(&root, since, show_all),
+        };
+        // Overlapping roots would list the same file twice.
+        entries.extend(found.into_iter().filter(|e| seen.insert(e.path.clone())));
+    }
+
+    entries.sort_unstable_by(|a, b| {
+        changed_at(b)
+            .cmp(&changed_at(a))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    entries.truncate(limit);
+    Ok(group_by_day(entries))
+}
+
+/// Lists the files most recently modified or created under `roots`, grouped by day.
+///
+/// `since` is a Unix timestamp and defaults to a week ago. Hidden files and
+/// cache directories are skipped unless `show_all` is set.
+#[tauri::command]
+#[specta::specta]
+pub async fn recent_changes(
+    roots: Vec<String>,
+    since: Option<i64>,
+    limit: Option<u32>,
+    show_all: Option<bool>,
+    app: AppHandle,
+) -> std::result::Result<Vec<RecentDay>, String> {
+    spawn_blocking(move || {
+        recent_changes_sync(
+            &roots,
+            since,
+            limit,
+            show_all.unwrap_or(false),
+            &app.state::<IndexState>(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(Into::into)
+}
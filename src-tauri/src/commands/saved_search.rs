@@ -0,0 +1,422 @@
+//! Saved searches that open as virtual directories ("smart folders").
+//!
+//! Searches are kept in a JSON file in the app data directory. Opening one
+//! re-runs it and lists the hits like a directory; a watched saved search is
+//! re-run once changes under its root settle and emits `saved-search-updated`
+//! when its listing changed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::task::spawn_blocking;
+
+use crate::constants::SAVED_SEARCH_REFRESH_DEBOUNCE_MS;
+use crate::error::{FileManagerError, Result};
+use crate::models::{FileEntry, SavedSearch, SavedSearchUpdate, SearchOptions};
+use crate::utils::{system_time_to_timestamp, validate_absolute_path, write_atomic_or_create};
+
+use super::file_ops::sort_entries;
+use super::search::search_files_sync;
+
+type Searches = Arc<Mutex<Vec<SavedSearch>>>;
+
+fn lock(searches: &Searches) -> Result<MutexGuard<'_, Vec<SavedSearch>>> {
+    searches
+        .lock()
+        .map_err(|e| FileManagerError::IoError(e.to_string()))
+}
+
+/// Global state for saved searches and the watchers keeping open ones live.
+pub struct SavedSearchState {
+    store_path: Option<PathBuf>,
+    searches: Searches,
+    live: Mutex<HashMap<String, RecommendedWatcher>>,
+}
+
+impl SavedSearchState {
+    /// Loads the saved searches stored at `path`; a missing file means none.
+    ///
+    /// A file that can't be parsed is renamed to `<name>.corrupt` so it can be
+    /// recovered by hand, and the store starts empty.
+    pub fn open(path: &Path) -> Result<Self> {
+        let searches = match fs::read(path) {
+            Ok(data) => match serde_json::from_slice(&data) {
+                Ok(searches) => searches,
+                Err(_) => {
+                    let mut aside = path.as_os_str().to_owned();
+                    aside.push(".corrupt");
+                    fs::rename(path, aside)?;
+                    Vec::new()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            store_path: Some(path.to_path_buf()),
+            searches: Arc::new(Mutex::new(searches)),
+            live: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Creates a store that is not persisted across restarts.
+    pub fn in_memory() -> Self {
+        Self {
+            store_path: None,
+            searches: Arc::default(),
+            live: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn live_search_count(&self) -> usize {
+        self.live.lock().map(|live| live.len()).unwrap_or(0)
+    }
+
+    fn persist(&self, searches: &[SavedSearch]) -> Result<()> {
+        let Some(ref path) = self.store_path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(searches)
+            .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+        write_atomic_or_create(path, &json)?;
+        Ok(())
+    }
+
+    fn options(&self, id: &str) -> Result<SearchOptions> {
+        lock(&self.searches)?
+            .iter()
+            .find(|search| search.id == id)
+            .map(|search| search.options.clone())
+            .ok_or_else(|| FileManagerError::SavedSearchNotFound(id.to_string()))
+    }
+}
+
+fn now() -> i64 {
+    system_time_to_timestamp(SystemTime::now()).unwrap_or(0)
+}
+
+/// Runs a saved search and lists its hits like a directory.
+///
+/// Hits inside archives have no entry on disk and are left out.
+fn run(options: &SearchOptions) -> Result<Vec<FileEntry>> {
+    let mut entries: Vec<FileEntry> = search_files_sync(options)?
+        .into_iter()
+        .filter_map(|result| {
+            let path = Path::new(&result.path);
+            fs::metadata(path)
+                .ok()
+                .map(|metadata| FileEntry::from_path(path, &metadata))
+        })
+        .collect();
+
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// What a listing looks like for deciding whether to send an update.
+fn fingerprint(entries: &[FileEntry]) -> Vec<(String, u64, Option<i64>)> {
+    entries
+        .iter()
+        .map(|entry| (entry.path.clone(), entry.size, entry.modified))
+        .collect()
+}
+
+/// Re-runs a watched search after each burst of filesystem events.
+///
+/// Exits when the watcher feeding `events` is dropped or the search is deleted.
+fn spawn_refresher(
+    id: String,
+    searches: Searches,
+    events: Receiver<()>,
+    mut last: Vec<(String, u64, Option<i64>)>,
+    on_update: impl Fn(SavedSearchUpdate) + Send + 'static,
+) -> Result<()> {
+    let debounce = Duration::from_millis(SAVED_SEARCH_REFRESH_DEBOUNCE_MS);
+
+    thread::Builder::new()
+        .name("saved-search".to_string())
+        .spawn(move || {
+            while events.recv().is_ok() {
+                loop {
+                    match events.recv_timeout(debounce) {
+                        Ok(()) => continue,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+
+                // Options are looked up again so edits apply without re-watching.
+                let Some(options) = lock(&searches).ok().and_then(|searches| {
+                    searches
+                        .iter()
+                        .find(|search| search.id == id)
+                        .map(|search| search.options.clone())
+                }) else {
+                    return;
+                };
+                let Ok(entries) = run(&options) else {
+                    continue;
+                };
+
+                let current = fingerprint(&entries);
+                if current != last {
+                    last = current;
+                    on_update(SavedSearchUpdate {
+                        id: id.clone(),
+                        entries,
+                    });
+                }
+            }
+        })?;
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn list_saved_searches_sync(state: &SavedSearchState) -> Result<Vec<SavedSearch>> {
+    let mut searches = lock(&state.searches)?.clone();
+    searches.sort_by_key(|search| search.name.to_lowercase());
+    Ok(searches)
+}
+
+/// Lists saved searches by name.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_saved_searches(app: AppHandle) -> std::result::Result<Vec<SavedSearch>, String> {
+    list_saved_searches_sync(&app.state::<SavedSearchState>()).map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn save_search_sync(
+    id: Option<&str>,
+    name: &str,
+    options: SearchOptions,
+    state: &SavedSearchState,
+) -> Result<SavedSearch> {
+    validate_absolute_path(&options.search_path)?;
+    let name = match name.trim() {
+        "" if options.query.trim().is_empty() => options.search_path.clone(),
+        "" => options.query.trim().to_string(),
+        name => name.to_string(),
+    };
+
+    let mut searches = lock(&state.searches)?;
+    let now = now();
+    let mut moved_from = None;
+
+    let saved = match id {
+        Some(id) => {
+            let search = searches
+                .iter_mut()
+                .find(|search| search.id == id)
+                .ok_or_else(|| FileManagerError::SavedSearchNotFound(id.to_string()))?;
+            if search.options.search_path != options.search_path {
+                moved_from = Some(search.options.search_path.clone());
+            }
+            search.name = name;
+            search.options = options;
+            search.updated = now;
+            search.clone()
+        }
+        None => {
+            let base = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis());
+            let mut n = 0;
+            let id = loop {
+                let id = format!("search-{base:x}-{n}");
+                if searches.iter().all(|search| search.id != id) {
+                    break id;
+                }
+                n += 1;
+            };
+            let search = SavedSearch {
+                id,
+                name,
+                options,
+                created: now,
+                updated: now,
+            };
+            searches.push(search.clone());
+            search
+        }
+    };
+
+    state.persist(&searches)?;
+    drop(searches);
+
+    if let Some(old_root) = moved_from {
+        rewatch(&saved, &old_root, state)?;
+    }
+    Ok(saved)
+}
+
+/// Moves the watcher of a live search whose root changed to the new root.
+///
+/// If the new root can't be watched the search stops being live.
+fn rewatch(search: &SavedSearch, old_root: &str, state: &SavedSearchState) -> Result<()> {
+    let mut live = state
+        .live
+        .lock()
+        .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+    let Some(watcher) = live.get_mut(&search.id) else {
+        return Ok(());
+    };
+    let _ = watcher.unwatch(Path::new(old_root));
+    if watcher
+        .watch(
+            Path::new(&search.options.search_path),
+            RecursiveMode::Recursive,
+        )
+        .is_err()
+    {
+        live.remove(&search.id);
+    }
+    Ok(())
+}
+
+/// Saves a new search, or replaces the name and options of the one with `id`.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_search(
+    name: String,
+    options: SearchOptions,
+    id: Option<String>,
+    app: AppHandle,
+) -> std::result::Result<SavedSearch, String> {
+    save_search_sync(
+        id.as_deref(),
+        &name,
+        options,
+        &app.state::<SavedSearchState>(),
+    )
+    .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn delete_saved_search_sync(id: &str, state: &SavedSearchState) -> Result<bool> {
+    let removed = {
+        let mut searches = lock(&state.searches)?;
+        let before = searches.len();
+        searches.retain(|search| search.id != id);
+        let removed = searches.len() != before;
+        if removed {
+            state.persist(&searches)?;
+        }
+        removed
+    };
+    unwatch_saved_search_sync(id, state)?;
+    Ok(removed)
+}
+
+/// Deletes a saved search. Returns `false` if there was none with `id`.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_saved_search(id: String, app: AppHandle) -> std::result::Result<bool, String> {
+    delete_saved_search_sync(&id, &app.state::<SavedSearchState>()).map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn open_saved_search_sync(id: &str, state: &SavedSearchState) -> Result<Vec<FileEntry>> {
+    run(&state.options(id)?)
+}
+
+/// Runs a saved search and returns its hits as a directory listing.
+#[tauri::command]
+#[specta::specta]
+pub async fn open_saved_search(
+    id: String,
+    app: AppHandle,
+) -> std::result::Result<Vec<FileEntry>, String> {
+    spawn_blocking(move || open_saved_search_sync(&id, &app.state::<SavedSearchState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn watch_saved_search_sync(
+    id: &str,
+    state: &SavedSearchState,
+    on_update: impl Fn(SavedSearchUpdate) + Send + 'static,
+) -> Result<Vec<FileEntry>> {
+    let options = state.options(id)?;
+    let entries = run(&options)?;
+
+    let mut live = state
+        .live
+        .lock()
+        .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+    if live.contains_key(id) {
+        return Ok(entries);
+    }
+
+    let (tx, rx) = channel();
+    let mut watcher = recommended_watcher(move |res: notify::Result<Event>| {
+        if res.is_ok_and(|event| !matches!(event.kind, EventKind::Access(_))) {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| FileManagerError::WatchError(e.to_string()))?;
+    watcher
+        .watch(Path::new(&options.search_path), RecursiveMode::Recursive)
+        .map_err(|e| FileManagerError::WatchError(e.to_string()))?;
+
+    spawn_refresher(
+        id.to_string(),
+        Arc::clone(&state.searches),
+        rx,
+        fingerprint(&entries),
+        on_update,
+    )?;
+    live.insert(id.to_string(), watcher);
+    Ok(entries)
+}
+
+/// Opens a saved search and keeps it live until `unwatch_saved_search`.
+///
+/// Returns the current listing; later listings arrive as `saved-search-updated` events.
+#[tauri::command]
+#[specta::specta]
+pub async fn watch_saved_search(
+    id: String,
+    app: AppHandle,
+) -> std::result::Result<Vec<FileEntry>, String> {
+    spawn_blocking(move || {
+        let emit_app = app.clone();
+        watch_saved_search_sync(&id, &app.state::<SavedSearchState>(), move |update| {
+            let _ = emit_app.emit("saved-search-updated", update);
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn unwatch_saved_search_sync(id: &str, state: &SavedSearchState) -> Result<bool> {
+    let mut live = state
+        .live
+        .lock()
+        .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+    // Dropping the watcher disconnects the refresher, which then exits.
+    Ok(live.remove(id).is_some())
+}
+
+/// Stops live updates for a saved search. Returns `false` if it wasn't watched.
+#[tauri::command]
+#[specta::specta]
+pub async fn unwatch_saved_search(id: String, app: AppHandle) -> std::result::Result<bool, String> {
+    unwatch_saved_search_sync(&id, &app.state::<SavedSearchState>()).map_err(Into::into)
+}
@@ -0,0 +1,116 @@
+//! Reading the files inside ZIP and TAR archives for content search.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+
+use crate::constants::{MAX_ARCHIVE_SEARCH_ENTRIES, MAX_OFFICE_FILE_SIZE, MAX_SEARCH_FILE_SIZE};
+use crate::error::{FileManagerError, Result};
+
+use super::documents::DocumentKind;
+
+/// Archive formats content search descends into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Detects the format from a file name's extension.
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if lower.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if lower.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Largest entry worth reading: documents are parsed, everything else is scanned as text.
+fn entry_size_limit(name: &str) -> u64 {
+    if DocumentKind::from_name(name).is_some() {
+        MAX_OFFICE_FILE_SIZE
+    } else {
+        MAX_SEARCH_FILE_SIZE
+    }
+}
+
+/// Calls `visit` with the inner path (`/`-separated) and contents of each regular file.
+///
+/// Oversized entries are skipped and at most `MAX_ARCHIVE_SEARCH_ENTRIES` entries
+/// are looked at. Returning `false` from `visit` stops early. Nested archives
+/// are passed to `visit` like any other file, not descended into.
+pub(super) fn for_each_file(
+    path: &Path,
+    kind: ArchiveKind,
+    visit: impl FnMut(&str, Vec<u8>) -> bool,
+) -> Result<()> {
+    let file = BufReader::new(File::open(path)?);
+    match kind {
+        ArchiveKind::Zip => for_each_zip_file(file, visit),
+        ArchiveKind::Tar => for_each_tar_file(file, visit),
+        ArchiveKind::TarGz => for_each_tar_file(GzDecoder::new(file), visit),
+    }
+}
+
+fn for_each_zip_file(
+    file: BufReader<File>,
+    mut visit: impl FnMut(&str, Vec<u8>) -> bool,
+) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| FileManagerError::ReadFileError(format!("Invalid ZIP archive: {e}")))?;
+
+    for index in 0..archive.len().min(MAX_ARCHIVE_SEARCH_ENTRIES) {
+        // Encrypted or unsupported entries are skipped.
+        let Ok(mut entry) = archive.by_index(index) else {
+            continue;
+        };
+        if !entry.is_file() || entry.size() > entry_size_limit(entry.name()) {
+            continue;
+        }
+
+        let name = entry.name().to_string();
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        if entry.read_to_end(&mut data).is_err() {
+            continue;
+        }
+        if !visit(&name, data) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn for_each_tar_file(
+    reader: impl Read,
+    mut visit: impl FnMut(&str, Vec<u8>) -> bool,
+) -> Result<()> {
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries()?.take(MAX_ARCHIVE_SEARCH_ENTRIES) {
+        // A corrupt header ends the stream; there is no way to resynchronize.
+        let mut entry = entry?;
+        let name = String::from_utf8_lossy(&entry.path_bytes())
+            .trim_start_matches("./")
+            .to_string();
+        if !entry.header().entry_type().is_file() || entry.size() > entry_size_limit(&name) {
+            continue;
+        }
+
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut data)?;
+        if !visit(&name, data) {
+            break;
+        }
+    }
+    Ok(())
+}
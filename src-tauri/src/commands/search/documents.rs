@@ -0,0 +1,370 @@
+//! Text extraction from office documents and PDFs for content search.
+//!
+//! Unlike the preview parsers nothing is truncated and no images are decoded,
+//! and every block of text keeps its location so hits can point at a
+//! paragraph, footnote, cell, slide or page. The content index reads office
+//! documents through [`office_text`] for the same reasons.
+
+use std::fs;
+use std::io::Cursor;
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use zip::ZipArchive;
+
+use crate::commands::office::{
+    footnotes, local_name, ooxml_paragraphs, parse_slide, part_path, part_relationships,
+    read_entry, slide_paths, walk_odf, OdfEvent,
+};
+use crate::constants::MAX_OFFICE_FILE_SIZE;
+use crate::error::{FileManagerError, Result};
+use crate::models::MatchLocation;
+
+/// Document formats whose text content search can extract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DocumentKind {
+    Docx,
+    Odt,
+    Spreadsheet,
+    Pptx,
+    Odp,
+    Pdf,
+}
+
+impl DocumentKind {
+    /// Detects the format from a file name's extension.
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        let (_, ext) = name.rsplit_once('.')?;
+        match ext.to_ascii_lowercase().as_str() {
+            "docx" => Some(Self::Docx),
+            "odt" => Some(Self::Odt),
+            "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => Some(Self::Spreadsheet),
+            "pptx" => Some(Self::Pptx),
+            "odp" => Some(Self::Odp),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// A block of extracted text and where it sits in the document.
+pub(super) struct TextUnit {
+    pub location: MatchLocation,
+    pub text: String,
+}
+
+/// Extracts the text of a document held in memory.
+pub(super) fn extract(kind: DocumentKind, data: &[u8]) -> Result<Vec<TextUnit>> {
+    match kind {
+        DocumentKind::Docx => docx_units(data),
+        DocumentKind::Odt => odt_units(data),
+        DocumentKind::Spreadsheet => spreadsheet_units(data),
+        DocumentKind::Pptx => slide_units(data),
+        DocumentKind::Odp => odp_units(data),
+        DocumentKind::Pdf => pdf_units(data),
+    }
+}
+
+/// The text of an office document on disk for the content index, one unit
+/// per line. PDFs are left to content search.
+///
+/// Returns `None` for other files, and empty text for documents over
+/// `MAX_OFFICE_FILE_SIZE` or that can't be read.
+pub(crate) fn office_text(path: &Path, size: u64) -> Option<String> {
+    let kind = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(DocumentKind::from_name)
+        .filter(|kind| *kind != DocumentKind::Pdf)?;
+    if size > MAX_OFFICE_FILE_SIZE {
+        return Some(String::new());
+    }
+    let units = fs::read(path)
+        .ok()
+        .and_then(|data| extract(kind, &data).ok())
+        .unwrap_or_default();
+    Some(
+        units
+            .into_iter()
+            .map(|unit| unit.text)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+fn parse_error(kind: &str, err: impl std::fmt::Display) -> FileManagerError {
+    FileManagerError::ReadFileError(format!("Invalid {kind}: {err}"))
+}
+
+fn open_archive<'a>(data: &'a [u8], kind: &str) -> Result<ZipArchive<Cursor<&'a [u8]>>> {
+    ZipArchive::new(Cursor::new(data)).map_err(|e| parse_error(kind, e))
+}
+
+fn required_entry(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+    kind: &str,
+) -> Result<String> {
+    read_entry(archive, name).ok_or_else(|| parse_error(kind, format!("missing {name}")))
+}
+
+fn docx_units(data: &[u8]) -> Result<Vec<TextUnit>> {
+    let mut archive = open_archive(data, "document")?;
+    let xml = required_entry(&mut archive, "word/document.xml", "document")?;
+    let paragraphs = ooxml_paragraphs(&xml).map_err(|e| parse_error("document", e))?;
+
+    let mut units: Vec<TextUnit> = paragraphs
+        .into_iter()
+        .enumerate()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(index, text)| TextUnit {
+            location: MatchLocation::Paragraph {
+                index: index as u32 + 1,
+            },
+            text,
+        })
+        .collect();
+    let notes = read_entry(&mut archive, "word/footnotes.xml")
+        .map(|xml| footnotes(&xml))
+        .unwrap_or_default();
+    units.extend(
+        notes
+            .into_iter()
+            .filter(|note| !note.text.is_empty())
+            .map(|note| TextUnit {
+                location: MatchLocation::Footnote { id: note.id },
+                text: note.text,
+            }),
+    );
+    Ok(units)
+}
+
+/// ODT paragraphs, numbered like the preview numbers them: non-empty
+/// paragraphs in the order they end. Only tracked deletions are left out.
+fn odt_units(data: &[u8]) -> Result<Vec<TextUnit>> {
+    let mut archive = open_archive(data, "document")?;
+    let xml = required_entry(&mut archive, "content.xml", "document")?;
+    let mut units = Vec::new();
+    walk_odf(&xml, &[b"tracked-changes"], |event| {
+        if let OdfEvent::Paragraph { text, .. } = event {
+            units.push(TextUnit {
+                location: MatchLocation::Paragraph {
+                    index: units.len() as u32 + 1,
+                },
+                text,
+            });
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| parse_error("document", e))?;
+    Ok(units)
+}
+
+fn spreadsheet_units(data: &[u8]) -> Result<Vec<TextUnit>> {
+    let mut workbook =
+        open_workbook_auto_from_rs(Cursor::new(data)).map_err(|e| parse_error("spreadsheet", e))?;
+    let mut units = Vec::new();
+
+    for sheet in workbook.sheet_names() {
+        let Ok(range) = workbook.worksheet_range(&sheet) else {
+            continue;
+        };
+        let (first_row, first_col) = range.start().unwrap_or((0, 0));
+
+        for (row, col, value) in range.used_cells() {
+            if matches!(value, Data::Empty) {
+                continue;
+            }
+            let text = value.to_string();
+            if text.trim().is_empty() {
+                continue;
+            }
+            units.push(TextUnit {
+                location: MatchLocation::Cell {
+                    sheet: sheet.clone(),
+                    cell: cell_reference(first_row + row as u32, first_col + col as u32),
+                },
+                text,
+            });
+        }
+    }
+
+    Ok(units)
+}
+
+/// A1-style reference for zero-based coordinates, e.g. `(3, 27)` -> `AB4`.
+fn cell_reference(row: u32, col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8_lossy(&letters), row + 1)
+}
+
+/// Slide text and speaker notes, numbered in presentation order as the
+/// preview numbers slides.
+fn slide_units(data: &[u8]) -> Result<Vec<TextUnit>> {
+    let mut archive = open_archive(data, "presentation")?;
+
+    let mut units = Vec::new();
+    for (index, name) in slide_paths(&mut archive).iter().enumerate() {
+        let number = index as u32 + 1;
+        let xml = required_entry(&mut archive, name, "presentation")?;
+        let paragraphs = ooxml_paragraphs(&xml).map_err(|e| parse_error("presentation", e))?;
+        units.extend(
+            paragraphs
+                .into_iter()
+                .filter(|text| !text.trim().is_empty())
+                .map(|text| TextUnit {
+                    location: MatchLocation::Slide { number },
+                    text,
+                }),
+        );
+
+        let (dir, rels) = part_relationships(&mut archive, name);
+        let notes = rels
+            .values()
+            .find(|rel| rel.kind.ends_with("/notesSlide"))
+            .and_then(|rel| read_entry(&mut archive, &part_path(&dir, &rel.target)))
+            .and_then(|xml| parse_slide(&xml).ok())
+            .and_then(|notes| notes.notes());
+        units.extend(notes.map(|text| TextUnit {
+            location: MatchLocation::Slide { number },
+            text,
+        }));
+    }
+
+    Ok(units)
+}
+
+/// ODP slide text, speaker notes included, by `draw:page`.
+fn odp_units(data: &[u8]) -> Result<Vec<TextUnit>> {
+    let mut archive = open_archive(data, "presentation")?;
+    let xml = required_entry(&mut archive, "content.xml", "presentation")?;
+    let mut units = Vec::new();
+    let mut number = 0;
+    walk_odf(&xml, &[b"tracked-changes"], |event| {
+        match event {
+            OdfEvent::Start(e) if local_name(e.name().as_ref()) == b"page" => number += 1,
+            OdfEvent::Paragraph { text, .. } if number > 0 => units.push(TextUnit {
+                location: MatchLocation::Slide { number },
+                text,
+            }),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| parse_error("presentation", e))?;
+    Ok(units)
+}
+
+fn pdf_units(data: &[u8]) -> Result<Vec<TextUnit>> {
+    let document = lopdf::Document::load_mem(data).map_err(|e| parse_error("PDF", e))?;
+
+    Ok(document
+        .get_pages()
+        .into_keys()
+        .filter_map(|number| {
+            let text = document.extract_text(&[number]).ok()?;
+            (!text.trim().is_empty()).then_some(TextUnit {
+                location: MatchLocation::Page { number },
+                text,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_references_use_spreadsheet_columns() {
+        assert_eq!(cell_reference(0, 0), "A1");
+        assert_eq!(cell_reference(3, 25), "Z4");
+        assert_eq!(cell_reference(3, 27), "AB4");
+        assert_eq!(cell_reference(9, 701), "ZZ10");
+        assert_eq!(cell_reference(0, 702), "AAA1");
+    }
+
+    #[test]
+    fn odf_paragraphs_include_spans_and_spacing() {
+        let xml = r#"<office:text>
+            <text:h>Heading</text:h>
+            <text:p>Plain <text:span>styled</text:span><text:s/>text</text:p>
+        </office:text>"#;
+
+        let mut paragraphs = Vec::new();
+        walk_odf(xml, &[b"tracked-changes"], |event| {
+            if let OdfEvent::Paragraph { text, .. } = event {
+                paragraphs.push(text);
+            }
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(paragraphs, vec!["Heading", "Plain styled text"]);
+    }
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(data.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn locations(units: &[TextUnit]) -> Vec<(MatchLocation, &str)> {
+        units
+            .iter()
+            .map(|unit| (unit.location.clone(), unit.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn docx_footnotes_and_odp_slides_are_extracted() {
+        let docx = zip(&[
+            (
+                "word/document.xml",
+                "<w:document><w:body><w:p><w:r><w:t>Body</w:t></w:r></w:p></w:body></w:document>",
+            ),
+            (
+                "word/footnotes.xml",
+                r#"<w:footnotes><w:footnote w:type="separator" w:id="-1"><w:p/></w:footnote>
+                <w:footnote w:id="1"><w:p><w:r><w:t>Source note</w:t></w:r></w:p></w:footnote></w:footnotes>"#,
+            ),
+        ]);
+        assert_eq!(
+            locations(&extract(DocumentKind::Docx, &docx).unwrap()),
+            vec![
+                (MatchLocation::Paragraph { index: 1 }, "Body"),
+                (MatchLocation::Footnote { id: 1 }, "Source note"),
+            ]
+        );
+
+        let odp = zip(&[(
+            "content.xml",
+            r#"<office:presentation>
+                <draw:page><draw:frame><draw:text-box><text:p>Intro</text:p></draw:text-box></draw:frame>
+                    <presentation:notes><draw:frame><draw:text-box><text:p>Say hello</text:p></draw:text-box></draw:frame></presentation:notes></draw:page>
+                <draw:page><draw:frame><draw:text-box><text:p>Outro</text:p></draw:text-box></draw:frame></draw:page>
+            </office:presentation>"#,
+        )]);
+        assert_eq!(
+            locations(&extract(DocumentKind::Odp, &odp).unwrap()),
+            vec![
+                (MatchLocation::Slide { number: 1 }, "Intro"),
+                (MatchLocation::Slide { number: 1 }, "Say hello"),
+                (MatchLocation::Slide { number: 2 }, "Outro"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Evaluation of parsed query expressions against walker entries.
+
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::DirEntry;
+
+use crate::constants::MAX_CONTENT_MATCHES_PER_FILE;
+use crate::error::{FileManagerError, Result};
+use crate::models::{ContentMatch, QueryExpr, SearchOptions};
+
+use super::filters::EntryFilter;
+use super::{search_entry_content, text_contains};
+
+enum Node {
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+    /// Index into [`CompiledQuery::terms`].
+    Text(usize),
+    /// Index into [`CompiledQuery::terms`].
+    Content(usize),
+    Name(GlobMatcher),
+    PathGlob(GlobMatcher),
+    PathContains(String),
+    Extension(String),
+    Filter(EntryFilter),
+}
+
+struct Term {
+    value: String,
+    /// False for terms under an odd number of negations; their matches aren't reported.
+    positive: bool,
+}
+
+/// A [`QueryExpr`] prepared once per search and shared by walker threads.
+pub(super) struct CompiledQuery {
+    root: Node,
+    terms: Vec<Term>,
+    /// `path:` terms match paths relative to this directory.
+    search_root: PathBuf,
+    case_sensitive: bool,
+    search_content: bool,
+}
+
+impl CompiledQuery {
+    pub(super) fn compile(expr: &QueryExpr, options: &SearchOptions) -> Result<Self> {
+        let mut terms = Vec::new();
+        let root = compile_node(expr, options.case_sensitive, true, &mut terms)?;
+
+        Ok(Self {
+            root,
+            terms,
+            search_root: PathBuf::from(&options.search_path),
+            case_sensitive: options.case_sensitive,
+            search_content: options.search_content,
+        })
+    }
+
+    /// Evaluates the query for `entry`.
+    ///
+    /// Returns the content matches of non-negated text terms when the entry matches.
+    /// Each term's content is searched at most once per entry.
+    pub(super) fn matches(&self, entry: &DirEntry, name: &str) -> Option<Vec<ContentMatch>> {
+        let mut eval = Evaluation {
+            query: self,
+            entry,
+            name,
+            content: (0..self.terms.len()).map(|_| None).collect(),
+        };
+
+        if !eval.node(&self.root) {
+            return None;
+        }
+
+        let mut matches: Vec<ContentMatch> = eval
+            .content
+            .into_iter()
+            .zip(&self.terms)
+            .filter(|(_, term)| term.positive)
+            .filter_map(|(found, _)| found)
+            .flatten()
+            .collect();
+
+        matches.sort_by_key(|m| (m.line_number, m.match_start));
+        matches.dedup_by_key(|m| (m.line_number, m.match_start));
+        matches.truncate(MAX_CONTENT_MATCHES_PER_FILE);
+        Some(matches)
+    }
+}
+
+fn compile_node(
+    expr: &QueryExpr,
+    case_sensitive: bool,
+    positive: bool,
+    terms: &mut Vec<Term>,
+) -> Result<Node> {
+    let mut push_term = |value: &str| {
+        terms.push(Term {
+            value: value.to_string(),
+            positive,
+        });
+        terms.len() - 1
+    };
+
+    Ok(match expr {
+        QueryExpr::And { items } => Node::And(
+            items
+                .iter()
+                .map(|item| compile_node(item, case_sensitive, positive, terms))
+                .collect::<Result<_>>()?,
+        ),
+        QueryExpr::Or { items } => Node::Or(
+            items
+                .iter()
+                .map(|item| compile_node(item, case_sensitive, positive, terms))
+                .collect::<Result<_>>()?,
+        ),
+        QueryExpr::Not { item } => Node::Not(Box::new(compile_node(
+            item,
+            case_sensitive,
+            !positive,
+            terms,
+        )?)),
+        QueryExpr::Text { value } => Node::Text(push_term(value)),
+        QueryExpr::Content { value } => Node::Content(push_term(value)),
+        QueryExpr::Name { pattern } => Node::Name(compile_glob(pattern, case_sensitive)?),
+        QueryExpr::Path { pattern } if pattern.contains(['*', '?', '[', '{']) => {
+            Node::PathGlob(compile_glob(pattern, case_sensitive)?)
+        }
+        QueryExpr::Path { pattern } => Node::PathContains(pattern.clone()),
+        QueryExpr::Extension { value } => {
+            Node::Extension(value.trim_start_matches('.').to_string())
+        }
+        QueryExpr::Filter { filters } => Node::Filter(EntryFilter::compile(filters)?),
+    })
+}
+
+/// Compiles a glob where `*` stays within one path component and `**` crosses them.
+fn compile_glob(pattern: &str, case_sensitive: bool) -> Result<GlobMatcher> {
+    GlobBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .literal_separator(true)
+        .build()
+        .map(|glob| glob.compile_matcher())
+        .map_err(|e| FileManagerError::InvalidPattern(e.to_string()))
+}
+
+struct Evaluation<'a> {
+    query: &'a CompiledQuery,
+    entry: &'a DirEntry,
+    name: &'a str,
+    /// Lazily computed content matches, one slot per term.
+    content: Vec<Option<Vec<ContentMatch>>>,
+}
+
+impl Evaluation<'_> {
+    fn node(&mut self, node: &Node) -> bool {
+        let case_sensitive = self.query.case_sensitive;
+
+        match node {
+            Node::And(items) => items.iter().all(|item| self.node(item)),
+            Node::Or(items) => items.iter().any(|item| self.node(item)),
+            Node::Not(item) => !self.node(item),
+            Node::Text(term) => {
+                let name_matches =
+                    text_contains(self.name, &self.query.terms[*term].value, case_sensitive);
+                let content_matches = self.query.search_content && self.has_content(*term);
+                name_matches || content_matches
+            }
+            Node::Content(term) => self.has_content(*term),
+            Node::Name(glob) => glob.is_match(self.name),
+            Node::PathGlob(glob) => glob.is_match(self.relative_path()),
+            Node::PathContains(needle) => text_contains(
+                &self.relative_path().to_string_lossy(),
+                needle,
+                case_sensitive,
+            ),
+            Node::Extension(ext) => self
+                .entry
+                .path()
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case(ext)),
+            Node::Filter(filter) => filter.matches(self.entry),
+        }
+    }
+
+    /// The entry's path below the search root, so directories above it don't match.
+    fn relative_path(&self) -> &Path {
+        let path = self.entry.path();
+        path.strip_prefix(&self.query.search_root).unwrap_or(path)
+    }
+
+    fn has_content(&mut self, term: usize) -> bool {
+        let query = self.query;
+        let entry = self.entry;
+        !self.content[term]
+            .get_or_insert_with(|| {
+                search_entry_content(entry, &query.terms[term].value, query.case_sensitive)
+            })
+            .is_empty()
+    }
+}
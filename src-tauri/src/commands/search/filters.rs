@@ -0,0 +1,241 @@
+//! Metadata predicates applied to search candidates.
+
+use std::fs::{self, Metadata};
+use std::path::Path;
+use std::time::SystemTime;
+
+use ignore::DirEntry;
+
+use crate::error::{FileManagerError, Result};
+use crate::models::{EntryKind, SearchFilters, TimeBound, TimeRange};
+use crate::utils::{is_hidden, system_time_to_timestamp};
+
+/// Extensions treated as executable on Windows, which has no execute bit.
+#[cfg(windows)]
+const WINDOWS_EXECUTABLE_EXTENSIONS: &[&str] = &["exe", "bat", "cmd", "com", "ps1", "msi"];
+
+/// Inclusive timestamp range with relative bounds resolved.
+#[derive(Debug, Clone, Copy)]
+struct ResolvedRange {
+    after: Option<i64>,
+    before: Option<i64>,
+}
+
+impl ResolvedRange {
+    fn resolve(range: &TimeRange, now: i64) -> Self {
+        let resolve = |bound: TimeBound| match bound {
+            TimeBound::Absolute { timestamp } => timestamp,
+            TimeBound::Relative { seconds } => {
+                now.saturating_sub(i64::try_from(seconds).unwrap_or(i64::MAX))
+            }
+        };
+        Self {
+            after: range.after.map(resolve),
+            before: range.before.map(resolve),
+        }
+    }
+
+    fn contains(&self, timestamp: Option<i64>) -> bool {
+        let Some(ts) = timestamp else {
+            return false;
+        };
+        self.after.is_none_or(|after| ts >= after) && self.before.is_none_or(|before| ts <= before)
+    }
+}
+
+/// Resolves a time range to inclusive `(after, before)` timestamps as of now.
+pub(crate) fn resolve_time_range(range: &TimeRange) -> (Option<i64>, Option<i64>) {
+    let now = system_time_to_timestamp(SystemTime::now()).unwrap_or(0);
+    let resolved = ResolvedRange::resolve(range, now);
+    (resolved.after, resolved.before)
+}
+
+/// [`SearchFilters`] prepared once per search and shared by walker threads.
+#[derive(Debug, Clone)]
+pub(crate) struct EntryFilter {
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified: Option<ResolvedRange>,
+    created: Option<ResolvedRange>,
+    kind: Option<EntryKind>,
+    empty: Option<bool>,
+    hidden: Option<bool>,
+    executable: Option<bool>,
+    owner_uid: Option<u32>,
+}
+
+impl EntryFilter {
+    /// Resolves relative time bounds against the current time and looks up the owner.
+    pub(crate) fn compile(filters: &SearchFilters) -> Result<Self> {
+        let now = system_time_to_timestamp(SystemTime::now()).unwrap_or(0);
+
+        Ok(Self {
+            min_size: filters.min_size,
+            max_size: filters.max_size,
+            modified: filters
+                .modified
+                .as_ref()
+                .map(|r| ResolvedRange::resolve(r, now)),
+            created: filters
+                .created
+                .as_ref()
+                .map(|r| ResolvedRange::resolve(r, now)),
+            kind: filters.kind,
+            empty: filters.empty,
+            hidden: filters.hidden,
+            executable: filters.executable,
+            owner_uid: filters.owner.as_deref().map(resolve_owner).transpose()?,
+        })
+    }
+
+    /// Returns true if the entry satisfies every configured predicate.
+    ///
+    /// Metadata is only read when a predicate needs it.
+    pub(crate) fn matches(&self, entry: &DirEntry) -> bool {
+        let path = entry.path();
+
+        if let Some(kind) = self.kind {
+            if entry_kind(entry) != Some(kind) {
+                return false;
+            }
+        }
+
+        if let Some(hidden) = self.hidden {
+            if is_hidden(path) != hidden {
+                return false;
+            }
+        }
+
+        if !self.needs_metadata() {
+            return true;
+        }
+
+        let Ok(meta) = entry.metadata() else {
+            return false;
+        };
+
+        self.matches_metadata(path, &meta)
+    }
+
+    fn needs_metadata(&self) -> bool {
+        self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.modified.is_some()
+            || self.created.is_some()
+            || self.empty.is_some()
+            || self.executable.is_some()
+            || self.owner_uid.is_some()
+    }
+
+    /// Checks the metadata-based predicates for `path`.
+    pub(crate) fn matches_metadata(&self, path: &Path, meta: &Metadata) -> bool {
+        let size = if meta.is_file() { meta.len() } else { 0 };
+
+        if self.min_size.is_some_and(|min| size < min) {
+            return false;
+        }
+        if self.max_size.is_some_and(|max| size > max) {
+            return false;
+        }
+
+        if let Some(range) = self.modified {
+            if !range.contains(meta.modified().ok().and_then(system_time_to_timestamp)) {
+                return false;
+            }
+        }
+        if let Some(range) = self.created {
+            if !range.contains(meta.created().ok().and_then(system_time_to_timestamp)) {
+                return false;
+            }
+        }
+
+        if let Some(empty) = self.empty {
+            if is_empty(path, meta) != empty {
+                return false;
+            }
+        }
+
+        if let Some(executable) = self.executable {
+            if is_executable(path, meta) != executable {
+                return false;
+            }
+        }
+
+        if let Some(uid) = self.owner_uid {
+            if owner_uid(meta) != Some(uid) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn entry_kind(entry: &DirEntry) -> Option<EntryKind> {
+    if entry.path_is_symlink() {
+        return Some(EntryKind::Symlink);
+    }
+    let file_type = entry.file_type()?;
+    if file_type.is_dir() {
+        Some(EntryKind::Directory)
+    } else if file_type.is_file() {
+        Some(EntryKind::File)
+    } else {
+        None
+    }
+}
+
+fn is_empty(path: &Path, meta: &Metadata) -> bool {
+    if meta.is_dir() {
+        fs::read_dir(path)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false)
+    } else {
+        meta.len() == 0
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(_path: &Path, meta: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    meta.is_file() && meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path, meta: &Metadata) -> bool {
+    meta.is_file()
+        && crate::utils::get_extension(path)
+            .is_some_and(|ext| WINDOWS_EXECUTABLE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+#[cfg(unix)]
+fn owner_uid(meta: &Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(meta.uid())
+}
+
+#[cfg(not(unix))]
+fn owner_uid(_meta: &Metadata) -> Option<u32> {
+    None
+}
+
+/// Resolves an owner given as a user name or a numeric uid.
+#[cfg(unix)]
+fn resolve_owner(owner: &str) -> Result<u32> {
+    let owner = owner.trim();
+    if let Ok(uid) = owner.parse::<u32>() {
+        return Ok(uid);
+    }
+    uzers::get_user_by_name(owner)
+        .map(|user| user.uid())
+        .ok_or_else(|| FileManagerError::InvalidPattern(format!("Unknown owner: {owner}")))
+}
+
+#[cfg(not(unix))]
+fn resolve_owner(_owner: &str) -> Result<u32> {
+    Err(FileManagerError::UnsupportedPlatform(
+        "owner filter".to_string(),
+    ))
+}
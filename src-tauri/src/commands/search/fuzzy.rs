@@ -0,0 +1,311 @@
+//! Fuzzy filename search with fzf-style ranking.
+//!
+//! The walked candidate list is cached per root, so per-keystroke queries only
+//! pay for matching, not for the walk. A cached list is dropped when a
+//! recursive watcher on its root reports a change, or after
+//! `FUZZY_CANDIDATE_TTL_SECS` when the root can't be watched.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Config, Matcher, Utf32String};
+
+use crate::constants::{FUZZY_CANDIDATE_TTL_SECS, MAX_FUZZY_CANDIDATES};
+use crate::error::Result;
+use crate::models::{FuzzyMatch, FuzzySearchOptions, SearchOptions};
+
+use super::walker::{self, WalkEvent};
+
+/// Added when the query matches within the file name alone.
+const BASENAME_BONUS: i64 = 24;
+
+/// Subtracted per directory level below the search root.
+const DEPTH_PENALTY: i64 = 2;
+
+/// Bonus for the most recently used path; later entries get proportionally less.
+const RECENT_BONUS: i64 = 64;
+
+/// Number of roots whose candidate lists are kept in memory.
+const MAX_CACHED_ROOTS: usize = 4;
+
+/// A walked entry prepared for matching.
+pub(super) struct Candidate {
+    path: String,
+    relative: String,
+    haystack: Utf32String,
+    /// Character index where the file name starts in `relative`.
+    name_start: usize,
+    depth: usize,
+    is_dir: bool,
+}
+
+impl Candidate {
+    fn new(root: &Path, entry: &ignore::DirEntry) -> Option<Self> {
+        let path = entry.path();
+        let relative = path.strip_prefix(root).ok()?.to_str()?.to_string();
+        let name = path.file_name()?.to_str()?;
+        let name_start = relative.chars().count() - name.chars().count();
+
+        // Char-per-char haystack so match indices map back to `relative`.
+        let haystack = if relative.is_ascii() {
+            Utf32String::Ascii(relative.clone().into_boxed_str())
+        } else {
+            Utf32String::Unicode(relative.chars().collect())
+        };
+
+        Some(Self {
+            path: path.to_string_lossy().to_string(),
+            relative,
+            haystack,
+            name_start,
+            depth: entry.depth(),
+            is_dir: entry.file_type().is_some_and(|t| t.is_dir()),
+        })
+    }
+
+    fn name(&self) -> String {
+        self.relative.chars().skip(self.name_start).collect()
+    }
+}
+
+struct CachedRoot {
+    built: Instant,
+    candidates: Arc<Vec<Candidate>>,
+    /// Set by `watcher` when anything below the root changes.
+    changed: Arc<AtomicBool>,
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl CachedRoot {
+    fn is_fresh(&self) -> bool {
+        if self.changed.load(Ordering::Relaxed) {
+            return false;
+        }
+        // Without a watcher only the age tells whether the list is current.
+        self.watcher.is_some()
+            || self.built.elapsed() < Duration::from_secs(FUZZY_CANDIDATE_TTL_SECS)
+    }
+}
+
+/// Watches `root` recursively, raising `changed` on any modification.
+fn watch_root(root: &Path, changed: &Arc<AtomicBool>) -> Option<RecommendedWatcher> {
+    let changed = changed.clone();
+    let mut watcher = recommended_watcher(move |res: notify::Result<Event>| {
+        if res.map_or(true, |event| !matches!(event.kind, EventKind::Access(_))) {
+            changed.store(true, Ordering::Relaxed);
+        }
+    })
+    .ok()?;
+    watcher.watch(root, RecursiveMode::Recursive).ok()?;
+    Some(watcher)
+}
+
+/// Candidate lists of recently searched roots.
+#[derive(Default)]
+pub(super) struct CandidateCache {
+    roots: Mutex<HashMap<(PathBuf, bool), CachedRoot>>,
+}
+
+impl CandidateCache {
+    /// Returns the cached candidates for the options' root, walking it if stale.
+    pub(super) fn get_or_walk(&self, options: &FuzzySearchOptions) -> Result<Arc<Vec<Candidate>>> {
+        let key = (
+            PathBuf::from(&options.search_path),
+            options.respect_gitignore,
+        );
+
+        if let Ok(roots) = self.roots.lock() {
+            if let Some(cached) = roots.get(&key).filter(|c| c.is_fresh()) {
+                return Ok(cached.candidates.clone());
+            }
+        }
+
+        // Watch before walking so changes made during the walk aren't missed.
+        let changed = Arc::new(AtomicBool::new(false));
+        let watcher = watch_root(&key.0, &changed);
+
+        // Walk without holding the lock so other roots stay searchable.
+        let candidates = Arc::new(walk_candidates(options)?);
+
+        if let Ok(mut roots) = self.roots.lock() {
+            if roots.len() >= MAX_CACHED_ROOTS && !roots.contains_key(&key) {
+                let oldest = roots
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.built)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    roots.remove(&oldest);
+                }
+            }
+            roots.insert(
+                key,
+                CachedRoot {
+                    built: Instant::now(),
+                    candidates: candidates.clone(),
+                    changed,
+                    watcher,
+                },
+            );
+        }
+
+        Ok(candidates)
+    }
+}
+
+fn walk_candidates(options: &FuzzySearchOptions) -> Result<Vec<Candidate>> {
+    let root = Path::new(&options.search_path);
+    let walk_options = SearchOptions {
+        search_path: options.search_path.clone(),
+        respect_gitignore: options.respect_gitignore,
+        ..Default::default()
+    };
+    let mut candidates = Vec::new();
+
+    walker::walk_parallel(
+        &walk_options,
+        &AtomicBool::new(false),
+        None,
+        |entry| {
+            if entry.depth() == 0 {
+                return None;
+            }
+            Candidate::new(root, entry)
+        },
+        |event| match event {
+            WalkEvent::Progress(..) => true,
+            WalkEvent::Match(candidate) => {
+                candidates.push(candidate);
+                if candidates.len() >= 2 * MAX_FUZZY_CANDIDATES {
+                    keep_shallowest(&mut candidates);
+                }
+                true
+            }
+        },
+    )?;
+
+    keep_shallowest(&mut candidates);
+    Ok(candidates)
+}
+
+/// Cuts `candidates` to the `MAX_FUZZY_CANDIDATES` shallowest entries, by
+/// relative path within a level, so the kept set doesn't depend on walk order.
+fn keep_shallowest(candidates: &mut Vec<Candidate>) {
+    if candidates.len() > MAX_FUZZY_CANDIDATES {
+        candidates.select_nth_unstable_by(MAX_FUZZY_CANDIDATES, |a, b| {
+            (a.depth, &a.relative).cmp(&(b.depth, &b.relative))
+        });
+        candidates.truncate(MAX_FUZZY_CANDIDATES);
+    }
+}
+
+/// Scores every candidate against `query` and returns the best `max_results`.
+///
+/// The query uses fzf syntax: space-separated terms, `'exact`, `^prefix`,
+/// `suffix$` and `!negation`. Smart case applies.
+pub(super) fn rank(
+    candidates: &[Candidate],
+    query: &str,
+    recent_paths: &[String],
+    max_results: usize,
+) -> Vec<FuzzyMatch> {
+    let pattern = Pattern::parse(query, CaseMatching::Smart, Normalization::Smart);
+    let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+
+    let mut recent_rank: HashMap<&str, usize> = HashMap::new();
+    for (rank, path) in recent_paths.iter().enumerate() {
+        recent_rank.entry(path.as_str()).or_insert(rank);
+    }
+    let recent_bonus = |path: &str| {
+        recent_rank.get(path).map_or(0, |&rank| {
+            let total = recent_paths.len() as i64;
+            RECENT_BONUS * (total - rank as i64) / total
+        })
+    };
+
+    // Score everything first; indices are only computed for the survivors.
+    let mut scored: Vec<(i64, bool, &Candidate)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let path_score = i64::from(pattern.score(candidate.haystack.slice(..), &mut matcher)?);
+            let name_score = pattern
+                .score(
+                    candidate.haystack.slice(candidate.name_start..),
+                    &mut matcher,
+                )
+                .map(|score| i64::from(score) + BASENAME_BONUS);
+
+            let (score, on_name) = match name_score {
+                Some(name_score) if name_score >= path_score => (name_score, true),
+                _ => (path_score, false),
+            };
+            let depth = candidate.depth.saturating_sub(1) as i64;
+            let score = score - depth * DEPTH_PENALTY + recent_bonus(&candidate.path);
+
+            Some((score, on_name, candidate))
+        })
+        .collect();
+
+    scored.sort_unstable_by(|(a_score, _, a), (b_score, _, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.relative.len().cmp(&b.relative.len()))
+            .then_with(|| a.relative.cmp(&b.relative))
+    });
+    scored.truncate(max_results);
+
+    let mut indices = Vec::new();
+    scored
+        .into_iter()
+        .map(|(score, on_name, candidate)| {
+            indices.clear();
+            if on_name {
+                pattern.indices(
+                    candidate.haystack.slice(candidate.name_start..),
+                    &mut matcher,
+                    &mut indices,
+                );
+                let offset = candidate.name_start as u32;
+                indices.iter_mut().for_each(|i| *i += offset);
+            } else {
+                pattern.indices(candidate.haystack.slice(..), &mut matcher, &mut indices);
+            }
+            indices.sort_unstable();
+            indices.dedup();
+
+            FuzzyMatch {
+                path: candidate.path.clone(),
+                name: candidate.name(),
+                relative_path: candidate.relative.clone(),
+                is_dir: candidate.is_dir,
+                score,
+                indices: utf16_indices(&candidate.relative, &indices),
+            }
+        })
+        .collect()
+}
+
+/// Converts sorted character indices into UTF-16 offsets for the frontend.
+fn utf16_indices(text: &str, char_indices: &[u32]) -> Vec<u32> {
+    if text.is_ascii() {
+        return char_indices.to_vec();
+    }
+
+    let mut offsets = Vec::with_capacity(char_indices.len());
+    let mut wanted = char_indices.iter().peekable();
+    let mut utf16 = 0u32;
+
+    for (index, ch) in text.chars().enumerate() {
+        if wanted.peek().is_some_and(|&&i| i as usize == index) {
+            offsets.push(utf16);
+            wanted.next();
+        }
+        utf16 += ch.len_utf16() as u32;
+    }
+
+    offsets
+}
@@ -0,0 +1,931 @@
+//! File search functionality with content search support.
+
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::{async_runtime::spawn_blocking, AppHandle, Emitter, Manager, Window};
+
+use crate::constants::{
+    ARCHIVE_PATH_SEPARATOR, DEFAULT_MAX_SEARCH_RESULTS, MAX_ARCHIVE_SEARCH_SIZE,
+    MAX_CONTENT_MATCHES_PER_FILE, MAX_OFFICE_FILE_SIZE, MAX_SEARCH_FILE_SIZE,
+};
+use crate::error::{FileManagerError, Result};
+use crate::models::{
+    ContentMatch, FuzzyMatch, FuzzySearchOptions, QueryError, ReplaceOptions, ReplaceReport,
+    ReplaceUndoReport, SearchBatch, SearchComplete, SearchOptions, SearchProgress, SearchResult,
+};
+
+mod archive;
+mod documents;
+mod expr;
+pub(crate) mod filters;
+mod fuzzy;
+mod query;
+mod replace;
+mod walker;
+
+pub(crate) use documents::office_text;
+
+use archive::ArchiveKind;
+use documents::DocumentKind;
+use expr::CompiledQuery;
+use filters::EntryFilter;
+use fuzzy::CandidateCache;
+use replace::ReplaceHistory;
+use walker::{PathCutoff, WalkEvent};
+
+const SEARCH_RESULT_BATCH_SIZE: usize = 25;
+
+struct ActiveSearch {
+    /// Label of the window that started the search.
+    owner: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Global state for tracking in-flight streamed searches.
+///
+/// Each window has at most one active search: starting a new one cancels the previous.
+/// Also caches walked candidates for fuzzy search and keeps undo records of replacements.
+pub struct SearchState {
+    searches: Mutex<HashMap<String, ActiveSearch>>,
+    fuzzy: CandidateCache,
+    replacements: ReplaceHistory,
+}
+
+impl SearchState {
+    pub fn new() -> Self {
+        Self {
+            searches: Mutex::new(HashMap::new()),
+            fuzzy: CandidateCache::default(),
+            replacements: ReplaceHistory::default(),
+        }
+    }
+
+    /// Registers a search and returns its cancellation flag.
+    ///
+    /// Any search still running for the same `owner` is cancelled and forgotten.
+    #[doc(hidden)]
+    pub fn begin(
+        &self,
+        search_id: &str,
+        owner: &str,
+    ) -> std::result::Result<Arc<AtomicBool>, String> {
+        let mut searches = self.searches.lock().map_err(|e| e.to_string())?;
+
+        searches.retain(|_, search| {
+            if search.owner == owner {
+                search.cancelled.store(true, Ordering::Relaxed);
+                false
+            } else {
+                true
+            }
+        });
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        searches.insert(
+            search_id.to_string(),
+            ActiveSearch {
+                owner: owner.to_string(),
+                cancelled: cancelled.clone(),
+            },
+        );
+
+        Ok(cancelled)
+    }
+
+    /// Removes a finished search from the registry.
+    #[doc(hidden)]
+    pub fn finish(&self, search_id: &str) {
+        if let Ok(mut searches) = self.searches.lock() {
+            searches.remove(search_id);
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn active_search_count(&self) -> usize {
+        self.searches.lock().map(|s| s.len()).unwrap_or(0)
+    }
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Searches for files matching the given options.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_files(
+    options: SearchOptions,
+) -> std::result::Result<Vec<SearchResult>, String> {
+    spawn_blocking(move || search_files_sync(&options))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+/// Streaming search with progress events.
+///
+/// Starting a stream cancels the previous search from the same window. Every emitted
+/// event carries `search_id` so the frontend can drop results of superseded searches.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_files_stream(
+    options: SearchOptions,
+    search_id: String,
+    window: Window,
+    app: AppHandle,
+) -> std::result::Result<Vec<SearchResult>, String> {
+    let cancelled = app
+        .state::<SearchState>()
+        .begin(&search_id, window.label())?;
+
+    spawn_blocking(move || {
+        let result = search_files_with_progress(&options, &search_id, &cancelled, Some(&app));
+        app.state::<SearchState>().finish(&search_id);
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn cancel_search_sync(
+    search_id: &str,
+    state: &SearchState,
+) -> std::result::Result<bool, String> {
+    let mut searches = state.searches.lock().map_err(|e| e.to_string())?;
+
+    match searches.remove(search_id) {
+        Some(search) => {
+            search.cancelled.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Cancels a streamed search. Returns `false` if it already finished.
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_search(search_id: String, app: AppHandle) -> std::result::Result<bool, String> {
+    let state = app.state::<SearchState>();
+    cancel_search_sync(&search_id, &state)
+}
+
+/// Streaming search without an `AppHandle` (integration tests).
+#[doc(hidden)]
+pub fn search_files_cancellable_sync(
+    options: &SearchOptions,
+    search_id: &str,
+    cancelled: &AtomicBool,
+) -> Result<Vec<SearchResult>> {
+    search_files_with_progress(options, search_id, cancelled, None)
+}
+
+fn emit_search_event<S: Serialize + Clone>(app: Option<&AppHandle>, event: &str, payload: S) {
+    if let Some(app) = app {
+        let _ = app.emit(event, payload);
+    }
+}
+
+/// Search with progress reporting.
+fn search_files_with_progress(
+    options: &SearchOptions,
+    search_id: &str,
+    cancelled: &AtomicBool,
+    app: Option<&AppHandle>,
+) -> Result<Vec<SearchResult>> {
+    let search_path = Path::new(&options.search_path);
+
+    if !search_path.exists() {
+        return Err(FileManagerError::SearchPathNotFound(
+            options.search_path.clone(),
+        ));
+    }
+
+    let max_results = options
+        .max_results
+        .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS as u32) as usize;
+
+    let matcher = SearchMatcher::compile(options)?;
+    let mut found: usize = 0;
+    let mut streamed: usize = 0;
+    let cutoff = PathCutoff::default();
+    let mut results = FirstResults::new(max_results, &cutoff);
+    let mut batch: Vec<SearchResult> = Vec::with_capacity(SEARCH_RESULT_BATCH_SIZE);
+
+    // Emit initial progress
+    emit_search_event(
+        app,
+        "search-progress",
+        SearchProgress {
+            search_id: search_id.to_string(),
+            scanned: 0,
+            found: 0,
+            current_path: options.search_path.clone(),
+        },
+    );
+
+    // Stream the first `max_results` matches as they are found (batching reduces
+    // IPC overhead). The walk goes on collecting, and the returned list is the
+    // sorted selection, which replaces the streamed preview.
+    let walked = walker::walk_parallel(
+        options,
+        cancelled,
+        Some(&cutoff),
+        |entry| process_search_entry(entry, options, &matcher),
+        |event| match event {
+            WalkEvent::Progress(scanned, path) => {
+                emit_search_event(
+                    app,
+                    "search-progress",
+                    SearchProgress {
+                        search_id: search_id.to_string(),
+                        scanned,
+                        found,
+                        current_path: path.to_string_lossy().to_string(),
+                    },
+                );
+                true
+            }
+            WalkEvent::Match(matched) => {
+                found = found.saturating_add(matched.len());
+                let room = max_results.saturating_sub(streamed + batch.len());
+                batch.extend(matched.iter().take(room).cloned());
+                results.extend(matched);
+
+                if batch.len() >= SEARCH_RESULT_BATCH_SIZE {
+                    streamed += batch.len();
+                    emit_search_event(
+                        app,
+                        "search-batch",
+                        SearchBatch {
+                            search_id: search_id.to_string(),
+                            results: std::mem::take(&mut batch),
+                        },
+                    );
+                }
+
+                true
+            }
+        },
+    );
+
+    let scanned = match walked {
+        Ok(scanned) => scanned,
+        Err(err) => {
+            if matches!(err, FileManagerError::Cancelled) {
+                emit_search_event(
+                    app,
+                    "search-complete",
+                    SearchComplete {
+                        search_id: search_id.to_string(),
+                        found,
+                        cancelled: true,
+                    },
+                );
+            }
+            return Err(err);
+        }
+    };
+
+    if !batch.is_empty() {
+        emit_search_event(
+            app,
+            "search-batch",
+            SearchBatch {
+                search_id: search_id.to_string(),
+                results: batch,
+            },
+        );
+    }
+
+    let results = results.into_sorted();
+
+    // Emit final progress
+    emit_search_event(
+        app,
+        "search-progress",
+        SearchProgress {
+            search_id: search_id.to_string(),
+            scanned,
+            found: results.len(),
+            current_path: String::new(),
+        },
+    );
+
+    emit_search_event(
+        app,
+        "search-complete",
+        SearchComplete {
+            search_id: search_id.to_string(),
+            found: results.len(),
+            cancelled: false,
+        },
+    );
+
+    Ok(results)
+}
+
+/// Synchronous search implementation.
+#[doc(hidden)]
+pub fn search_files_sync(options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    let search_path = Path::new(&options.search_path);
+
+    if !search_path.exists() {
+        return Err(FileManagerError::SearchPathNotFound(
+            options.search_path.clone(),
+        ));
+    }
+
+    let max_results = options
+        .max_results
+        .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS as u32) as usize;
+    let matcher = SearchMatcher::compile(options)?;
+
+    if max_results == 0 {
+        return Ok(Vec::new());
+    }
+
+    let cutoff = PathCutoff::default();
+    let mut results = FirstResults::new(max_results, &cutoff);
+    walker::walk_parallel(
+        options,
+        &AtomicBool::new(false),
+        Some(&cutoff),
+        |entry| process_search_entry(entry, options, &matcher),
+        |event| match event {
+            WalkEvent::Progress(..) => true,
+            WalkEvent::Match(matched) => {
+                results.extend(matched);
+                true
+            }
+        },
+    )?;
+
+    Ok(results.into_sorted())
+}
+
+/// A result ordered by its path.
+struct ByPath(SearchResult);
+
+impl PartialEq for ByPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.path == other.0.path
+    }
+}
+
+impl Eq for ByPath {}
+
+impl PartialOrd for ByPath {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByPath {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.0.path.cmp(&other.0.path)
+    }
+}
+
+/// The first `limit` results by path, from matches found in any order.
+///
+/// Parallel walks find entries in varying order, so the selection can't stop at
+/// the first `limit` matches. Once `limit` results are held, the last of them
+/// becomes the walk's cutoff, and only entries that could still sort earlier
+/// are visited.
+struct FirstResults<'a> {
+    heap: BinaryHeap<ByPath>,
+    limit: usize,
+    cutoff: &'a PathCutoff,
+}
+
+impl<'a> FirstResults<'a> {
+    fn new(limit: usize, cutoff: &'a PathCutoff) -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            limit,
+            cutoff,
+        }
+    }
+
+    fn extend(&mut self, results: Vec<SearchResult>) {
+        for result in results {
+            let full = self.heap.len() >= self.limit;
+            if full
+                && self
+                    .heap
+                    .peek()
+                    .is_none_or(|last| result.path >= last.0.path)
+            {
+                continue;
+            }
+            self.heap.push(ByPath(result));
+            if self.heap.len() > self.limit {
+                self.heap.pop();
+            }
+            if self.heap.len() == self.limit {
+                if let Some(last) = self.heap.peek() {
+                    self.cutoff.set(&last.0.path);
+                }
+            }
+        }
+    }
+
+    fn into_sorted(self) -> Vec<SearchResult> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|result| result.0)
+            .collect()
+    }
+}
+
+/// Filters and query expression of a search, compiled once and shared by walker threads.
+struct SearchMatcher {
+    filter: Option<EntryFilter>,
+    expression: Option<CompiledQuery>,
+}
+
+impl SearchMatcher {
+    fn compile(options: &SearchOptions) -> Result<Self> {
+        Ok(Self {
+            filter: options
+                .filters
+                .as_ref()
+                .map(EntryFilter::compile)
+                .transpose()?,
+            expression: options
+                .expression
+                .as_ref()
+                .map(|expr| CompiledQuery::compile(expr, options))
+                .transpose()?,
+        })
+    }
+}
+
+/// Processes a single entry for search matching.
+///
+/// Plain content searches also look inside ZIP/TAR archives, adding a result
+/// per matching inner file.
+fn process_search_entry(
+    entry: &ignore::DirEntry,
+    options: &SearchOptions,
+    matcher: &SearchMatcher,
+) -> Option<Vec<SearchResult>> {
+    let mut results: Vec<SearchResult> = match_search_entry(entry, options, matcher)
+        .into_iter()
+        .collect();
+
+    if options.search_content && matcher.expression.is_none() {
+        let kind = entry
+            .file_name()
+            .to_str()
+            .and_then(ArchiveKind::from_name)
+            .filter(|_| entry.file_type().is_some_and(|t| t.is_file()));
+        if let Some(kind) = kind {
+            if matcher.filter.as_ref().is_none_or(|f| f.matches(entry)) {
+                results.extend(search_archive(entry, kind, options));
+            }
+        }
+    }
+
+    (!results.is_empty()).then_some(results)
+}
+
+/// Whether `name` passes the search's extension filter.
+fn extension_allowed(name: &str, is_dir: bool, options: &SearchOptions) -> bool {
+    let Some(ref extensions) = options.file_extensions else {
+        return true;
+    };
+    match Path::new(name).extension().and_then(|e| e.to_str()) {
+        Some(ext) => extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)),
+        None => is_dir,
+    }
+}
+
+/// Matches a single entry against the query and filters.
+fn match_search_entry(
+    entry: &ignore::DirEntry,
+    options: &SearchOptions,
+    matcher: &SearchMatcher,
+) -> Option<SearchResult> {
+    let path = entry.path();
+    let name = path.file_name()?.to_str()?.to_string();
+    let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+
+    // Filter by extension if specified
+    if !extension_allowed(&name, is_dir, options) {
+        return None;
+    }
+
+    // Filter by metadata predicates
+    if matcher.filter.as_ref().is_some_and(|f| !f.matches(entry)) {
+        return None;
+    }
+
+    // A parsed query replaces the plain `query` string
+    if let Some(ref expression) = matcher.expression {
+        let matches = expression.matches(entry, &name)?;
+        return Some(SearchResult {
+            path: path.to_string_lossy().to_string(),
+            name,
+            is_dir,
+            matches,
+        });
+    }
+
+    // Check filename match
+    let name_matches = text_contains(&name, &options.query, options.case_sensitive);
+
+    // Search content if enabled
+    let content_matches = if options.search_content {
+        search_entry_content(entry, &options.query, options.case_sensitive)
+    } else {
+        Vec::new()
+    };
+
+    if name_matches || !content_matches.is_empty() {
+        Some(SearchResult {
+            path: path.to_string_lossy().to_string(),
+            name,
+            is_dir,
+            matches: content_matches,
+        })
+    } else {
+        None
+    }
+}
+
+/// Substring test honoring the search's case sensitivity.
+fn text_contains(haystack: &str, needle: &str, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        haystack.contains(needle)
+    } else {
+        haystack.to_lowercase().contains(&needle.to_lowercase())
+    }
+}
+
+/// Searches the content of a regular file.
+///
+/// Office documents and PDFs up to `MAX_OFFICE_FILE_SIZE` are searched by their
+/// extracted text; other files up to `MAX_SEARCH_FILE_SIZE` line by line.
+fn search_entry_content(
+    entry: &ignore::DirEntry,
+    query: &str,
+    case_sensitive: bool,
+) -> Vec<ContentMatch> {
+    if !entry.file_type().is_some_and(|t| t.is_file()) {
+        return Vec::new();
+    }
+
+    let document = entry.file_name().to_str().and_then(DocumentKind::from_name);
+    let limit = if document.is_some() {
+        MAX_OFFICE_FILE_SIZE
+    } else {
+        MAX_SEARCH_FILE_SIZE
+    };
+    if entry.metadata().map_or(true, |meta| meta.len() > limit) {
+        return Vec::new();
+    }
+
+    match document {
+        Some(kind) => fs::read(entry.path())
+            .map(|data| search_document_content(kind, &data, query, case_sensitive))
+            .unwrap_or_default(),
+        None => match File::open(entry.path()) {
+            Ok(file) => search_file_content(BufReader::new(file), query, case_sensitive),
+            Err(_) => Vec::new(),
+        },
+    }
+}
+
+/// Searches the files inside an archive no larger than `MAX_ARCHIVE_SEARCH_SIZE`.
+///
+/// Hits are reported as `archive.zip!/inner/path.txt`.
+fn search_archive(
+    entry: &ignore::DirEntry,
+    kind: ArchiveKind,
+    options: &SearchOptions,
+) -> Vec<SearchResult> {
+    if entry
+        .metadata()
+        .map_or(true, |meta| meta.len() > MAX_ARCHIVE_SEARCH_SIZE)
+    {
+        return Vec::new();
+    }
+
+    let archive_path = entry.path().to_string_lossy();
+    let max_results = options
+        .max_results
+        .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS as u32) as usize;
+    let mut results = Vec::new();
+
+    // A damaged archive still yields the hits found before the damage.
+    let _ = archive::for_each_file(entry.path(), kind, |inner, data| {
+        let name = inner.rsplit('/').next().unwrap_or(inner);
+        if !extension_allowed(name, false, options) {
+            return true;
+        }
+
+        let name_matches = text_contains(name, &options.query, options.case_sensitive);
+        let matches = match DocumentKind::from_name(name) {
+            Some(kind) => {
+                search_document_content(kind, &data, &options.query, options.case_sensitive)
+            }
+            None => search_file_content(data.as_slice(), &options.query, options.case_sensitive),
+        };
+
+        if name_matches || !matches.is_empty() {
+            results.push(SearchResult {
+                path: format!("{archive_path}{ARCHIVE_PATH_SEPARATOR}{inner}"),
+                name: name.to_string(),
+                is_dir: false,
+                matches,
+            });
+        }
+        results.len() < max_results
+    });
+
+    results
+}
+
+/// Searches the extracted text of a document; unreadable documents have no matches.
+fn search_document_content(
+    kind: DocumentKind,
+    data: &[u8],
+    query: &str,
+    case_sensitive: bool,
+) -> Vec<ContentMatch> {
+    let Ok(units) = documents::extract(kind, data) else {
+        return Vec::new();
+    };
+    let query_lower = query.to_lowercase();
+    let mut matches = Vec::new();
+    let mut line_number = 0;
+
+    for unit in units {
+        for line in unit.text.lines() {
+            line_number += 1;
+            if let Some(mut found) =
+                match_line(line, line_number, query, &query_lower, case_sensitive)
+            {
+                found.location = Some(unit.location.clone());
+                matches.push(found);
+
+                if matches.len() >= MAX_CONTENT_MATCHES_PER_FILE {
+                    return matches;
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+/// Searches file content for matches.
+fn search_file_content(
+    reader: impl BufRead,
+    query: &str,
+    case_sensitive: bool,
+) -> Vec<ContentMatch> {
+    let mut matches = Vec::new();
+    let query_lower = query.to_lowercase();
+
+    for (line_num, line_result) in reader.lines().enumerate() {
+        let Ok(line) = line_result else {
+            continue;
+        };
+
+        if let Some(found) = match_line(&line, line_num + 1, query, &query_lower, case_sensitive) {
+            matches.push(found);
+
+            if matches.len() >= MAX_CONTENT_MATCHES_PER_FILE {
+                break;
+            }
+        }
+    }
+
+    matches
+}
+
+/// Finds the first occurrence of the query in a line.
+fn match_line(
+    line: &str,
+    line_number: usize,
+    query: &str,
+    query_lower: &str,
+    case_sensitive: bool,
+) -> Option<ContentMatch> {
+    let (start, len) = if case_sensitive {
+        (line.find(query)?, query.len())
+    } else {
+        // Allocate lowercase version for case-insensitive search
+        (line.to_lowercase().find(query_lower)?, query_lower.len())
+    };
+
+    Some(ContentMatch {
+        line_number: line_number as u64,
+        line_content: line.chars().take(200).collect(),
+        match_start: start as u64,
+        match_end: (start + len) as u64,
+        location: None,
+    })
+}
+
+/// Parses a query-language string into search options based on `base`.
+///
+/// The returned options keep the raw text in `query` and carry the parsed
+/// `expression`, which takes precedence when searching.
+#[tauri::command]
+#[specta::specta]
+pub async fn parse_search_query(
+    input: String,
+    base: SearchOptions,
+) -> std::result::Result<SearchOptions, QueryError> {
+    parse_search_query_sync(&input, base)
+}
+
+#[doc(hidden)]
+pub fn parse_search_query_sync(
+    input: &str,
+    base: SearchOptions,
+) -> std::result::Result<SearchOptions, QueryError> {
+    let expression = query::parse_query(input)?;
+
+    Ok(SearchOptions {
+        query: input.to_string(),
+        expression,
+        ..base
+    })
+}
+
+/// Fuzzy filename search ranked by score, with highlight indices.
+#[tauri::command]
+#[specta::specta]
+pub async fn fuzzy_search(
+    options: FuzzySearchOptions,
+    app: AppHandle,
+) -> std::result::Result<Vec<FuzzyMatch>, String> {
+    spawn_blocking(move || fuzzy_search_sync(&options, &app.state::<SearchState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn fuzzy_search_sync(
+    options: &FuzzySearchOptions,
+    state: &SearchState,
+) -> Result<Vec<FuzzyMatch>> {
+    if !Path::new(&options.search_path).exists() {
+        return Err(FileManagerError::SearchPathNotFound(
+            options.search_path.clone(),
+        ));
+    }
+
+    let query = options.query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let max_results = options
+        .max_results
+        .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS as u32) as usize;
+    let candidates = state.fuzzy.get_or_walk(options)?;
+
+    Ok(fuzzy::rank(
+        &candidates,
+        query,
+        options.recent_paths.as_deref().unwrap_or_default(),
+        max_results,
+    ))
+}
+
+/// Replaces matches of a search across files.
+///
+/// In dry-run mode nothing is written and each file reports a diff; otherwise
+/// files are rewritten atomically and the result carries an `undo_id`.
+#[tauri::command]
+#[specta::specta]
+pub async fn replace_in_files(
+    options: ReplaceOptions,
+    app: AppHandle,
+) -> std::result::Result<ReplaceReport, String> {
+    spawn_blocking(move || replace_in_files_sync(&options, &app.state::<SearchState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn replace_in_files_sync(
+    options: &ReplaceOptions,
+    state: &SearchState,
+) -> Result<ReplaceReport> {
+    replace::replace(options, &state.replacements)
+}
+
+/// Restores the files changed by `replace_in_files`, unless they were edited since.
+#[tauri::command]
+#[specta::specta]
+pub async fn undo_replace(
+    undo_id: String,
+    app: AppHandle,
+) -> std::result::Result<ReplaceUndoReport, String> {
+    spawn_blocking(move || undo_replace_sync(&undo_id, &app.state::<SearchState>()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn undo_replace_sync(undo_id: &str, state: &SearchState) -> Result<ReplaceUndoReport> {
+    replace::undo(undo_id, &state.replacements)
+}
+
+/// Searches for files by name only.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_by_name(
+    search_path: String,
+    query: String,
+    max_results: Option<u32>,
+) -> std::result::Result<Vec<SearchResult>, String> {
+    let search_path_clone = search_path.clone();
+    let query_clone = query.clone();
+    spawn_blocking(move || search_by_name_sync(&search_path_clone, &query_clone, max_results))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
+}
+
+/// Searches file contents.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_content(
+    search_path: String,
+    query: String,
+    extensions: Option<Vec<String>>,
+    max_results: Option<u32>,
+) -> std::result::Result<Vec<SearchResult>, String> {
+    let search_path_clone = search_path.clone();
+    let query_clone = query.clone();
+    let extensions_clone = extensions.clone();
+    spawn_blocking(move || {
+        search_content_sync(
+            &search_path_clone,
+            &query_clone,
+            extensions_clone,
+            max_results,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(Into::into)
+}
+
+#[doc(hidden)]
+pub fn search_by_name_sync(
+    search_path: &str,
+    query: &str,
+    max_results: Option<u32>,
+) -> Result<Vec<SearchResult>> {
+    search_files_sync(&SearchOptions {
+        query: query.to_string(),
+        search_path: search_path.to_string(),
+        search_content: false,
+        case_sensitive: false,
+        max_results,
+        file_extensions: None,
+        ..Default::default()
+    })
+}
+
+#[doc(hidden)]
+pub fn search_content_sync(
+    search_path: &str,
+    query: &str,
+    extensions: Option<Vec<String>>,
+    max_results: Option<u32>,
+) -> Result<Vec<SearchResult>> {
+    search_files_sync(&SearchOptions {
+        query: query.to_string(),
+        search_path: search_path.to_string(),
+        search_content: true,
+        case_sensitive: false,
+        max_results,
+        file_extensions: extensions,
+        ..Default::default()
+    })
+}
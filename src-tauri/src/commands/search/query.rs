@@ -0,0 +1,966 @@
+//! Parser for the search query language.
+//!
+//! ```text
+//! ext:rs size:>1M modified:<2w "todo" -path:target
+//! (name:*.md OR name:*.txt) NOT is:empty
+//! ```
+//!
+//! Terms separated by whitespace (or `AND`) must all match; `OR` binds looser
+//! than `AND`; `-term` and `NOT term` negate; parentheses group. Bare words
+//! match names (and content when content search is on), bare words with glob
+//! characters match names as globs, and quoted phrases are always literal text.
+
+use chrono::{Days, Local, NaiveDate, TimeZone};
+use globset::Glob;
+
+use crate::models::{EntryKind, QueryError, QueryExpr, SearchFilters, TimeBound, TimeRange};
+
+/// Field names recognised before a `:`. Anything else is treated as plain text,
+/// so inputs like `C:\Users` still work as text terms.
+const FIELDS: &[&str] = &[
+    "ext",
+    "extension",
+    "name",
+    "path",
+    "content",
+    "size",
+    "modified",
+    "mtime",
+    "created",
+    "ctime",
+    "type",
+    "is",
+    "owner",
+];
+
+const GLOB_CHARS: &[char] = &['*', '?', '[', '{'];
+
+/// Deepest nesting of parentheses and negations accepted; the parser and the
+/// matcher recurse once per level.
+const MAX_NESTING: usize = 32;
+
+/// Error with byte offsets into the input; converted to [`QueryError`] at the boundary.
+#[derive(Debug)]
+struct ParseError {
+    message: String,
+    start: usize,
+    end: usize,
+}
+
+impl ParseError {
+    fn new(message: impl Into<String>, start: usize, end: usize) -> Self {
+        Self {
+            message: message.into(),
+            start,
+            end,
+        }
+    }
+
+    fn into_query_error(self, input: &str) -> QueryError {
+        QueryError {
+            message: self.message,
+            start: utf16_offset(input, self.start),
+            end: utf16_offset(input, self.end),
+        }
+    }
+}
+
+fn utf16_offset(input: &str, byte: usize) -> u32 {
+    let byte = byte.min(input.len());
+    u32::try_from(input[..byte].encode_utf16().count()).unwrap_or(u32::MAX)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Term {
+        field: Option<String>,
+        value: String,
+        quoted: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    /// Start of the value part of a `field:value` term.
+    value_start: usize,
+}
+
+/// Parses a query string. Returns `Ok(None)` for blank input.
+pub fn parse_query(input: &str) -> std::result::Result<Option<QueryExpr>, QueryError> {
+    parse(input).map_err(|e| e.into_query_error(input))
+}
+
+fn parse(input: &str) -> Result<Option<QueryExpr>, ParseError> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+        input_len: input.len(),
+    };
+    let expr = parser.parse_or()?;
+
+    if let Some(token) = parser.peek() {
+        return Err(ParseError::new(
+            "Unmatched closing parenthesis",
+            token.start,
+            token.end,
+        ));
+    }
+
+    Ok(Some(expr))
+}
+
+// ---------------------------------------------------------------------------
+// Tokenizer
+// ---------------------------------------------------------------------------
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while let Some(ch) = input[pos..].chars().next() {
+        let start = pos;
+
+        if ch.is_whitespace() {
+            pos += ch.len_utf8();
+            continue;
+        }
+
+        match ch {
+            '(' | ')' => {
+                pos += 1;
+                tokens.push(Token {
+                    kind: if ch == '(' {
+                        TokenKind::LParen
+                    } else {
+                        TokenKind::RParen
+                    },
+                    start,
+                    end: pos,
+                    value_start: pos,
+                });
+            }
+            '-' if input[pos + 1..]
+                .chars()
+                .next()
+                .is_some_and(|next| !next.is_whitespace() && next != ')') =>
+            {
+                pos += 1;
+                tokens.push(Token {
+                    kind: TokenKind::Not,
+                    start,
+                    end: pos,
+                    value_start: pos,
+                });
+            }
+            '"' => {
+                let (value, end) = read_quoted(input, start)?;
+                pos = end;
+                tokens.push(Token {
+                    kind: TokenKind::Term {
+                        field: None,
+                        value,
+                        quoted: true,
+                    },
+                    start,
+                    end,
+                    value_start: start,
+                });
+            }
+            _ => {
+                let token = read_word(input, start)?;
+                pos = token.end;
+                tokens.push(token);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Reads a `"quoted phrase"` starting at `start`. `\"` and `\\` are escapes.
+fn read_quoted(input: &str, start: usize) -> Result<(String, usize), ParseError> {
+    let mut value = String::new();
+    let mut chars = input[start + 1..].char_indices();
+
+    while let Some((offset, ch)) = chars.next() {
+        match ch {
+            '"' => return Ok((value, start + 1 + offset + 1)),
+            '\\' => match chars.next() {
+                Some((_, escaped)) => value.push(escaped),
+                None => break,
+            },
+            _ => value.push(ch),
+        }
+    }
+
+    Err(ParseError::new(
+        "Unterminated quoted phrase",
+        start,
+        input.len(),
+    ))
+}
+
+/// Reads a bare word, a keyword, or a `field:value` term starting at `start`.
+fn read_word(input: &str, start: usize) -> Result<Token, ParseError> {
+    let rest = &input[start..];
+    let word_len = rest
+        .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .unwrap_or(rest.len());
+    let word = &rest[..word_len];
+    let end = start + word_len;
+
+    let keyword = match word {
+        "OR" | "|" | "||" => Some(TokenKind::Or),
+        "AND" | "&&" => Some(TokenKind::And),
+        "NOT" => Some(TokenKind::Not),
+        _ => None,
+    };
+    if let Some(kind) = keyword {
+        return Ok(Token {
+            kind,
+            start,
+            end,
+            value_start: end,
+        });
+    }
+
+    if let Some((field, _)) = word.split_once(':') {
+        let field = field.to_ascii_lowercase();
+        if FIELDS.contains(&field.as_str()) {
+            let value_start = start + field.len() + 1;
+
+            // field:"quoted value" may contain whitespace and parentheses.
+            if input[value_start..].starts_with('"') {
+                let (value, end) = read_quoted(input, value_start)?;
+                return Ok(Token {
+                    kind: TokenKind::Term {
+                        field: Some(field),
+                        value,
+                        quoted: true,
+                    },
+                    start,
+                    end,
+                    value_start,
+                });
+            }
+
+            return Ok(Token {
+                kind: TokenKind::Term {
+                    field: Some(field),
+                    value: input[value_start..end].to_string(),
+                    quoted: false,
+                },
+                start,
+                end,
+                value_start,
+            });
+        }
+    }
+
+    Ok(Token {
+        kind: TokenKind::Term {
+            field: None,
+            value: word.to_string(),
+            quoted: false,
+        },
+        start,
+        end,
+        value_start: start,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Groups and negations currently open.
+    depth: usize,
+    input_len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.peek().map(|t| &t.kind)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Enters a group or negation opened by `token`.
+    fn descend(&mut self, token: &Token) -> Result<(), ParseError> {
+        if self.depth >= MAX_NESTING {
+            return Err(ParseError::new(
+                format!("Query is nested too deeply (at most {MAX_NESTING} levels)"),
+                token.start,
+                token.end,
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Error for a missing term at the current position.
+    fn expected_term(&self, message: &str) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError::new(message, token.start, token.end),
+            None => ParseError::new(message, self.input_len, self.input_len),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr, ParseError> {
+        let mut items = vec![self.parse_and()?];
+
+        while self.peek_kind() == Some(&TokenKind::Or) {
+            self.next();
+            items.push(self.parse_and()?);
+        }
+
+        Ok(collapse(items, |items| QueryExpr::Or { items }))
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr, ParseError> {
+        let mut items = Vec::new();
+
+        loop {
+            match self.peek_kind() {
+                None | Some(TokenKind::RParen) | Some(TokenKind::Or) => break,
+                Some(TokenKind::And) => {
+                    if items.is_empty() {
+                        return Err(self.expected_term("Expected a term before AND"));
+                    }
+                    self.next();
+                    if matches!(
+                        self.peek_kind(),
+                        None | Some(TokenKind::RParen) | Some(TokenKind::Or) | Some(TokenKind::And)
+                    ) {
+                        return Err(self.expected_term("Expected a term after AND"));
+                    }
+                }
+                Some(_) => items.push(self.parse_unary()?),
+            }
+        }
+
+        if items.is_empty() {
+            return Err(self.expected_term("Expected a search term"));
+        }
+
+        Ok(collapse(items, |items| QueryExpr::And { items }))
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr, ParseError> {
+        if self.peek_kind() == Some(&TokenKind::Not) {
+            let not = self.next().expect("peeked token");
+            if matches!(
+                self.peek_kind(),
+                None | Some(TokenKind::RParen) | Some(TokenKind::Or) | Some(TokenKind::And)
+            ) {
+                return Err(ParseError::new(
+                    "Expected a term to negate",
+                    not.start,
+                    not.end,
+                ));
+            }
+            self.descend(&not)?;
+            let item = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(QueryExpr::Not {
+                item: Box::new(item),
+            });
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<QueryExpr, ParseError> {
+        let token = self
+            .next()
+            .ok_or_else(|| self.expected_term("Expected a search term"))?;
+
+        match token.kind {
+            TokenKind::LParen => {
+                if self.peek_kind() == Some(&TokenKind::RParen) {
+                    let close = self.next().expect("peeked token");
+                    return Err(ParseError::new("Empty group", token.start, close.end));
+                }
+                self.descend(&token)?;
+                let expr = self.parse_or()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::RParen,
+                        ..
+                    }) => Ok(expr),
+                    _ => Err(ParseError::new(
+                        "Unclosed parenthesis",
+                        token.start,
+                        token.end,
+                    )),
+                }
+            }
+            TokenKind::Term {
+                ref field,
+                ref value,
+                quoted,
+            } => term_to_expr(field.as_deref(), value, quoted, &token),
+            _ => Err(ParseError::new(
+                "Expected a search term",
+                token.start,
+                token.end,
+            )),
+        }
+    }
+}
+
+fn collapse(
+    mut items: Vec<QueryExpr>,
+    wrap: impl FnOnce(Vec<QueryExpr>) -> QueryExpr,
+) -> QueryExpr {
+    if items.len() == 1 {
+        items.remove(0)
+    } else {
+        wrap(items)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Terms
+// ---------------------------------------------------------------------------
+
+fn term_to_expr(
+    field: Option<&str>,
+    value: &str,
+    quoted: bool,
+    token: &Token,
+) -> Result<QueryExpr, ParseError> {
+    let value_error = |message: String| ParseError::new(message, token.value_start, token.end);
+
+    let Some(field) = field else {
+        if value.is_empty() {
+            return Err(ParseError::new("Empty phrase", token.start, token.end));
+        }
+        if !quoted && value.contains(GLOB_CHARS) {
+            validate_glob(value).map_err(value_error)?;
+            return Ok(QueryExpr::Name {
+                pattern: value.to_string(),
+            });
+        }
+        return Ok(QueryExpr::Text {
+            value: value.to_string(),
+        });
+    };
+
+    if value.is_empty() {
+        return Err(ParseError::new(
+            format!("Missing value for `{field}:`"),
+            token.start,
+            token.end,
+        ));
+    }
+
+    let filter = |filters: SearchFilters| QueryExpr::Filter { filters };
+
+    match field {
+        "ext" | "extension" => {
+            let mut items = Vec::new();
+            for ext in value.split(',') {
+                let ext = ext.trim().trim_start_matches('.');
+                if ext.is_empty() {
+                    return Err(value_error("Empty extension".to_string()));
+                }
+                items.push(QueryExpr::Extension {
+                    value: ext.to_string(),
+                });
+            }
+            Ok(collapse(items, |items| QueryExpr::Or { items }))
+        }
+        "name" => {
+            validate_glob(value).map_err(value_error)?;
+            Ok(QueryExpr::Name {
+                pattern: value.to_string(),
+            })
+        }
+        "path" => {
+            if value.contains(GLOB_CHARS) {
+                validate_glob(value).map_err(value_error)?;
+            }
+            Ok(QueryExpr::Path {
+                pattern: value.to_string(),
+            })
+        }
+        "content" => Ok(QueryExpr::Content {
+            value: value.to_string(),
+        }),
+        "size" => {
+            let (min_size, max_size) = parse_size_filter(value).map_err(value_error)?;
+            Ok(filter(SearchFilters {
+                min_size,
+                max_size,
+                ..Default::default()
+            }))
+        }
+        "modified" | "mtime" => {
+            let range = parse_time_filter(value).map_err(value_error)?;
+            Ok(filter(SearchFilters {
+                modified: Some(range),
+                ..Default::default()
+            }))
+        }
+        "created" | "ctime" => {
+            let range = parse_time_filter(value).map_err(value_error)?;
+            Ok(filter(SearchFilters {
+                created: Some(range),
+                ..Default::default()
+            }))
+        }
+        "type" => {
+            let kind = parse_entry_kind(value).ok_or_else(|| {
+                value_error(format!("Unknown type `{value}` (use file, dir or symlink)"))
+            })?;
+            Ok(filter(SearchFilters {
+                kind: Some(kind),
+                ..Default::default()
+            }))
+        }
+        "is" => {
+            let filters = match value.to_ascii_lowercase().as_str() {
+                "empty" => SearchFilters {
+                    empty: Some(true),
+                    ..Default::default()
+                },
+                "hidden" => SearchFilters {
+                    hidden: Some(true),
+                    ..Default::default()
+                },
+                "executable" | "exec" => SearchFilters {
+                    executable: Some(true),
+                    ..Default::default()
+                },
+                other => match parse_entry_kind(other) {
+                    Some(kind) => SearchFilters {
+                        kind: Some(kind),
+                        ..Default::default()
+                    },
+                    None => {
+                        return Err(value_error(format!(
+                            "Unknown property `{value}` (use empty, hidden, executable, file, dir or symlink)"
+                        )))
+                    }
+                },
+            };
+            Ok(filter(filters))
+        }
+        "owner" => Ok(filter(SearchFilters {
+            owner: Some(value.to_string()),
+            ..Default::default()
+        })),
+        _ => Err(ParseError::new(
+            format!("Unknown field `{field}`"),
+            token.start,
+            token.value_start,
+        )),
+    }
+}
+
+fn validate_glob(pattern: &str) -> Result<(), String> {
+    Glob::new(pattern)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid glob: {}", e.kind()))
+}
+
+fn parse_entry_kind(value: &str) -> Option<EntryKind> {
+    match value.to_ascii_lowercase().as_str() {
+        "file" | "f" => Some(EntryKind::File),
+        "dir" | "d" | "directory" | "folder" => Some(EntryKind::Directory),
+        "symlink" | "link" | "l" => Some(EntryKind::Symlink),
+        _ => None,
+    }
+}
+
+/// Splits a leading comparison operator off a field value.
+fn split_operator(value: &str) -> (&str, &str) {
+    for op in [">=", "<=", ">", "<", "="] {
+        if let Some(rest) = value.strip_prefix(op) {
+            return (op, rest);
+        }
+    }
+    ("", value)
+}
+
+/// Parses `>1M`, `<=10k`, `1M..5M` or `512` into inclusive byte bounds.
+fn parse_size_filter(value: &str) -> Result<(Option<u64>, Option<u64>), String> {
+    if let Some((low, high)) = value.split_once("..") {
+        let min = (!low.is_empty()).then(|| parse_size(low)).transpose()?;
+        let max = (!high.is_empty()).then(|| parse_size(high)).transpose()?;
+        return Ok((min, max));
+    }
+
+    let (op, amount) = split_operator(value);
+    let size = parse_size(amount)?;
+
+    Ok(match op {
+        ">" => (Some(size.saturating_add(1)), None),
+        ">=" => (Some(size), None),
+        "<" => (None, Some(size.saturating_sub(1))),
+        "<=" => (None, Some(size)),
+        _ => (Some(size), Some(size)),
+    })
+}
+
+/// Parses a size such as `1.5M`, `10kb` or `2GiB` (binary units) into bytes.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size `{value}`"))?;
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return Err(format!("Unknown size unit `{unit}`")),
+    };
+
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+/// Parses `<2w`, `>30d`, `>=2024-01-01`, `today` or `2024-01-01..2024-02-01`.
+///
+/// Durations compare the entry's age: `<2w` means "within the last two weeks".
+/// They stay relative so saved queries keep meaning the same thing.
+fn parse_time_filter(value: &str) -> Result<TimeRange, String> {
+    if let Some((low, high)) = value.split_once("..") {
+        let after = if low.is_empty() {
+            None
+        } else {
+            Some(parse_day(low).ok_or_else(|| invalid_date(low))?.0)
+        };
+        let before = if high.is_empty() {
+            None
+        } else {
+            Some(parse_day(high).ok_or_else(|| invalid_date(high))?.1)
+        };
+        return Ok(TimeRange {
+            after: after.map(|timestamp| TimeBound::Absolute { timestamp }),
+            before: before.map(|timestamp| TimeBound::Absolute { timestamp }),
+        });
+    }
+
+    let (op, operand) = split_operator(value);
+
+    if let Some(seconds) = parse_duration(operand) {
+        let bound = Some(TimeBound::Relative { seconds });
+        return Ok(match op {
+            ">" | ">=" => TimeRange {
+                after: None,
+                before: bound,
+            },
+            _ => TimeRange {
+                after: bound,
+                before: None,
+            },
+        });
+    }
+
+    let (day_start, day_end) = parse_day(operand).ok_or_else(|| invalid_date(operand))?;
+    let absolute = |timestamp: i64| Some(TimeBound::Absolute { timestamp });
+
+    Ok(match op {
+        "<" => TimeRange {
+            after: None,
+            before: absolute(day_start - 1),
+        },
+        "<=" => TimeRange {
+            after: None,
+            before: absolute(day_end),
+        },
+        ">" => TimeRange {
+            after: absolute(day_end + 1),
+            before: None,
+        },
+        ">=" => TimeRange {
+            after: absolute(day_start),
+            before: None,
+        },
+        _ => TimeRange {
+            after: absolute(day_start),
+            before: absolute(day_end),
+        },
+    })
+}
+
+fn invalid_date(value: &str) -> String {
+    format!("Invalid date or duration `{value}` (use e.g. 3d, 2w, 2024-01-31)")
+}
+
+/// Parses durations like `90s`, `15min`, `12h`, `3d`, `2w`, `6mo`, `1y` into seconds.
+fn parse_duration(value: &str) -> Option<u64> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().ok()?;
+
+    let unit_seconds: u64 = match unit.to_ascii_lowercase().as_str() {
+        "s" | "sec" => 1,
+        "m" | "min" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        "mo" => 30 * 86_400,
+        "y" => 365 * 86_400,
+        _ => return None,
+    };
+
+    number.checked_mul(unit_seconds)
+}
+
+/// Parses a calendar day into the local timestamps of its first and last second.
+fn parse_day(value: &str) -> Option<(i64, i64)> {
+    let today = Local::now().date_naive();
+    let date = match value.to_ascii_lowercase().as_str() {
+        "today" => today,
+        "yesterday" => today.checked_sub_days(Days::new(1))?,
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?,
+    };
+
+    let start = local_midnight(date)?;
+    let end = local_midnight(date.checked_add_days(Days::new(1))?)? - 1;
+    Some((start, end))
+}
+
+fn local_midnight(date: NaiveDate) -> Option<i64> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|dt| dt.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> QueryExpr {
+        QueryExpr::Text {
+            value: value.to_string(),
+        }
+    }
+
+    fn parse_ok(input: &str) -> QueryExpr {
+        parse_query(input).expect("parse").expect("expression")
+    }
+
+    #[test]
+    fn parses_example_query() {
+        let expr = parse_ok(r#"ext:rs size:>1M modified:<2w "todo" -path:target"#);
+        let QueryExpr::And { items } = expr else {
+            panic!("expected AND, got {expr:?}");
+        };
+        assert_eq!(items.len(), 5);
+        assert_eq!(
+            items[0],
+            QueryExpr::Extension {
+                value: "rs".to_string()
+            }
+        );
+        assert_eq!(
+            items[1],
+            QueryExpr::Filter {
+                filters: SearchFilters {
+                    min_size: Some((1 << 20) + 1),
+                    ..Default::default()
+                }
+            }
+        );
+        assert_eq!(
+            items[2],
+            QueryExpr::Filter {
+                filters: SearchFilters {
+                    modified: Some(TimeRange {
+                        after: Some(TimeBound::Relative {
+                            seconds: 14 * 86_400
+                        }),
+                        before: None,
+                    }),
+                    ..Default::default()
+                }
+            }
+        );
+        assert_eq!(items[3], text("todo"));
+        assert_eq!(
+            items[4],
+            QueryExpr::Not {
+                item: Box::new(QueryExpr::Path {
+                    pattern: "target".to_string()
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        let expr = parse_ok("a b OR c");
+        assert_eq!(
+            expr,
+            QueryExpr::Or {
+                items: vec![
+                    QueryExpr::And {
+                        items: vec![text("a"), text("b")]
+                    },
+                    text("c"),
+                ]
+            }
+        );
+
+        let grouped = parse_ok("a (b OR c) AND NOT d");
+        let QueryExpr::And { items } = grouped else {
+            panic!("expected AND");
+        };
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[1], QueryExpr::Or { .. }));
+        assert!(matches!(items[2], QueryExpr::Not { .. }));
+    }
+
+    #[test]
+    fn bare_globs_become_name_patterns_but_quoted_text_stays_literal() {
+        assert_eq!(
+            parse_ok("*.md"),
+            QueryExpr::Name {
+                pattern: "*.md".to_string()
+            }
+        );
+        assert_eq!(parse_ok(r#""*.md""#), text("*.md"));
+        assert_eq!(parse_ok(r#""say \"hi\"""#), text(r#"say "hi""#));
+        assert_eq!(parse_ok(r"C:\Users"), text(r"C:\Users"));
+    }
+
+    #[test]
+    fn parses_extension_lists_and_quoted_field_values() {
+        assert_eq!(
+            parse_ok("ext:.rs,ts"),
+            QueryExpr::Or {
+                items: vec![
+                    QueryExpr::Extension {
+                        value: "rs".to_string()
+                    },
+                    QueryExpr::Extension {
+                        value: "ts".to_string()
+                    },
+                ]
+            }
+        );
+        assert_eq!(
+            parse_ok(r#"content:"fn main()""#),
+            QueryExpr::Content {
+                value: "fn main()".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_size_and_time_operators() {
+        assert_eq!(parse_size_filter("<=10k"), Ok((None, Some(10 * 1024))));
+        assert_eq!(
+            parse_size_filter("1M..2M"),
+            Ok((Some(1 << 20), Some(2 << 20)))
+        );
+        assert_eq!(parse_size_filter("1.5k"), Ok((Some(1536), Some(1536))));
+        assert!(parse_size_filter(">1X").is_err());
+
+        let older = parse_time_filter(">30d").expect("duration");
+        assert_eq!(
+            older.before,
+            Some(TimeBound::Relative {
+                seconds: 30 * 86_400
+            })
+        );
+        assert_eq!(older.after, None);
+
+        let day = parse_time_filter("2024-01-31").expect("date");
+        let (
+            Some(TimeBound::Absolute { timestamp: start }),
+            Some(TimeBound::Absolute { timestamp: end }),
+        ) = (day.after, day.before)
+        else {
+            panic!("expected absolute bounds");
+        };
+        assert_eq!(end - start, 86_399);
+    }
+
+    #[test]
+    fn reports_error_spans() {
+        let err = parse_query("ext:rs size:>1X").unwrap_err();
+        assert_eq!((err.start, err.end), (12, 15));
+        assert!(err.message.contains("unit"));
+
+        let err = parse_query("(a OR b").unwrap_err();
+        assert_eq!((err.start, err.end), (0, 1));
+
+        let err = parse_query("a )").unwrap_err();
+        assert_eq!((err.start, err.end), (2, 3));
+
+        let err = parse_query(r#"name:"open"#).unwrap_err();
+        assert_eq!((err.start, err.end), (5, 10));
+
+        let err = parse_query("a OR").unwrap_err();
+        assert_eq!((err.start, err.end), (4, 4));
+
+        let err = parse_query("type:blob").unwrap_err();
+        assert_eq!((err.start, err.end), (5, 9));
+    }
+
+    #[test]
+    fn rejects_deep_nesting_and_empty_phrases() {
+        let nested = format!("{}a{}", "(".repeat(32), ")".repeat(32));
+        assert!(parse_query(&nested).is_ok());
+
+        let err = parse_query(&"(".repeat(10_000)).unwrap_err();
+        assert_eq!((err.start, err.end), (32, 33));
+        assert!(err.message.contains("nested"));
+
+        let err = parse_query(&format!("{}a", "NOT ".repeat(100))).unwrap_err();
+        assert_eq!((err.start, err.end), (128, 131));
+
+        let err = parse_query(r#"a "" b"#).unwrap_err();
+        assert_eq!((err.start, err.end), (2, 4));
+        assert_eq!(err.message, "Empty phrase");
+    }
+
+    #[test]
+    fn error_spans_use_utf16_offsets() {
+        let err = parse_query("файл 😀 size:>1X").unwrap_err();
+        // "файл " is 5 UTF-16 units, the emoji is 2, plus a space.
+        assert_eq!(err.start, 13);
+    }
+
+    #[test]
+    fn blank_input_has_no_expression() {
+        assert_eq!(parse_query("   "), Ok(None));
+    }
+}
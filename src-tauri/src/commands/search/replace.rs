@@ -0,0 +1,423 @@
+//! Search and replace across files.
+//!
+//! Files are chosen like in a content search and rewritten with
+//! [`write_atomic`]. The original contents of every applied replacement are
+//! kept in memory so `undo_replace` can restore them.
+
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::hash::{DefaultHasher, Hasher};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use regex::{Regex, RegexBuilder};
+use similar::TextDiff;
+
+use crate::constants::{
+    CONTENT_SNIFF_LENGTH, DEFAULT_MAX_SEARCH_RESULTS, MAX_REPLACE_UNDO_RECORDS,
+    MAX_SEARCH_FILE_SIZE, REPLACE_DIFF_CONTEXT,
+};
+use crate::error::{FileManagerError, Result};
+use crate::models::{
+    FileReplacement, ReplaceMatch, ReplaceOptions, ReplaceReport, ReplaceSelection,
+    ReplaceUndoReport,
+};
+use crate::utils::write_atomic;
+
+use super::extension_allowed;
+use super::filters::EntryFilter;
+use super::walker::{self, WalkEvent};
+
+struct UndoFile {
+    path: PathBuf,
+    original: Vec<u8>,
+    /// Fingerprint of what the replacement wrote.
+    written: u64,
+}
+
+struct UndoRecord {
+    id: String,
+    files: Vec<UndoFile>,
+}
+
+/// Original contents of the most recent applied replacements.
+#[derive(Default)]
+pub(super) struct ReplaceHistory {
+    records: Mutex<VecDeque<UndoRecord>>,
+    next_id: AtomicU64,
+}
+
+impl ReplaceHistory {
+    fn record(&self, files: Vec<UndoFile>) -> Result<String> {
+        let id = format!(
+            "replace-{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let mut records = self
+            .records
+            .lock()
+            .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+
+        if records.len() >= MAX_REPLACE_UNDO_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(UndoRecord {
+            id: id.clone(),
+            files,
+        });
+        Ok(id)
+    }
+
+    fn take(&self, id: &str) -> Result<Option<UndoRecord>> {
+        let mut records = self
+            .records
+            .lock()
+            .map_err(|e| FileManagerError::IoError(e.to_string()))?;
+        Ok(records
+            .iter()
+            .position(|record| record.id == id)
+            .and_then(|index| records.remove(index)))
+    }
+}
+
+fn fingerprint(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(data);
+    hasher.finish()
+}
+
+fn version_string(data: &[u8]) -> String {
+    format!("{:016x}", fingerprint(data))
+}
+
+/// A file's replacements worked out in memory.
+struct Plan {
+    report: FileReplacement,
+    original: Vec<u8>,
+    output: String,
+}
+
+/// Builds the pattern from `search.query`.
+///
+/// Query expressions and name-only searches don't describe text to replace,
+/// so they are rejected rather than replaced literally.
+fn compile_pattern(options: &ReplaceOptions) -> Result<Regex> {
+    if options.search.expression.is_some() {
+        return Err(FileManagerError::InvalidPattern(
+            "Query expressions can't be used to replace".to_string(),
+        ));
+    }
+    if !options.search.search_content {
+        return Err(FileManagerError::InvalidPattern(
+            "Replacing needs a content search".to_string(),
+        ));
+    }
+    let query = &options.search.query;
+    if query.is_empty() {
+        return Err(FileManagerError::InvalidPattern(
+            "Search query is empty".to_string(),
+        ));
+    }
+
+    let pattern = if options.regex {
+        query.clone()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.search.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map_err(|e| FileManagerError::InvalidPattern(e.to_string()))
+}
+
+/// Reads a file that is safe to rewrite as text.
+///
+/// Returns `None` for unreadable, oversized, binary or non-UTF-8 files.
+fn read_text(path: &Path) -> Option<Vec<u8>> {
+    if fs::metadata(path).ok()?.len() > MAX_SEARCH_FILE_SIZE {
+        return None;
+    }
+    let data = fs::read(path).ok()?;
+    if data[..data.len().min(CONTENT_SNIFF_LENGTH)].contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(&data).is_ok().then_some(data)
+}
+
+/// Applies the selected replacements to a file in memory.
+///
+/// Returns `None` for files that can't be rewritten as text and files without matches.
+fn plan_file(
+    path: &Path,
+    pattern: &Regex,
+    options: &ReplaceOptions,
+    selection: Option<&ReplaceSelection>,
+) -> Option<Plan> {
+    let original = read_text(path)?;
+    let text = std::str::from_utf8(&original).ok()?;
+    let version = version_string(&original);
+    let display_path = path.to_string_lossy().to_string();
+
+    // Indices from a dry run only mean something for the content they were computed on.
+    if let Some(expected) = selection.and_then(|s| s.version.as_ref()) {
+        if *expected != version {
+            return Some(Plan {
+                report: FileReplacement {
+                    path: display_path,
+                    version,
+                    matches: Vec::new(),
+                    diff: String::new(),
+                    replaced: 0,
+                    error: Some("File changed since the preview".to_string()),
+                },
+                output: text.to_string(),
+                original,
+            });
+        }
+    }
+    let selected: Option<HashSet<u32>> = selection.map(|s| s.indices.iter().copied().collect());
+
+    let mut matches = Vec::new();
+    let mut output = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut line_number = 1;
+    let mut counted = 0;
+
+    for (index, captures) in pattern.captures_iter(text).enumerate() {
+        let Some(found) = captures.get(0) else {
+            continue;
+        };
+        let index = index as u32;
+
+        let mut replacement = String::new();
+        if options.regex {
+            captures.expand(&options.replacement, &mut replacement);
+        } else {
+            replacement.push_str(&options.replacement);
+        }
+
+        line_number += text[counted..found.start()].matches('\n').count();
+        counted = found.start();
+        let line_start = text[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[found.start()..]
+            .find('\n')
+            .map_or(text.len(), |i| found.start() + i);
+
+        if selected.as_ref().is_none_or(|s| s.contains(&index)) {
+            output.push_str(&text[copied..found.start()]);
+            output.push_str(&replacement);
+            copied = found.end();
+        }
+
+        matches.push(ReplaceMatch {
+            index,
+            line_number: line_number as u64,
+            line_content: text[line_start..line_end]
+                .trim_end_matches('\r')
+                .chars()
+                .take(200)
+                .collect(),
+            matched: found.as_str().to_string(),
+            replacement,
+        });
+    }
+
+    if matches.is_empty() {
+        return None;
+    }
+    output.push_str(&text[copied..]);
+
+    let replaced = selected.as_ref().map_or(matches.len(), |s| {
+        matches.iter().filter(|m| s.contains(&m.index)).count()
+    }) as u32;
+    let diff = if replaced > 0 {
+        TextDiff::from_lines(text, &output)
+            .unified_diff()
+            .context_radius(REPLACE_DIFF_CONTEXT)
+            .header(&display_path, &display_path)
+            .to_string()
+    } else {
+        String::new()
+    };
+
+    Some(Plan {
+        report: FileReplacement {
+            path: display_path,
+            version,
+            matches,
+            diff,
+            replaced,
+            error: None,
+        },
+        original,
+        output,
+    })
+}
+
+/// Finds matching files under the search root, honoring the search's filters.
+///
+/// Every match is collected and sorted before the cut to `max_results`, so the
+/// same files are chosen whatever order the walker threads find them in.
+/// Returns whether files were left out.
+fn walk_matching_files(options: &ReplaceOptions, pattern: &Regex) -> Result<(Vec<PathBuf>, bool)> {
+    let search = &options.search;
+    if !Path::new(&search.search_path).exists() {
+        return Err(FileManagerError::SearchPathNotFound(
+            search.search_path.clone(),
+        ));
+    }
+
+    let max_results = search
+        .max_results
+        .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS as u32) as usize;
+    let filter = search
+        .filters
+        .as_ref()
+        .map(EntryFilter::compile)
+        .transpose()?;
+    let mut paths = Vec::new();
+
+    walker::walk_parallel(
+        search,
+        &AtomicBool::new(false),
+        None,
+        |entry| {
+            if !entry.file_type().is_some_and(|t| t.is_file())
+                || !extension_allowed(&entry.file_name().to_string_lossy(), false, search)
+                || filter.as_ref().is_some_and(|f| !f.matches(entry))
+            {
+                return None;
+            }
+            let data = read_text(entry.path())?;
+            let text = std::str::from_utf8(&data).ok()?;
+            pattern.is_match(text).then(|| entry.path().to_path_buf())
+        },
+        |event| match event {
+            WalkEvent::Progress(..) => true,
+            WalkEvent::Match(path) => {
+                paths.push(path);
+                true
+            }
+        },
+    )?;
+
+    paths.sort_unstable();
+    let truncated = paths.len() > max_results;
+    paths.truncate(max_results);
+    Ok((paths, truncated))
+}
+
+/// Whether a selected file lies under the search root.
+///
+/// `..` components are rejected outright since `starts_with` compares paths
+/// without resolving them. Unless the search follows symlinks, the resolved
+/// file must lie under the resolved root as well, so a link can't be used to
+/// write outside it.
+fn is_inside_root(path: &Path, root: &Path, follow_symlinks: bool) -> bool {
+    if !path.is_absolute()
+        || path.components().any(|c| c == Component::ParentDir)
+        || !path.starts_with(root)
+    {
+        return false;
+    }
+    if follow_symlinks {
+        return true;
+    }
+    match (fs::canonicalize(path), fs::canonicalize(root)) {
+        (Ok(path), Ok(root)) => path.starts_with(root),
+        // A missing file is reported when it is read.
+        _ => true,
+    }
+}
+
+/// Replaces matches in every file the search finds, or only in `options.selection`.
+pub(super) fn replace(options: &ReplaceOptions, history: &ReplaceHistory) -> Result<ReplaceReport> {
+    let pattern = compile_pattern(options)?;
+
+    let mut truncated = false;
+    let targets: Vec<(PathBuf, Option<&ReplaceSelection>)> = match options.selection {
+        Some(ref selection) => {
+            let root = Path::new(&options.search.search_path);
+            selection
+                .iter()
+                .map(|s| {
+                    let path = PathBuf::from(&s.path);
+                    if !is_inside_root(&path, root, options.search.follow_symlinks) {
+                        return Err(FileManagerError::InvalidPath(format!(
+                            "{} is outside {}",
+                            s.path, options.search.search_path
+                        )));
+                    }
+                    Ok((path, Some(s)))
+                })
+                .collect::<Result<_>>()?
+        }
+        None => {
+            let (paths, cut) = walk_matching_files(options, &pattern)?;
+            truncated = cut;
+            paths.into_iter().map(|path| (path, None)).collect()
+        }
+    };
+
+    let mut files = Vec::new();
+    let mut undo = Vec::new();
+    let mut total_replaced = 0;
+
+    for (path, selection) in targets {
+        let Some(mut plan) = plan_file(&path, &pattern, options, selection) else {
+            continue;
+        };
+
+        if !options.dry_run && plan.report.replaced > 0 && plan.report.error.is_none() {
+            match write_atomic(&path, plan.output.as_bytes()) {
+                Ok(()) => undo.push(UndoFile {
+                    path,
+                    original: plan.original,
+                    written: fingerprint(plan.output.as_bytes()),
+                }),
+                Err(e) => {
+                    plan.report.replaced = 0;
+                    plan.report.error = Some(e.to_string());
+                }
+            }
+        }
+
+        total_replaced += plan.report.replaced;
+        files.push(plan.report);
+    }
+
+    let undo_id = if undo.is_empty() {
+        None
+    } else {
+        Some(history.record(undo)?)
+    };
+
+    Ok(ReplaceReport {
+        files,
+        total_replaced,
+        undo_id,
+        truncated,
+    })
+}
+
+/// Restores the files of an applied replacement that weren't modified since.
+pub(super) fn undo(undo_id: &str, history: &ReplaceHistory) -> Result<ReplaceUndoReport> {
+    let record = history.take(undo_id)?.ok_or_else(|| {
+        FileManagerError::UndoUnavailable(format!("unknown or expired replacement {undo_id}"))
+    })?;
+
+    let mut report = ReplaceUndoReport::default();
+    for file in record.files {
+        let path = file.path.to_string_lossy().to_string();
+        let unchanged = fs::read(&file.path).is_ok_and(|data| fingerprint(&data) == file.written);
+
+        if unchanged && write_atomic(&file.path, &file.original).is_ok() {
+            report.restored.push(path);
+        } else {
+            report.conflicts.push(path);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,168 @@
+//! Parallel directory walker used by search.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::sync_channel;
+use std::sync::RwLock;
+
+use ignore::overrides::OverrideBuilder;
+use ignore::{DirEntry, WalkBuilder, WalkParallel, WalkState};
+
+use crate::constants::{MAX_SEARCH_DEPTH, SEARCH_PROGRESS_INTERVAL};
+use crate::error::{FileManagerError, Result};
+use crate::models::SearchOptions;
+
+/// Bound on queued walker messages; applies backpressure when the consumer is slow.
+const WALK_CHANNEL_CAPACITY: usize = 1024;
+
+/// Events delivered to the consumer of [`walk_parallel`].
+pub(super) enum WalkEvent<T> {
+    /// Sent every `SEARCH_PROGRESS_INTERVAL` entries with the running entry count.
+    Progress(usize, PathBuf),
+    Match(T),
+}
+
+/// The last path a search still wants when it keeps only the first paths in order.
+///
+/// Entries that sort after it are passed over, and so are whole directories,
+/// since everything below a directory sorts after the directory itself.
+#[derive(Default)]
+pub(super) struct PathCutoff(RwLock<Option<String>>);
+
+impl PathCutoff {
+    pub(super) fn set(&self, path: &str) {
+        if let Ok(mut cutoff) = self.0.write() {
+            *cutoff = Some(path.to_string());
+        }
+    }
+
+    fn excludes(&self, path: &Path) -> bool {
+        match self.0.read() {
+            Ok(cutoff) => cutoff
+                .as_deref()
+                .is_some_and(|cutoff| *path.to_string_lossy() > *cutoff),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Builds a parallel walker for `options.search_path`.
+///
+/// Hidden entries are always visited; ignore files are honored only when
+/// `respect_gitignore` is set. With `follow_symlinks`, symlink loops are
+/// detected by the walker and skipped.
+fn build_walker(options: &SearchOptions) -> Result<WalkParallel> {
+    let search_path = Path::new(&options.search_path);
+    let respect_ignore = options.respect_gitignore;
+    let max_depth = options
+        .max_depth
+        .map(|d| d as usize)
+        .unwrap_or(MAX_SEARCH_DEPTH);
+
+    let mut builder = WalkBuilder::new(search_path);
+    builder
+        .hidden(false)
+        .parents(respect_ignore)
+        .ignore(respect_ignore)
+        .git_ignore(respect_ignore)
+        .git_global(respect_ignore)
+        .git_exclude(respect_ignore)
+        .require_git(false)
+        .max_depth(Some(max_depth))
+        .follow_links(options.follow_symlinks);
+
+    if let Some(ref globs) = options.exclude_globs {
+        let mut overrides = OverrideBuilder::new(search_path);
+        for glob in globs.iter().filter(|g| !g.trim().is_empty()) {
+            overrides
+                .add(&format!("!{}", glob.trim()))
+                .map_err(|e| FileManagerError::InvalidPattern(e.to_string()))?;
+        }
+        let overrides = overrides
+            .build()
+            .map_err(|e| FileManagerError::InvalidPattern(e.to_string()))?;
+        builder.overrides(overrides);
+    }
+
+    Ok(builder.build_parallel())
+}
+
+/// Walks the search root in parallel and feeds matches back to the calling thread.
+///
+/// `matcher` runs on worker threads; `on_event` runs on the calling thread, so it can
+/// emit events or accumulate results without locking. Returning `false` from
+/// `on_event` stops the walk. Entries past `cutoff` are neither matched nor descended into.
+///
+/// Returns the number of scanned entries, or `Cancelled` if `cancelled` was raised.
+pub(super) fn walk_parallel<T, M, F>(
+    options: &SearchOptions,
+    cancelled: &AtomicBool,
+    cutoff: Option<&PathCutoff>,
+    matcher: M,
+    mut on_event: F,
+) -> Result<usize>
+where
+    T: Send,
+    M: Fn(&DirEntry) -> Option<T> + Sync,
+    F: FnMut(WalkEvent<T>) -> bool,
+{
+    let walker = build_walker(options)?;
+    let scanned = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (tx, rx) = sync_channel::<WalkEvent<T>>(WALK_CHANNEL_CAPACITY);
+
+    std::thread::scope(|scope| {
+        let scanned = &scanned;
+        let stop = &stop;
+        let matcher = &matcher;
+
+        scope.spawn(move || {
+            walker.run(|| {
+                let tx = tx.clone();
+                Box::new(move |entry| {
+                    if stop.load(Ordering::Relaxed) || cancelled.load(Ordering::Relaxed) {
+                        return WalkState::Quit;
+                    }
+
+                    let Ok(entry) = entry else {
+                        return WalkState::Continue;
+                    };
+
+                    let count = scanned.fetch_add(1, Ordering::Relaxed) + 1;
+                    if count.is_multiple_of(SEARCH_PROGRESS_INTERVAL)
+                        && tx
+                            .send(WalkEvent::Progress(count, entry.path().to_path_buf()))
+                            .is_err()
+                    {
+                        return WalkState::Quit;
+                    }
+
+                    if cutoff.is_some_and(|cutoff| cutoff.excludes(entry.path())) {
+                        return WalkState::Skip;
+                    }
+
+                    if let Some(found) = matcher(&entry) {
+                        if tx.send(WalkEvent::Match(found)).is_err() {
+                            return WalkState::Quit;
+                        }
+                    }
+
+                    WalkState::Continue
+                })
+            });
+        });
+
+        for event in rx {
+            if !on_event(event) {
+                stop.store(true, Ordering::Relaxed);
+                break;
+            }
+        }
+    });
+
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(FileManagerError::Cancelled);
+    }
+
+    Ok(scanned.load(Ordering::Relaxed))
+}
@@ -1,15 +1,19 @@
-//! Filesystem watcher for real-time directory updates.
+//! Filesystem watcher for real-time directory updates and live file tails.
 
 use std::collections::HashMap;
-use std::path::Path;
-use std::sync::Mutex;
+use std::fs::{self, File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
-use notify::{recommended_watcher, Event, RecursiveMode, Watcher};
+use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
 
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::constants::MAX_TAIL_READ_BYTES;
 use crate::error::FileManagerError;
-use crate::models::FsChangeEvent;
+use crate::models::{FileTailEvent, FsChangeEvent};
 
 fn normalize_watch_key(raw: &str) -> String {
     let trimmed = raw.trim();
@@ -78,12 +82,15 @@ fn normalize_watch_key(raw: &str) -> String {
 /// Global state for managing filesystem watchers.
 pub struct WatcherState {
     watchers: Mutex<HashMap<String, Box<dyn Watcher + Send>>>,
+    /// Tailed files, keyed like `watchers`.
+    tails: Mutex<HashMap<String, TailWatch>>,
 }
 
 impl WatcherState {
     pub fn new() -> Self {
         Self {
             watchers: Mutex::new(HashMap::new()),
+            tails: Mutex::new(HashMap::new()),
         }
     }
 
@@ -92,6 +99,11 @@ impl WatcherState {
         self.watchers.lock().map(|w| w.len()).unwrap_or(0)
     }
 
+    #[doc(hidden)]
+    pub fn active_tail_count(&self) -> usize {
+        self.tails.lock().map(|t| t.len()).unwrap_or(0)
+    }
+
     #[doc(hidden)]
     pub fn register_null_watcher_for_test(&self, path: &str) -> std::result::Result<(), String> {
         let watch_path = validate_watch_path(path)?;
@@ -244,13 +256,282 @@ pub async fn unwatch_directory(path: String, app: AppHandle) -> std::result::Res
 pub fn unwatch_all_sync(state: &WatcherState) -> std::result::Result<(), String> {
     let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
     watchers.clear();
+    let mut tails = state.tails.lock().map_err(|e| e.to_string())?;
+    tails.clear();
     Ok(())
 }
 
-/// Stops watching all directories. Called on app cleanup.
+/// Stops watching all directories and tailing all files. Called on app cleanup.
 #[tauri::command]
 #[specta::specta]
 pub async fn unwatch_all(app: AppHandle) -> std::result::Result<(), String> {
     let state = app.state::<WatcherState>();
     unwatch_all_sync(&state)
 }
+
+/// Identifies the file behind a path, so rotation can be told from appends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity(u64, u64);
+
+impl FileIdentity {
+    #[cfg(unix)]
+    fn of(meta: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self(meta.dev(), meta.ino())
+    }
+
+    /// Without inodes, a recreated file is recognized by its creation time.
+    #[cfg(not(unix))]
+    fn of(meta: &Metadata) -> Self {
+        let created = meta
+            .created()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self(0, created)
+    }
+}
+
+/// How far a tailed file has been read.
+struct TailCursor {
+    path: PathBuf,
+    identity: FileIdentity,
+    offset: u64,
+    /// Start of a line whose newline hasn't been written yet.
+    partial: Vec<u8>,
+    /// Skipping ahead cut a line; its rest is dropped up to the next newline.
+    resyncing: bool,
+}
+
+impl TailCursor {
+    /// Starts at the current end of the file.
+    fn open(path: &Path) -> std::io::Result<Self> {
+        let meta = fs::metadata(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            identity: FileIdentity::of(&meta),
+            offset: meta.len(),
+            partial: Vec::new(),
+            resyncing: false,
+        })
+    }
+
+    /// Reads what was appended since the last call.
+    ///
+    /// Returns `None` when there is nothing to report, including while a
+    /// rotated file is missing before its replacement is created.
+    fn poll(&mut self) -> Option<FileTailEvent> {
+        let meta = fs::metadata(&self.path).ok()?;
+        let identity = FileIdentity::of(&meta);
+        let len = meta.len();
+        let rotated = identity != self.identity;
+        let truncated = !rotated && len < self.offset;
+
+        if rotated || truncated {
+            self.identity = identity;
+            self.offset = 0;
+            self.partial.clear();
+            self.resyncing = false;
+        }
+
+        let mut skipped_bytes = 0;
+        let mut lines = Vec::new();
+        if len > self.offset {
+            if len - self.offset > MAX_TAIL_READ_BYTES {
+                let resume = len - MAX_TAIL_READ_BYTES;
+                skipped_bytes = resume - self.offset;
+                // The byte before `resume` is read again, so a skip that ends
+                // exactly at a line start doesn't drop that line.
+                self.offset = resume - 1;
+                self.partial.clear();
+                self.resyncing = true;
+            }
+
+            let mut file = File::open(&self.path).ok()?;
+            file.seek(SeekFrom::Start(self.offset)).ok()?;
+            let read = file
+                .take(len - self.offset)
+                .read_to_end(&mut self.partial)
+                .ok()?;
+            self.offset += read as u64;
+
+            if self.resyncing {
+                match self.partial.iter().position(|&b| b == b'\n') {
+                    Some(end) => {
+                        self.partial.drain(..=end);
+                        self.resyncing = false;
+                    }
+                    None => self.partial.clear(),
+                }
+            }
+
+            if let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') {
+                let complete: Vec<u8> = self.partial.drain(..=end).collect();
+                lines = String::from_utf8_lossy(&complete[..end])
+                    .split('\n')
+                    .map(|line| line.trim_end_matches('\r').to_string())
+                    .collect();
+            }
+        }
+
+        if lines.is_empty() && !rotated && !truncated && skipped_bytes == 0 {
+            return None;
+        }
+        Some(FileTailEvent {
+            path: self.path.to_string_lossy().to_string(),
+            lines,
+            truncated,
+            rotated,
+            skipped_bytes,
+        })
+    }
+}
+
+/// Watcher of a tailed file's parent directory.
+struct TailWatch {
+    _watcher: Box<dyn Watcher + Send>,
+    /// Set on drop, since the watcher may still deliver an event while it shuts down.
+    stopped: Arc<AtomicBool>,
+}
+
+impl Drop for TailWatch {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+fn validate_tail_path(path: &str) -> std::result::Result<PathBuf, String> {
+    if path.trim().is_empty() {
+        return Err(FileManagerError::EmptyPath.to_string());
+    }
+    let file_path = PathBuf::from(path);
+    if !file_path.is_absolute() {
+        return Err(FileManagerError::NotAbsolutePath(path.to_string()).to_string());
+    }
+    if !file_path.is_file() {
+        return Err(FileManagerError::InvalidPath(format!("Not a file: {path}")).to_string());
+    }
+    Ok(file_path)
+}
+
+/// Starts tailing `path`, calling `on_event` with lines appended from now on.
+///
+/// The parent directory is watched rather than the file, so a file that is
+/// renamed away and recreated (log rotation) keeps being followed.
+#[doc(hidden)]
+pub fn tail_file_sync(
+    path: &str,
+    state: &WatcherState,
+    on_event: impl Fn(FileTailEvent) + Send + 'static,
+) -> std::result::Result<(), String> {
+    let file_path = validate_tail_path(path)?;
+    let key = normalize_watch_key(path);
+    let (Some(parent), Some(name)) = (file_path.parent(), file_path.file_name()) else {
+        return Err(FileManagerError::InvalidPath(path.to_string()).to_string());
+    };
+    let name = name.to_os_string();
+
+    let mut tails = state.tails.lock().map_err(|e| e.to_string())?;
+    if tails.contains_key(&key) {
+        return Ok(());
+    }
+
+    let cursor = Arc::new(Mutex::new(
+        TailCursor::open(&file_path).map_err(|e| e.to_string())?,
+    ));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let watcher_stopped = Arc::clone(&stopped);
+    let mut watcher: Box<dyn Watcher + Send> = Box::new(
+        recommended_watcher(move |res: std::result::Result<Event, notify::Error>| {
+            let Ok(event) = res else {
+                return;
+            };
+            let relevant = event.need_rescan()
+                || (!matches!(event.kind, EventKind::Access(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == Some(name.as_os_str())));
+            if !relevant {
+                return;
+            }
+            let Ok(mut cursor) = cursor.lock() else {
+                return;
+            };
+            if watcher_stopped.load(Ordering::SeqCst) {
+                return;
+            }
+            if let Some(tail) = cursor.poll() {
+                on_event(tail);
+            }
+        })
+        .map_err(|e| e.to_string())?,
+    );
+    watcher
+        .watch(parent, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    tails.insert(
+        key,
+        TailWatch {
+            _watcher: watcher,
+            stopped,
+        },
+    );
+    Ok(())
+}
+
+/// Follows a file as it grows, emitting appended lines as `file-tail` events.
+#[tauri::command]
+#[specta::specta]
+pub async fn tail_file(path: String, app: AppHandle) -> std::result::Result<(), String> {
+    let emit_app = app.clone();
+    let state = app.state::<WatcherState>();
+    tail_file_sync(&path, &state, move |event| {
+        let _ = emit_app.emit("file-tail", event);
+    })
+}
+
+#[doc(hidden)]
+pub fn untail_file_sync(path: &str, state: &WatcherState) -> std::result::Result<(), String> {
+    let mut tails = state.tails.lock().map_err(|e| e.to_string())?;
+    tails.remove(&normalize_watch_key(path));
+    Ok(())
+}
+
+/// Stops tailing a file.
+#[tauri::command]
+#[specta::specta]
+pub async fn untail_file(path: String, app: AppHandle) -> std::result::Result<(), String> {
+    let state = app.state::<WatcherState>();
+    untail_file_sync(&path, &state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_skips_to_the_next_whole_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        fs::write(&path, "").unwrap();
+        let mut cursor = TailCursor::open(&path).unwrap();
+
+        let line_len = "entry 0000000\n".len() as u64;
+        let count = 2 * MAX_TAIL_READ_BYTES / line_len;
+        let body: String = (0..count).map(|n| format!("entry {n:07}\n")).collect();
+        fs::write(&path, &body).unwrap();
+
+        let event = cursor.poll().unwrap();
+        assert!(event.skipped_bytes > 0);
+        assert!(event
+            .lines
+            .iter()
+            .all(|line| line.len() == 13 && line.starts_with("entry ")));
+        assert_eq!(
+            event.lines.last().map(String::as_str),
+            Some(format!("entry {:07}", count - 1).as_str())
+        );
+    }
+}
@@ -3,12 +3,26 @@
 /// Maximum file size for content search (4 MB).
 pub const MAX_SEARCH_FILE_SIZE: u64 = 4 * 1024 * 1024;
 
+/// Maximum archive size that content search descends into (512 MB).
+pub const MAX_ARCHIVE_SEARCH_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Maximum entries read from a single archive during content search.
+pub const MAX_ARCHIVE_SEARCH_ENTRIES: usize = 10_000;
+
+/// Separates an archive path from the path of an entry inside it, as in `a.zip!/dir/file.txt`.
+pub const ARCHIVE_PATH_SEPARATOR: &str = "!/";
+
 /// Maximum file size for full text content reads (4 MB).
 pub const MAX_FILE_CONTENT_SIZE: u64 = 4 * 1024 * 1024;
 
-/// Maximum file size for preview (5 MB).
+/// Maximum size of a GIF sent to the preview as it is, keeping its animation (5 MB).
 pub const MAX_PREVIEW_FILE_SIZE: usize = 5_000_000;
 
+/// Maximum file size for image previews, which are decoded and downscaled (256 MB).
+///
+/// Images must also pass `MAX_THUMBNAIL_PIXELS`.
+pub const MAX_IMAGE_PREVIEW_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
 /// Maximum file size allowed for thumbnail generation (25 MB).
 ///
 /// This is a safety and performance limit to avoid decoding extremely large files.
@@ -23,13 +37,46 @@ pub const MAX_THUMBNAIL_PIXELS: u64 = 100_000_000;
 pub const MIN_THUMBNAIL_SIDE: u32 = 16;
 pub const MAX_THUMBNAIL_SIDE: u32 = 512;
 
+/// Bytes scanned from the start of an image for an XMP packet (1 MB).
+pub const XMP_SCAN_LENGTH: usize = 1024 * 1024;
+
+/// Archive-based formats whose thumbnail is an image stored inside them:
+/// the saved preview of office files, or the cover of e-books and comics.
+pub const EMBEDDED_THUMBNAIL_EXTENSIONS: &[&str] = &[
+    "docx", "xlsx", "xlsm", "xlsb", "pptx", "odt", "ods", "odp", "epub", "cbz",
+];
+
 /// Maximum text preview length in characters.
 pub const MAX_TEXT_PREVIEW_LENGTH: usize = 10_000;
 
+/// Lines between checkpoints of the sparse line-offset index used for paging text.
+pub const LINE_INDEX_INTERVAL: u64 = 1_000;
+
+/// Maximum bytes returned by one `read_text_range` call (1 MB).
+pub const MAX_TEXT_RANGE_BYTES: u64 = 1024 * 1024;
+
+/// Number of files whose line-offset index is kept in memory.
+pub const MAX_CACHED_LINE_INDEXES: usize = 16;
+
+/// Bytes shown per hex dump row.
+pub const HEX_BYTES_PER_ROW: usize = 16;
+
+/// Bytes of a binary file shown in its hex preview (4 KB).
+pub const HEX_PREVIEW_BYTES: u32 = 4 * 1024;
+
+/// Maximum bytes returned by one `read_bytes_range` call (64 KB).
+pub const MAX_HEX_RANGE_BYTES: u32 = 64 * 1024;
+
+/// Matches returned by one `search_bytes` call unless a limit is given.
+pub const DEFAULT_BYTE_SEARCH_MATCHES: u32 = 1_000;
+
+/// Maximum bytes of new lines sent in one `file-tail` event (1 MB).
+pub const MAX_TAIL_READ_BYTES: u64 = 1024 * 1024;
+
 /// Batch size for directory streaming.
 pub const DIRECTORY_BATCH_SIZE: usize = 100;
 
-/// Maximum depth for recursive search.
+/// Default maximum depth for recursive search.
 pub const MAX_SEARCH_DEPTH: usize = 10;
 
 /// Default maximum search results.
@@ -41,6 +88,62 @@ pub const MAX_CONTENT_MATCHES_PER_FILE: usize = 10;
 /// Progress update interval for search (every N files).
 pub const SEARCH_PROGRESS_INTERVAL: usize = 100;
 
+/// How long a walked candidate list is reused by fuzzy search when its root
+/// can't be watched for changes (seconds).
+pub const FUZZY_CANDIDATE_TTL_SECS: u64 = 30;
+
+/// Maximum number of entries kept per root for fuzzy search; the shallowest win.
+pub const MAX_FUZZY_CANDIDATES: usize = 200_000;
+
+/// Number of applied replacements kept for `undo_replace`.
+pub const MAX_REPLACE_UNDO_RECORDS: usize = 20;
+
+/// Lines of context around each change in replacement diffs.
+pub const REPLACE_DIFF_CONTEXT: usize = 3;
+
+/// File name of the saved searches store inside the app data directory.
+pub const SAVED_SEARCHES_FILE: &str = "saved-searches.json";
+
+/// Quiet period after filesystem changes before a watched saved search is re-run (ms).
+pub const SAVED_SEARCH_REFRESH_DEBOUNCE_MS: u64 = 500;
+
+/// How far back `recent_changes` looks by default (7 days).
+pub const DEFAULT_RECENT_CHANGES_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Default maximum number of files returned by `recent_changes`.
+pub const DEFAULT_RECENT_CHANGES_LIMIT: usize = 200;
+
+/// Directory names skipped by `recent_changes` by default: caches and build output.
+pub const RECENT_CHANGES_EXCLUDED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "__pycache__",
+    "cache",
+    "Cache",
+    "Caches",
+    "CachedData",
+    "GPUCache",
+    "Code Cache",
+];
+
+/// File name of the index database inside the app data directory.
+pub const INDEX_DATABASE_FILE: &str = "file-index.sqlite3";
+
+/// Interval between full reconciliation scans of indexed roots (15 minutes).
+pub const INDEX_RECONCILE_INTERVAL_SECS: u64 = 15 * 60;
+
+/// Number of index rows written per database transaction during a scan.
+pub const INDEX_BATCH_SIZE: usize = 1_000;
+
+/// Memory budget of the full-text index writer (50 MB).
+pub const CONTENT_INDEX_WRITER_HEAP: usize = 50 * 1024 * 1024;
+
+/// Number of files added to the full-text index between commits.
+pub const CONTENT_INDEX_COMMIT_INTERVAL: usize = 500;
+
+/// Maximum snippet length for full-text hits in characters.
+pub const CONTENT_SNIPPET_MAX_CHARS: usize = 200;
+
 /// Windows hidden file attribute flag.
 #[cfg(windows)]
 pub const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
@@ -52,17 +155,68 @@ pub const TEXT_EXTENSIONS: &[&str] = &[
     "csv",
 ];
 
+/// File names previewed as text regardless of content, matched case-insensitively.
+pub const TEXT_FILE_NAMES: &[&str] = &[
+    "dockerfile",
+    "containerfile",
+    "makefile",
+    "gnumakefile",
+    "cmakelists.txt",
+    "jenkinsfile",
+    "vagrantfile",
+    "gemfile",
+    "rakefile",
+    "procfile",
+    "brewfile",
+    "license",
+    "readme",
+    "changelog",
+    "authors",
+    ".env",
+    ".gitignore",
+    ".gitattributes",
+    ".gitmodules",
+    ".dockerignore",
+    ".editorconfig",
+    ".npmrc",
+    ".bashrc",
+    ".bash_profile",
+    ".zshrc",
+    ".profile",
+];
+
+/// Bytes read from the start of a file to decide whether it is text (8 KB).
+pub const CONTENT_SNIFF_LENGTH: usize = 8 * 1024;
+
 /// Image extensions for preview.
-pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "ico"];
+///
+/// Previews and thumbnails recognize images by content; this list names the
+/// formats they can read.
+pub const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "webp", "ico", "tif", "tiff", "tga", "pbm", "pgm", "ppm",
+    "pnm", "pam", "qoi", "dds", "svg", "svgz", "psd", "psb",
+];
 
-/// Document extensions (OOXML Word).
-pub const DOCUMENT_EXTENSIONS: &[&str] = &["docx"];
+/// Viewport side image previews are fitted into when none is requested.
+pub const DEFAULT_IMAGE_PREVIEW_SIDE: u32 = 2048;
 
-/// Spreadsheet extensions (OOXML Excel).
-pub const SPREADSHEET_EXTENSIONS: &[&str] = &["xlsx"];
+/// Largest viewport side accepted for image previews.
+pub const MAX_IMAGE_PREVIEW_SIDE: u32 = 8192;
 
-/// Presentation extensions (OOXML PowerPoint).
-pub const PRESENTATION_EXTENSIONS: &[&str] = &["pptx"];
+/// JPEG quality of downscaled image previews.
+pub const IMAGE_PREVIEW_JPEG_QUALITY: u8 = 85;
+
+/// Document extensions (OOXML Word, OpenDocument Text).
+pub const DOCUMENT_EXTENSIONS: &[&str] = &["docx", "odt"];
+
+/// Spreadsheet extensions (OOXML and legacy Excel, OpenDocument Spreadsheet).
+pub const SPREADSHEET_EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
+/// Presentation extensions (OOXML PowerPoint, OpenDocument Presentation).
+pub const PRESENTATION_EXTENSIONS: &[&str] = &["pptx", "odp"];
+
+/// PDF extensions.
+pub const PDF_EXTENSIONS: &[&str] = &["pdf"];
 
 /// Maximum file size for office document preview (50 MB).
 pub const MAX_OFFICE_FILE_SIZE: u64 = 50 * 1024 * 1024;
@@ -70,8 +224,32 @@ pub const MAX_OFFICE_FILE_SIZE: u64 = 50 * 1024 * 1024;
 /// Maximum paragraphs to extract from a DOCX.
 pub const MAX_DOCUMENT_PARAGRAPHS: usize = 500;
 
-/// Maximum rows per sheet to extract from an XLSX.
+/// Maximum embedded images returned with a document preview.
+pub const MAX_DOCUMENT_IMAGES: usize = 20;
+
+/// Longest side of images embedded in a document preview.
+pub const DOCUMENT_IMAGE_MAX_SIDE: u32 = 800;
+
+/// Maximum rows per sheet to extract from a spreadsheet.
 pub const MAX_SPREADSHEET_ROWS: usize = 200;
 
+/// Number of sheets kept in memory for `read_sheet_range` paging.
+pub const MAX_CACHED_SHEETS: usize = 4;
+
+/// Maximum rows returned by one `read_sheet_range` call.
+pub const MAX_SHEET_RANGE_ROWS: u32 = 1_000;
+
 /// Maximum slides to extract from a PPTX.
 pub const MAX_PRESENTATION_SLIDES: usize = 50;
+
+/// Maximum distinct pictures decoded for a presentation preview.
+pub const MAX_PRESENTATION_IMAGES: usize = 40;
+
+/// Longest side of slide pictures in a presentation preview.
+pub const SLIDE_IMAGE_MAX_SIDE: u32 = 320;
+
+/// Maximum pages whose text is extracted from a PDF.
+pub const MAX_PDF_PAGES: usize = 50;
+
+/// Maximum characters of text kept per PDF page.
+pub const MAX_PDF_PAGE_TEXT_LENGTH: usize = 10_000;
@@ -54,12 +54,27 @@ pub enum FileManagerError {
     #[error("Search path not found: {0}")]
     SearchPathNotFound(String),
 
+    #[error("Invalid search pattern: {0}")]
+    InvalidPattern(String),
+
+    #[error("Not supported on this platform: {0}")]
+    UnsupportedPlatform(String),
+
     #[error("Join error: {0}")]
     JoinError(String),
 
     #[error("Watch error: {0}")]
     WatchError(String),
 
+    #[error("Index error: {0}")]
+    IndexError(String),
+
+    #[error("Saved search not found: {0}")]
+    SavedSearchNotFound(String),
+
+    #[error("Cannot undo: {0}")]
+    UndoUnavailable(String),
+
     #[error("Operation cancelled")]
     Cancelled,
 
@@ -88,5 +103,17 @@ impl From<std::io::Error> for FileManagerError {
     }
 }
 
+impl From<rusqlite::Error> for FileManagerError {
+    fn from(err: rusqlite::Error) -> Self {
+        FileManagerError::IndexError(err.to_string())
+    }
+}
+
+impl From<tantivy::TantivyError> for FileManagerError {
+    fn from(err: tantivy::TantivyError) -> Self {
+        FileManagerError::IndexError(err.to_string())
+    }
+}
+
 /// Result type alias for file manager operations.
 pub type Result<T> = std::result::Result<T, FileManagerError>;
@@ -9,7 +9,13 @@ pub mod error;
 pub mod models;
 pub mod utils;
 
+use commands::index::IndexState;
+use commands::preview::{SheetCacheState, TextIndexState};
+use commands::saved_search::SavedSearchState;
+use commands::search::SearchState;
 use commands::watcher::WatcherState;
+use constants::{INDEX_DATABASE_FILE, SAVED_SEARCHES_FILE};
+use tauri::Manager;
 use tauri_specta::{collect_commands, Builder};
 
 /// Runs the Tauri application.
@@ -32,15 +38,44 @@ pub fn run() {
         // Search
         commands::search::search_files,
         commands::search::search_files_stream,
+        commands::search::cancel_search,
         commands::search::search_by_name,
         commands::search::search_content,
+        commands::search::parse_search_query,
+        commands::search::fuzzy_search,
+        commands::search::replace_in_files,
+        commands::search::undo_replace,
+        // Saved searches
+        commands::saved_search::list_saved_searches,
+        commands::saved_search::save_search,
+        commands::saved_search::delete_saved_search,
+        commands::saved_search::open_saved_search,
+        commands::saved_search::watch_saved_search,
+        commands::saved_search::unwatch_saved_search,
+        // Index
+        commands::index::add_index_root,
+        commands::index::remove_index_root,
+        commands::index::get_index_status,
+        commands::index::rebuild_index,
+        commands::index::search_index,
+        commands::index::search_content_index,
+        // Recent changes
+        commands::recent::recent_changes,
         // Preview
         commands::preview::get_file_preview,
+        commands::preview::get_image_preview,
         commands::preview::get_thumbnail,
+        commands::preview::read_text_range,
+        commands::preview::read_bytes_range,
+        commands::preview::search_bytes,
+        commands::preview::read_sheet_range,
+        commands::preview::get_image_metadata,
         // Watcher
         commands::watcher::watch_directory,
         commands::watcher::unwatch_directory,
         commands::watcher::unwatch_all,
+        commands::watcher::tail_file,
+        commands::watcher::untail_file,
     ]);
 
     #[cfg(debug_assertions)]
@@ -56,9 +91,32 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(WatcherState::new())
+        .manage(SearchState::new())
+        .manage(TextIndexState::new())
+        .manage(SheetCacheState::new())
         .invoke_handler(builder.invoke_handler())
         .setup(move |app| {
             builder.mount_events(app);
+
+            // Fall back to an in-memory index if the data directory is unusable.
+            let index = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| {
+                    IndexState::open(&dir.join(INDEX_DATABASE_FILE)).map_err(|e| e.to_string())
+                })
+                .or_else(|_| IndexState::in_memory())?;
+            app.manage(index);
+
+            let saved_searches = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .and_then(|dir| SavedSearchState::open(&dir.join(SAVED_SEARCHES_FILE)).ok())
+                .unwrap_or_else(SavedSearchState::in_memory);
+            app.manage(saved_searches);
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -17,3 +17,17 @@ pub struct FsChangeEvent {
     pub kind: String,
     pub paths: Vec<String>,
 }
+
+/// Lines appended to a tailed file, emitted as `file-tail`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FileTailEvent {
+    pub path: String,
+    /// Complete new lines, without line endings.
+    pub lines: Vec<String>,
+    /// The file shrank and is being read again from the start.
+    pub truncated: bool,
+    /// The path now refers to a different file (log rotation); read from its start.
+    pub rotated: bool,
+    /// Bytes left out because the file grew faster than they could be sent.
+    pub skipped_bytes: u64,
+}
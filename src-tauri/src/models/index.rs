@@ -0,0 +1,85 @@
+//! Persistent file index models.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::SearchFilters;
+
+/// Indexing state of a root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum IndexRootState {
+    /// Waiting for its first scan.
+    Pending,
+    /// A full scan is running.
+    Scanning { scanned: u64 },
+    /// Scanned and kept fresh by the watcher.
+    Ready,
+    /// The last scan or watcher setup failed.
+    Error { message: String },
+}
+
+/// Status of one indexed root.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct IndexRootStatus {
+    pub path: String,
+    pub state: IndexRootState,
+    pub entries: u64,
+    /// Unix timestamp of the last completed full scan.
+    pub last_scan: Option<i64>,
+    /// Whether a recursive watcher is keeping the root fresh.
+    pub watching: bool,
+}
+
+/// Overall status of the file index.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct IndexStatus {
+    pub roots: Vec<IndexRootStatus>,
+    pub total_entries: u64,
+    /// Number of files in the full-text content index.
+    pub content_documents: u64,
+    /// Location of the database; `None` when the index only lives in memory.
+    pub database_path: Option<String>,
+}
+
+/// A name and metadata query against the file index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct IndexQuery {
+    /// Case-insensitive substring of the file name; empty matches everything.
+    pub query: String,
+    /// Restrict results to these directories; defaults to all indexed roots.
+    #[serde(default)]
+    pub roots: Option<Vec<String>>,
+    #[serde(default)]
+    pub filters: Option<SearchFilters>,
+    pub max_results: Option<u32>,
+}
+
+/// A full-text query against indexed file contents.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct ContentQuery {
+    /// Words to find; all must occur. Supports `"phrases"`, `-exclusions` and `OR`.
+    pub query: String,
+    /// Restrict results to these directories; defaults to all indexed roots.
+    #[serde(default)]
+    pub roots: Option<Vec<String>>,
+    pub max_results: Option<u32>,
+}
+
+/// Highlighted span within a snippet, as UTF-16 offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct TextRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// A ranked full-text hit.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ContentHit {
+    pub path: String,
+    pub name: String,
+    pub score: f32,
+    /// Excerpt around the best matching terms; empty when only the name matched.
+    pub snippet: String,
+    pub highlights: Vec<TextRange>,
+}
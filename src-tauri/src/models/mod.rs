@@ -3,12 +3,33 @@
 mod drive_info;
 mod events;
 mod file_entry;
+mod index;
 pub(crate) mod preview;
+mod recent;
+mod replace;
+mod saved_search;
 mod search;
 
 pub use drive_info::DriveInfo;
-pub use events::{CopyProgress, FsChangeEvent};
+pub use events::{CopyProgress, FileTailEvent, FsChangeEvent};
 pub use file_entry::FileEntry;
+pub use index::{
+    ContentHit, ContentQuery, IndexQuery, IndexRootState, IndexRootStatus, IndexStatus, TextRange,
+};
 pub use preview::FilePreview;
 pub use preview::Thumbnail;
-pub use search::{ContentMatch, SearchOptions, SearchProgress, SearchResult};
+pub use preview::{
+    BytePattern, ByteSearchResult, CellValue, GpsPosition, HexChunk, HexRow, ImageMetadata,
+    MergedRange, SheetCell, SheetRange, TextChunk, TextRangeRequest,
+};
+pub use recent::RecentDay;
+pub use replace::{
+    FileReplacement, ReplaceMatch, ReplaceOptions, ReplaceReport, ReplaceSelection,
+    ReplaceUndoReport,
+};
+pub use saved_search::{SavedSearch, SavedSearchUpdate};
+pub use search::{
+    ContentMatch, EntryKind, FuzzyMatch, FuzzySearchOptions, MatchLocation, QueryError, QueryExpr,
+    SearchBatch, SearchComplete, SearchFilters, SearchOptions, SearchProgress, SearchResult,
+    TimeBound, TimeRange,
+};
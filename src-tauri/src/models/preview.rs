@@ -12,30 +12,249 @@ pub struct Thumbnail {
     pub mime: String,
 }
 
+/// Where a photo was taken, in decimal degrees.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct GpsPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Metres above sea level.
+    pub altitude: Option<f64>,
+}
+
+/// Pixel format and EXIF/XMP metadata of an image file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct ImageMetadata {
+    /// Format detected from the content, e.g. `"Jpeg"`.
+    pub format: String,
+    /// Displayed size, with the sides swapped for rotated orientations.
+    pub width: u32,
+    pub height: u32,
+    /// e.g. `"Rgb8"`; `None` when the header couldn't be read.
+    pub color_type: Option<String>,
+    /// Bits per channel.
+    pub bit_depth: Option<u8>,
+    pub has_alpha: bool,
+    /// EXIF orientation, 1 (upright) to 8.
+    pub orientation: Option<u32>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens: Option<String>,
+    /// Exposure time as cameras show it, e.g. `"1/250"`.
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<u32>,
+    /// Focal length in millimetres.
+    pub focal_length: Option<f64>,
+    /// Capture time as `YYYY-MM-DDTHH:MM:SS`, plus the UTC offset when recorded.
+    pub captured_at: Option<String>,
+    pub gps: Option<GpsPosition>,
+    /// XMP star rating; -1 marks a rejected photo.
+    pub rating: Option<i32>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub creator: Option<String>,
+    pub keywords: Vec<String>,
+}
+
+/// A run of highlighted text sharing one syntax scope.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct HighlightToken {
+    pub text: String,
+    /// Scope atoms of the innermost scope, e.g. `"string quoted double rust"`; empty for plain text.
+    pub class: String,
+}
+
+/// A run of uniformly formatted text in a document paragraph.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct DocRun {
+    pub text: String,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub underline: bool,
+    /// Hyperlink target: a URL, or `#bookmark` for links within the document.
+    #[serde(default)]
+    pub link: Option<String>,
+    /// Index into the document's `images` for an embedded picture.
+    #[serde(default)]
+    pub image: Option<u32>,
+    /// Id of the footnote this run refers to, listed in the document's `footnotes`.
+    #[serde(default)]
+    pub footnote: Option<u32>,
+}
+
+/// A table cell; its paragraphs are joined with newlines.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DocTableCell {
+    pub text: String,
+    #[serde(default)]
+    pub runs: Vec<DocRun>,
+}
+
 /// A paragraph extracted from a DOCX document.
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct DocParagraph {
     pub text: String,
-    /// Style hint: "heading1", "heading2", "heading3", "listItem", "normal"
+    /// Style hint: "heading1", "heading2", "heading3", "listItem", "table", "normal"
     pub style: String,
+    /// `text` split by formatting; empty for formats without run information.
+    #[serde(default)]
+    pub runs: Vec<DocRun>,
+    /// Rows of cells when `style` is "table"; `text` then holds the cells
+    /// separated by tabs and the rows by newlines.
+    #[serde(default)]
+    pub table: Option<Vec<Vec<DocTableCell>>>,
+}
+
+/// A footnote of a document.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DocFootnote {
+    pub id: u32,
+    pub text: String,
+}
+
+/// A downscaled picture embedded in a document.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DocImage {
+    pub base64: String,
+    pub mime: String,
+    /// Alternative text from the document, if any.
+    pub description: Option<String>,
+}
+
+/// The value of a spreadsheet cell, as stored (a cached result for formulas).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum CellValue {
+    Empty,
+    Text {
+        value: String,
+    },
+    Number {
+        value: f64,
+    },
+    Bool {
+        value: bool,
+    },
+    /// ISO 8601 date and time, e.g. "2024-03-01T00:00:00".
+    Date {
+        value: String,
+    },
+    /// ISO 8601 duration, or seconds formatted as one.
+    Duration {
+        value: String,
+    },
+    /// Error code such as "#DIV/0!".
+    Error {
+        value: String,
+    },
+}
+
+/// Type information for a cell that isn't plain text.
+///
+/// Row and column are 0-based sheet positions.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SheetCell {
+    pub row: u32,
+    pub column: u32,
+    pub value: CellValue,
+    /// Formula text without the leading `=`.
+    pub formula: Option<String>,
+    /// Number format code, e.g. `"0.00%"`; only read from XLSX files.
+    pub number_format: Option<String>,
+}
+
+/// A block of merged cells, with inclusive 0-based sheet positions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct MergedRange {
+    pub start_row: u32,
+    pub start_column: u32,
+    pub end_row: u32,
+    pub end_column: u32,
 }
 
-/// A sheet extracted from an XLSX spreadsheet.
+/// A sheet extracted from a spreadsheet.
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct SpreadsheetSheet {
     pub name: String,
+    /// The first row when it looks like column titles; otherwise empty.
     pub headers: Vec<String>,
     pub rows: Vec<Vec<String>>,
     pub total_rows: u64,
     pub truncated: bool,
+    /// Sheet position of `rows[0][0]`, for paging with `read_sheet_range`.
+    #[serde(default)]
+    pub row_offset: u32,
+    #[serde(default)]
+    pub column_offset: u32,
+    /// Typed values of the cells in `headers` and `rows` that aren't plain text.
+    #[serde(default)]
+    pub cells: Vec<SheetCell>,
+    #[serde(default)]
+    pub merged: Vec<MergedRange>,
+    #[serde(default)]
+    pub hidden: bool,
 }
 
-/// A slide extracted from a PPTX presentation.
+/// A page of rows read from one sheet.
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SheetRange {
+    pub rows: Vec<Vec<String>>,
+    /// Sheet position of `rows[0][0]`.
+    pub row_offset: u32,
+    pub column_offset: u32,
+    pub cells: Vec<SheetCell>,
+    /// Merged ranges overlapping the returned rows.
+    pub merged: Vec<MergedRange>,
+    /// Sheet row after the last used one.
+    pub end_row: u32,
+}
+
+/// A slide extracted from a PPTX presentation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
 pub struct PresentationSlide {
+    /// 1-based position in the presentation's slide order.
     pub number: u32,
     pub title: Option<String>,
     pub texts: Vec<String>,
+    /// Speaker notes.
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Cell text of each table on the slide, by row.
+    #[serde(default)]
+    pub tables: Vec<Vec<Vec<String>>>,
+    /// Pictures on the slide, shrunk to `SLIDE_IMAGE_MAX_SIDE`.
+    #[serde(default)]
+    pub images: Vec<DocImage>,
+}
+
+/// Text extracted from one page of a PDF.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PdfPage {
+    /// 1-based page number.
+    pub number: u32,
+    pub text: String,
+}
+
+/// A bookmark in a PDF's outline.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PdfOutlineItem {
+    pub title: String,
+    /// Nesting depth, starting at 1 for top-level entries.
+    pub level: u32,
+    pub page: u32,
+}
+
+/// One row of a hex dump.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct HexRow {
+    pub offset: u64,
+    /// Space-separated byte values, e.g. `"89 50 4e 47"`.
+    pub hex: String,
+    /// The same bytes as ASCII, with `.` for anything unprintable.
+    pub ascii: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -44,6 +263,12 @@ pub enum FilePreview {
     Text {
         content: String,
         truncated: bool,
+        /// Detected language, e.g. "Rust"; `None` for plain text.
+        #[serde(default)]
+        language: Option<String>,
+        /// `content` as lines of tokens; `None` when no language was detected.
+        #[serde(default)]
+        highlighted: Option<Vec<Vec<HighlightToken>>>,
     },
     Image {
         base64: String,
@@ -52,6 +277,10 @@ pub enum FilePreview {
     Document {
         paragraphs: Vec<DocParagraph>,
         truncated: bool,
+        #[serde(default)]
+        footnotes: Vec<DocFootnote>,
+        #[serde(default)]
+        images: Vec<DocImage>,
     },
     Spreadsheet {
         sheets: Vec<SpreadsheetSheet>,
@@ -59,7 +288,82 @@ pub enum FilePreview {
     Presentation {
         slides: Vec<PresentationSlide>,
     },
+    Pdf {
+        page_count: u32,
+        title: Option<String>,
+        author: Option<String>,
+        producer: Option<String>,
+        pages: Vec<PdfPage>,
+        outline: Vec<PdfOutlineItem>,
+        /// True when pages or page text were cut to the preview limits.
+        truncated: bool,
+    },
+    /// Hex dump of the start of a binary file; `read_bytes_range` pages further.
+    Hex {
+        mime: String,
+        file_size: u64,
+        rows: Vec<HexRow>,
+    },
     Unsupported {
         mime: String,
     },
 }
+
+/// Which part of a text file `read_text_range` returns.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum TextRangeRequest {
+    /// Whole lines starting at or after byte `offset`, up to about `length` bytes.
+    Bytes { offset: u64, length: u32 },
+    /// `count` lines starting at 1-based line `start` ("go to line").
+    Lines { start: u64, count: u32 },
+    /// The last `count` lines ("jump to end").
+    End { count: u32 },
+}
+
+/// A window of whole lines read from a text file.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct TextChunk {
+    pub content: String,
+    /// Byte range of `content` in the file, end exclusive.
+    pub start_offset: u64,
+    pub end_offset: u64,
+    /// 1-based number of the first line, when the line index has reached it.
+    pub first_line: Option<u64>,
+    pub line_count: u32,
+    pub file_size: u64,
+    /// Number of lines in the file, once the line index has scanned all of it.
+    pub total_lines: Option<u64>,
+}
+
+/// A window of raw bytes rendered as hex dump rows.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct HexChunk {
+    /// Offset of the first row, aligned down to a whole row.
+    pub offset: u64,
+    pub rows: Vec<HexRow>,
+    pub file_size: u64,
+}
+
+/// What `search_bytes` looks for.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum BytePattern {
+    /// Hex byte values such as `"de ad be ef"`; `??` matches any byte.
+    Hex { value: String },
+    /// UTF-8 text, ignoring ASCII case unless `case_sensitive` is set.
+    Text {
+        value: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+}
+
+/// Offsets where a byte pattern was found.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ByteSearchResult {
+    pub offsets: Vec<u64>,
+    pub pattern_length: u32,
+    /// Where to resume when the match limit was reached; `None` once the file is exhausted.
+    pub next_offset: Option<u64>,
+}
@@ -0,0 +1,14 @@
+//! Recent changes timeline models.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::FileEntry;
+
+/// Files changed on one day, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct RecentDay {
+    /// Local calendar date, `YYYY-MM-DD`.
+    pub date: String,
+    pub entries: Vec<FileEntry>,
+}
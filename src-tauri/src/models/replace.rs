@@ -0,0 +1,81 @@
+//! Search-and-replace models.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::SearchOptions;
+
+/// Options for replacing search matches across files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct ReplaceOptions {
+    /// Which files to touch and what to find; `search.query` is the pattern.
+    ///
+    /// `search.search_content` must be set, and `search.expression` must not be.
+    pub search: SearchOptions,
+    /// Replacement text. With `regex`, `$1` and `${name}` insert capture groups.
+    pub replacement: String,
+    /// Treat the query as a regular expression instead of literal text.
+    #[serde(default)]
+    pub regex: bool,
+    /// Only replace these matches; every match is replaced when omitted.
+    #[serde(default)]
+    pub selection: Option<Vec<ReplaceSelection>>,
+    /// Compute the changes without writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Matches chosen in one file, by their `index` from a dry run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct ReplaceSelection {
+    pub path: String,
+    pub indices: Vec<u32>,
+    /// `version` from the dry run; the file is skipped if it changed since.
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// A single match and what it becomes.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReplaceMatch {
+    pub index: u32,
+    pub line_number: u64,
+    pub line_content: String,
+    pub matched: String,
+    pub replacement: String,
+}
+
+/// Changes made (or planned) in one file.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FileReplacement {
+    pub path: String,
+    /// Fingerprint of the content the matches were found in.
+    pub version: String,
+    pub matches: Vec<ReplaceMatch>,
+    /// Unified diff of the selected replacements.
+    pub diff: String,
+    /// Number of matches replaced (or to be replaced in a dry run).
+    pub replaced: u32,
+    /// Set when the file could not be written; nothing was changed in it.
+    pub error: Option<String>,
+}
+
+/// Outcome of `replace_in_files`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReplaceReport {
+    pub files: Vec<FileReplacement>,
+    pub total_replaced: u32,
+    /// Pass to `undo_replace` to restore the original contents; `None` for dry runs.
+    pub undo_id: Option<String>,
+    /// More files matched than `search.max_results`; only the first ones by path
+    /// were included.
+    pub truncated: bool,
+}
+
+/// Outcome of `undo_replace`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct ReplaceUndoReport {
+    pub restored: Vec<String>,
+    /// Files left alone because they were modified after the replacement.
+    pub conflicts: Vec<String>,
+}
@@ -0,0 +1,24 @@
+//! Saved search ("smart folder") models.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::{FileEntry, SearchOptions};
+
+/// A named search that can be opened like a directory.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SavedSearch {
+    pub id: String,
+    pub name: String,
+    pub options: SearchOptions,
+    /// Unix timestamps in seconds.
+    pub created: i64,
+    pub updated: i64,
+}
+
+/// Fresh listing of a watched saved search, emitted as `saved-search-updated`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SavedSearchUpdate {
+    pub id: String,
+    pub entries: Vec<FileEntry>,
+}
@@ -13,16 +13,37 @@ pub struct SearchResult {
 }
 
 /// A content match within a file (line-based).
+///
+/// For documents, lines are counted over the extracted text and `location`
+/// says where in the document the line came from.
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ContentMatch {
     pub line_number: u64,
     pub line_content: String,
     pub match_start: u64,
     pub match_end: u64,
+    #[serde(default)]
+    pub location: Option<MatchLocation>,
+}
+
+/// Position of a match inside a structured document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum MatchLocation {
+    /// 1-based paragraph of a word processing document.
+    Paragraph { index: u32 },
+    /// Footnote of a word processing document, by its id in the document.
+    Footnote { id: u32 },
+    /// Spreadsheet cell, e.g. `sheet: "Budget", cell: "B4"`.
+    Cell { sheet: String, cell: String },
+    /// 1-based slide of a presentation.
+    Slide { number: u32 },
+    /// 1-based page of a PDF.
+    Page { number: u32 },
 }
 
 /// Options for file search operations.
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
 pub struct SearchOptions {
     pub query: String,
     pub search_path: String,
@@ -30,12 +51,174 @@ pub struct SearchOptions {
     pub case_sensitive: bool,
     pub max_results: Option<u32>,
     pub file_extensions: Option<Vec<String>>,
+    /// Skip entries ignored by `.gitignore`, `.ignore` and global git excludes.
+    #[serde(default)]
+    pub respect_gitignore: bool,
+    /// Glob patterns (gitignore syntax, relative to `search_path`) to leave out of the walk.
+    #[serde(default)]
+    pub exclude_globs: Option<Vec<String>>,
+    /// Maximum directory depth; defaults to `MAX_SEARCH_DEPTH`.
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    /// Descend into symlinked directories. Symlink loops are detected and skipped.
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Metadata predicates an entry must satisfy in addition to the query.
+    #[serde(default)]
+    pub filters: Option<SearchFilters>,
+    /// Parsed query expression. When set, it replaces `query` for matching.
+    #[serde(default)]
+    pub expression: Option<QueryExpr>,
+}
+
+/// Options for fuzzy filename search.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct FuzzySearchOptions {
+    pub query: String,
+    pub search_path: String,
+    pub max_results: Option<u32>,
+    /// Recently used paths, most recent first; matches among them rank higher.
+    #[serde(default)]
+    pub recent_paths: Option<Vec<String>>,
+    /// Skip entries ignored by `.gitignore`, `.ignore` and global git excludes.
+    #[serde(default)]
+    pub respect_gitignore: bool,
+}
+
+/// A ranked fuzzy filename match.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FuzzyMatch {
+    pub path: String,
+    pub name: String,
+    /// Path relative to the search root; `indices` point into it.
+    pub relative_path: String,
+    pub is_dir: bool,
+    pub score: i64,
+    /// Sorted UTF-16 offsets of matched characters in `relative_path`.
+    pub indices: Vec<u32>,
+}
+
+/// A boolean search expression, usually produced by `parse_search_query`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum QueryExpr {
+    And {
+        items: Vec<QueryExpr>,
+    },
+    Or {
+        items: Vec<QueryExpr>,
+    },
+    Not {
+        item: Box<QueryExpr>,
+    },
+    /// Name contains `value`, or file content does when `search_content` is set.
+    Text {
+        value: String,
+    },
+    /// File content contains `value`, regardless of `search_content`.
+    Content {
+        value: String,
+    },
+    /// File name matches a glob pattern.
+    Name {
+        pattern: String,
+    },
+    /// Path relative to the search root contains `pattern`, or matches it when it is a glob.
+    Path {
+        pattern: String,
+    },
+    /// File extension equals `value` (case-insensitive, without the dot).
+    Extension {
+        value: String,
+    },
+    Filter {
+        filters: SearchFilters,
+    },
+}
+
+/// A query syntax error with the span to underline.
+///
+/// Offsets are UTF-16 code units, matching JavaScript string indices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct QueryError {
+    pub message: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Kind of filesystem entry for search filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// A point in time, either absolute or relative to when the search starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum TimeBound {
+    /// Unix timestamp in seconds.
+    Absolute { timestamp: i64 },
+    /// Seconds before the search starts, e.g. `604800` for "7 days ago".
+    Relative { seconds: u64 },
+}
+
+/// An inclusive time range; either side may be open.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct TimeRange {
+    pub after: Option<TimeBound>,
+    pub before: Option<TimeBound>,
+}
+
+/// Metadata predicates for search. Every set field must match (logical AND).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct SearchFilters {
+    /// Minimum file size in bytes (inclusive). Directories have size 0.
+    #[serde(default)]
+    pub min_size: Option<u64>,
+    /// Maximum file size in bytes (inclusive).
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    #[serde(default)]
+    pub modified: Option<TimeRange>,
+    #[serde(default)]
+    pub created: Option<TimeRange>,
+    #[serde(default)]
+    pub kind: Option<EntryKind>,
+    /// Zero-length files and directories without children.
+    #[serde(default)]
+    pub empty: Option<bool>,
+    #[serde(default)]
+    pub hidden: Option<bool>,
+    /// Executable files: any execute bit on Unix, executable extensions on Windows.
+    #[serde(default)]
+    pub executable: Option<bool>,
+    /// Owner user name or numeric uid (Unix only).
+    #[serde(default)]
+    pub owner: Option<String>,
 }
 
 /// Progress information for search operations.
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct SearchProgress {
+    pub search_id: String,
     pub scanned: usize,
     pub found: usize,
     pub current_path: String,
 }
+
+/// A batch of streamed search results, tagged with the search that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SearchBatch {
+    pub search_id: String,
+    pub results: Vec<SearchResult>,
+}
+
+/// Final notification for a streamed search.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SearchComplete {
+    pub search_id: String,
+    pub found: usize,
+    pub cancelled: bool,
+}
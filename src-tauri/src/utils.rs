@@ -1,7 +1,10 @@
 //! Utility functions for file operations.
 
+use std::ffi::OsStr;
 use std::fs;
-use std::path::{Component, Path};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::SystemTime;
 
 use crate::error::{FileManagerError, Result};
@@ -64,6 +67,125 @@ fn symlink_file(target: &Path, dst: &Path) -> Result<()> {
     symlink_file(target, dst).map_err(|e| FileManagerError::CopyError(format!("symlink: {e}")))
 }
 
+/// Replaces the contents of an existing regular file atomically.
+///
+/// Symlinks are followed and their target is rewritten, so a link stays a
+/// link. The new contents go to a temporary sibling with the original owner
+/// and permissions, which is synced and renamed over the file, so readers see
+/// either the old or the new contents. Files with several hard links, or whose
+/// owner can't be restored, are rewritten in place instead.
+///
+/// Fails with `NotFound` for a missing file and `InvalidInput` for anything
+/// that isn't a regular file.
+pub fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let target = fs::canonicalize(path)?;
+    let metadata = fs::metadata(&target)?;
+    if !metadata.is_file() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a regular file", path.display()),
+        ));
+    }
+    replace_via_temp(&target, data, Some(&metadata))
+}
+
+/// Like [`write_atomic`], but creates the file when it doesn't exist yet.
+pub fn write_atomic_or_create(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => replace_via_temp(path, data, None),
+        _ => write_atomic(path, data),
+    }
+}
+
+/// Writes `data` to a temporary sibling of `path` and renames it over `path`,
+/// carrying over the owner and permissions of `original`.
+fn replace_via_temp(
+    path: &Path,
+    data: &[u8],
+    original: Option<&fs::Metadata>,
+) -> std::io::Result<()> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "path has no parent directory",
+        ));
+    };
+    let (temp, mut file) = create_temp(dir, name)?;
+
+    let result = (|| {
+        if let Some(original) = original {
+            if !keeps_identity(&temp, original) {
+                return Ok(false);
+            }
+            file.set_permissions(original.permissions())?;
+        }
+        file.write_all(data)?;
+        file.sync_all()?;
+        fs::rename(&temp, path)?;
+        Ok(true)
+    })();
+
+    if !matches!(result, Ok(true)) {
+        let _ = fs::remove_file(&temp);
+    }
+    match result {
+        Ok(false) => write_in_place(path, data),
+        result => result.map(|_| ()),
+    }
+}
+
+/// Creates a temporary file for `name` in `dir` under a name no other writer uses.
+///
+/// The file is created exclusively, so an existing file or symlink of the same
+/// name is never opened.
+fn create_temp(dir: &Path, name: &OsStr) -> std::io::Result<(PathBuf, fs::File)> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    loop {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        let temp = dir.join(format!(
+            ".{}.{}-{}-{nanos:08x}.tmp",
+            name.to_string_lossy(),
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)
+        {
+            Ok(file) => return Ok((temp, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether renaming `temp` over the original file keeps its identity: the
+/// file has no other hard links and `temp` could be given its owner.
+#[cfg(unix)]
+fn keeps_identity(temp: &Path, original: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    original.nlink() <= 1
+        && std::os::unix::fs::chown(temp, Some(original.uid()), Some(original.gid())).is_ok()
+}
+
+#[cfg(not(unix))]
+fn keeps_identity(_temp: &Path, _original: &fs::Metadata) -> bool {
+    true
+}
+
+fn write_in_place(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
 /// Converts `SystemTime` to Unix timestamp (seconds since epoch).
 #[inline]
 pub fn system_time_to_timestamp(time: SystemTime) -> Option<i64> {
@@ -172,4 +294,49 @@ mod tests {
         assert!(validate_absolute_path(path).is_ok());
         assert!(validate_deletable_path(path).is_ok());
     }
+
+    #[test]
+    fn write_atomic_only_replaces_existing_regular_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.txt");
+        assert!(write_atomic(&missing, b"x").is_err());
+        assert!(!missing.exists());
+        assert!(write_atomic(dir.path(), b"x").is_err());
+
+        write_atomic_or_create(&missing, b"created").unwrap();
+        write_atomic(&missing, b"replaced").unwrap();
+        assert_eq!(fs::read(&missing).unwrap(), b"replaced");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn write_atomic_keeps_hard_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        let link = dir.path().join("link.txt");
+        fs::write(&file, "old").unwrap();
+        fs::hard_link(&file, &link).unwrap();
+
+        write_atomic(&link, b"new").unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"new");
+    }
+
+    #[test]
+    fn concurrent_atomic_writes_use_separate_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "old").unwrap();
+
+        let contents: Vec<Vec<u8>> = (0..8u8).map(|i| vec![b'a' + i; 64 * 1024]).collect();
+        std::thread::scope(|scope| {
+            for data in &contents {
+                let file = &file;
+                scope.spawn(move || write_atomic(file, data).unwrap());
+            }
+        });
+
+        // One writer's output wins whole, and no temporary file is left behind.
+        assert!(contents.contains(&fs::read(&file).unwrap()));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
 }
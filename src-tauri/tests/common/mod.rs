@@ -1,4 +1,8 @@
+// Each integration test compiles this module and uses only some of its helpers.
+#![allow(dead_code)]
+
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
@@ -27,6 +31,100 @@ pub fn child_path(root: &str, name: &str) -> String {
     PathBuf::from(root).join(name).to_string_lossy().to_string()
 }
 
+/// A zip archive holding `files` as `(name, contents)` pairs.
+pub fn zip_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, data) in files {
+        zip.start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+/// Writes a zip archive of `files` to `path`.
+pub fn write_zip(path: impl AsRef<Path>, files: &[(&str, &[u8])]) {
+    fs::write(path, zip_bytes(files)).expect("write zip");
+}
+
+/// A minimal DOCX with one plain paragraph per entry of `paragraphs`.
+pub fn docx_bytes(paragraphs: &[&str]) -> Vec<u8> {
+    let body: String = paragraphs
+        .iter()
+        .map(|p| format!("<w:p><w:r><w:t>{p}</w:t></w:r></w:p>"))
+        .collect();
+    let xml = format!(
+        r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{body}</w:body></w:document>"#
+    );
+    zip_bytes(&[("word/document.xml", xml.as_bytes())])
+}
+
+/// A PDF with one page of Courier text per entry of `pages`.
+///
+/// Callers can add document info or an outline before saving it.
+pub fn pdf_document(pages: &[&str]) -> lopdf::Document {
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Document, Object, Stream};
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+
+    let kids: Vec<Object> = pages
+        .iter()
+        .map(|text| {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![72.into(), 720.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            })
+            .into()
+        })
+        .collect();
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc
+}
+
+/// The bytes of [`pdf_document`].
+pub fn pdf_bytes(pages: &[&str]) -> Vec<u8> {
+    let mut out = Vec::new();
+    pdf_document(pages).save_to(&mut out).unwrap();
+    out
+}
+
 #[cfg(test)]
 mod smoke {
     use super::*;
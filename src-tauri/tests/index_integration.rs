@@ -0,0 +1,369 @@
+mod common;
+
+use common::{create_fixture_tree, docx_bytes, setup_temp_workspace};
+use std::fs;
+use std::time::{Duration, Instant};
+
+use file_manager_lib::commands::index::{
+    add_index_root_sync, get_index_status_sync, rebuild_index_sync, remove_index_root_sync,
+    search_content_index_sync, search_index_sync, IndexState,
+};
+use file_manager_lib::models::{
+    ContentQuery, EntryKind, IndexQuery, IndexRootState, SearchFilters,
+};
+
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn name_query(query: &str) -> IndexQuery {
+    IndexQuery {
+        query: query.to_string(),
+        ..Default::default()
+    }
+}
+
+fn found_names(state: &IndexState, query: &IndexQuery) -> Vec<String> {
+    let mut names: Vec<String> = search_index_sync(query, state)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    names.sort();
+    names
+}
+
+fn content_query(query: &str) -> ContentQuery {
+    ContentQuery {
+        query: query.to_string(),
+        ..Default::default()
+    }
+}
+
+fn content_hits(state: &IndexState, query: &str) -> Vec<String> {
+    let mut names: Vec<String> = search_content_index_sync(&content_query(query), state)
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.name)
+        .collect();
+    names.sort();
+    names
+}
+
+/// Polls until the content index reflects a change.
+fn wait_for_content(state: &IndexState, query: &str, expected: usize) -> bool {
+    let deadline = Instant::now() + IDLE_TIMEOUT;
+    while Instant::now() < deadline {
+        state.wait_until_idle(IDLE_TIMEOUT);
+        if content_hits(state, query).len() == expected {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+/// Polls until the watcher has applied a change.
+fn wait_for(state: &IndexState, query: &IndexQuery, expected: usize) -> bool {
+    let deadline = Instant::now() + IDLE_TIMEOUT;
+    while Instant::now() < deadline {
+        state.wait_until_idle(IDLE_TIMEOUT);
+        if search_index_sync(query, state).unwrap().len() == expected {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+#[test]
+fn index_scans_root_and_answers_name_queries() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let (db_dir, _) = setup_temp_workspace();
+    let state = IndexState::open(&db_dir.path().join("index.sqlite3")).unwrap();
+
+    add_index_root_sync(&root, &state).unwrap();
+    assert!(state.wait_until_idle(IDLE_TIMEOUT));
+
+    assert_eq!(
+        found_names(&state, &name_query("NESTED")),
+        vec!["nested.txt", "nested.txt"]
+    );
+
+    // Exact names rank before partial matches, shallow paths first.
+    let ranked = search_index_sync(&name_query("subdir"), &state).unwrap();
+    assert_eq!(ranked[0].name, "subdir");
+    assert!(ranked[0].is_dir);
+
+    let status = get_index_status_sync(&state).unwrap();
+    assert_eq!(status.roots.len(), 1);
+    assert_eq!(status.roots[0].state, IndexRootState::Ready);
+    assert_eq!(status.roots[0].entries, 5);
+    assert!(status.roots[0].last_scan.is_some());
+    assert_eq!(status.total_entries, 5);
+}
+
+#[test]
+fn index_applies_metadata_filters_and_root_scopes() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    fs::write(dir.path().join("big.bin"), vec![0u8; 4096]).unwrap();
+    let state = IndexState::in_memory().unwrap();
+
+    add_index_root_sync(&root, &state).unwrap();
+    assert!(state.wait_until_idle(IDLE_TIMEOUT));
+
+    let dirs = IndexQuery {
+        filters: Some(SearchFilters {
+            kind: Some(EntryKind::Directory),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(found_names(&state, &dirs), vec!["empty-dir", "subdir"]);
+
+    let large = IndexQuery {
+        filters: Some(SearchFilters {
+            min_size: Some(1024),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(found_names(&state, &large), vec!["big.bin"]);
+
+    let empty = IndexQuery {
+        filters: Some(SearchFilters {
+            empty: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(found_names(&state, &empty), vec!["empty-dir"]);
+
+    let scoped = IndexQuery {
+        roots: Some(vec![dir
+            .path()
+            .join("subdir")
+            .to_string_lossy()
+            .to_string()]),
+        ..name_query("nested")
+    };
+    assert_eq!(found_names(&state, &scoped), vec!["nested.txt"]);
+}
+
+#[test]
+fn index_matches_substrings_and_scopes_roots_by_exact_case() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    fs::create_dir_all(dir.path().join("Data")).unwrap();
+    fs::write(dir.path().join("Data").join("Quarterly-Report.pdf"), "").unwrap();
+    let state = IndexState::in_memory().unwrap();
+
+    add_index_root_sync(&root, &state).unwrap();
+    assert!(state.wait_until_idle(IDLE_TIMEOUT));
+
+    // Long needles go through the trigram index, short ones scan names.
+    assert_eq!(
+        found_names(&state, &name_query("ly-rep")),
+        vec!["Quarterly-Report.pdf"]
+    );
+    assert_eq!(
+        found_names(&state, &name_query("\"report\"")),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        found_names(&state, &name_query("df")),
+        vec!["Quarterly-Report.pdf"]
+    );
+
+    // Paths compare byte for byte, so `data` doesn't scope to `Data`.
+    let scoped = |root: &str| IndexQuery {
+        roots: Some(vec![dir.path().join(root).to_string_lossy().to_string()]),
+        ..name_query("report")
+    };
+    assert_eq!(
+        found_names(&state, &scoped("Data")),
+        vec!["Quarterly-Report.pdf"]
+    );
+    assert!(found_names(&state, &scoped("data")).is_empty());
+    assert!(found_names(&state, &scoped("Dat")).is_empty());
+}
+
+#[test]
+fn index_follows_filesystem_changes() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let state = IndexState::in_memory().unwrap();
+
+    add_index_root_sync(&root, &state).unwrap();
+    assert!(state.wait_until_idle(IDLE_TIMEOUT));
+    assert!(get_index_status_sync(&state).unwrap().roots[0].watching);
+
+    fs::write(dir.path().join("fresh.log"), "new").unwrap();
+    assert!(wait_for(&state, &name_query("fresh"), 1));
+
+    // A directory moved in brings its contents along.
+    let (outside, _) = setup_temp_workspace();
+    let moved = outside.path().join("bundle");
+    fs::create_dir_all(&moved).unwrap();
+    fs::write(moved.join("inner.log"), "x").unwrap();
+    fs::rename(&moved, dir.path().join("bundle")).unwrap();
+    assert!(wait_for(&state, &name_query("inner"), 1));
+
+    fs::remove_dir_all(dir.path().join("subdir")).unwrap();
+    assert!(wait_for(&state, &name_query("nested"), 1));
+}
+
+#[test]
+fn index_roots_persist_and_can_be_removed_or_rebuilt() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let (db_dir, _) = setup_temp_workspace();
+    let db_path = db_dir.path().join("index.sqlite3");
+
+    {
+        let state = IndexState::open(&db_path).unwrap();
+        add_index_root_sync(&root, &state).unwrap();
+        assert!(state.wait_until_idle(IDLE_TIMEOUT));
+    }
+
+    // Entries survive a restart and the root is reconciled on open.
+    fs::write(dir.path().join("offline.txt"), "added while closed").unwrap();
+    let state = IndexState::open(&db_path).unwrap();
+    assert_eq!(
+        found_names(&state, &name_query("readme")),
+        vec!["readme.txt"]
+    );
+    assert!(state.wait_until_idle(IDLE_TIMEOUT));
+    assert_eq!(
+        found_names(&state, &name_query("offline")),
+        vec!["offline.txt"]
+    );
+
+    rebuild_index_sync(Some(&root), &state).unwrap();
+    assert!(state.wait_until_idle(IDLE_TIMEOUT));
+    assert_eq!(get_index_status_sync(&state).unwrap().total_entries, 6);
+    assert!(rebuild_index_sync(Some("/not/indexed"), &state).is_err());
+
+    assert!(remove_index_root_sync(&root, &state).unwrap());
+    assert!(!remove_index_root_sync(&root, &state).unwrap());
+    let status = get_index_status_sync(&state).unwrap();
+    assert!(status.roots.is_empty());
+    assert_eq!(status.total_entries, 0);
+}
+
+#[test]
+fn index_rejects_invalid_and_overlapping_roots() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let state = IndexState::in_memory().unwrap();
+
+    assert!(add_index_root_sync("relative/path", &state).is_err());
+    assert!(add_index_root_sync(&format!("{root}/readme.txt"), &state).is_err());
+
+    add_index_root_sync(&root, &state).unwrap();
+    // Adding the same root again is a no-op.
+    add_index_root_sync(&format!("{root}/"), &state).unwrap();
+    assert!(add_index_root_sync(&format!("{root}/subdir"), &state).is_err());
+    assert_eq!(get_index_status_sync(&state).unwrap().roots.len(), 1);
+}
+
+#[test]
+fn content_index_ranks_text_and_office_documents() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    fs::write(
+        dir.path().join("notes.md"),
+        "Meeting notes: the quarterly budget was approved.",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("report.docx"),
+        docx_bytes(&["Quarterly budget forecast"]),
+    )
+    .unwrap();
+    let state = IndexState::in_memory().unwrap();
+
+    add_index_root_sync(&root, &state).unwrap();
+    assert!(wait_for_content(&state, "budget", 2));
+
+    assert_eq!(
+        content_hits(&state, "quarterly budget"),
+        vec!["notes.md", "report.docx"]
+    );
+
+    let hits = search_content_index_sync(&content_query("approved"), &state).unwrap();
+    assert_eq!(hits.len(), 1);
+    let hit = &hits[0];
+    assert!(hit.path.ends_with("notes.md"));
+    assert!(hit.score > 0.0);
+    assert_eq!(hit.highlights.len(), 1);
+    let range = &hit.highlights[0];
+    let utf16: Vec<u16> = hit.snippet.encode_utf16().collect();
+    let highlighted = String::from_utf16(&utf16[range.start as usize..range.end as usize]);
+    assert_eq!(highlighted.unwrap(), "approved");
+
+    // Root scoping and empty queries.
+    let scoped = ContentQuery {
+        roots: Some(vec![dir
+            .path()
+            .join("subdir")
+            .to_string_lossy()
+            .to_string()]),
+        ..content_query("nested")
+    };
+    let scoped: Vec<String> = search_content_index_sync(&scoped, &state)
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.path)
+        .collect();
+    assert_eq!(scoped.len(), 1);
+    assert!(scoped[0].contains("subdir"));
+    assert!(content_hits(&state, "   ").is_empty());
+
+    // Every regular file is recorded, with or without text.
+    assert_eq!(get_index_status_sync(&state).unwrap().content_documents, 5);
+}
+
+#[test]
+fn content_index_reads_whole_documents() {
+    let (dir, root) = setup_temp_workspace();
+    // Longer than a document preview shows.
+    let mut paragraphs = vec!["filler"; 600];
+    paragraphs.push("Closing appendix");
+    fs::write(dir.path().join("long.docx"), docx_bytes(&paragraphs)).unwrap();
+    let state = IndexState::in_memory().unwrap();
+
+    add_index_root_sync(&root, &state).unwrap();
+    assert!(wait_for_content(&state, "appendix", 1));
+    assert_eq!(content_hits(&state, "appendix"), vec!["long.docx"]);
+}
+
+#[test]
+fn content_index_follows_changes_and_skips_binary_files() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let mut binary = b"zebra ".to_vec();
+    binary.push(0);
+    fs::write(dir.path().join("blob.bin"), binary).unwrap();
+    let state = IndexState::in_memory().unwrap();
+
+    add_index_root_sync(&root, &state).unwrap();
+    assert!(wait_for_content(&state, "fixture", 1));
+    assert!(content_hits(&state, "zebra").is_empty());
+
+    fs::write(dir.path().join("readme.txt"), "hello giraffe, goodbye").unwrap();
+    assert!(wait_for_content(&state, "giraffe", 1));
+    assert!(content_hits(&state, "fixture").is_empty());
+
+    fs::write(dir.path().join("added.txt"), "a giraffe appears").unwrap();
+    assert!(wait_for_content(&state, "giraffe", 2));
+
+    fs::remove_dir_all(dir.path().join("subdir")).unwrap();
+    assert!(wait_for_content(&state, "nested", 1));
+    fs::remove_file(dir.path().join("added.txt")).unwrap();
+    assert!(wait_for_content(&state, "giraffe", 1));
+
+    assert!(remove_index_root_sync(&root, &state).unwrap());
+    assert!(content_hits(&state, "giraffe").is_empty());
+    assert_eq!(get_index_status_sync(&state).unwrap().content_documents, 0);
+}
@@ -1,9 +1,16 @@
 mod common;
 
-use common::{child_path, create_fixture_tree, setup_temp_workspace};
+use base64::Engine;
+use common::{child_path, create_fixture_tree, pdf_document, setup_temp_workspace, write_zip};
 use file_manager_lib::commands::file_ops::get_file_content_sync;
-use file_manager_lib::commands::preview::{get_file_preview_sync, get_thumbnail_sync};
-use file_manager_lib::models::FilePreview;
+use file_manager_lib::commands::preview::{
+    get_file_preview_sync, get_image_metadata_sync, get_image_preview_sync, get_thumbnail_sync,
+    read_bytes_range_sync, read_sheet_range_sync, read_text_range_sync, search_bytes_sync,
+    SheetCacheState, TextIndexState,
+};
+use file_manager_lib::models::{
+    BytePattern, CellValue, FilePreview, MergedRange, TextRangeRequest,
+};
 
 #[test]
 fn get_file_preview_returns_text_metadata() {
@@ -12,15 +19,208 @@ fn get_file_preview_returns_text_metadata() {
     let file = child_path(&root, "readme.txt");
     let preview = get_file_preview_sync(&file).expect("preview");
     match preview {
-        FilePreview::Text { content, truncated } => {
+        FilePreview::Text {
+            content,
+            truncated,
+            language,
+            highlighted,
+        } => {
             assert!(content.contains("hello fixture"));
             assert!(!truncated);
+            assert!(language.is_none());
+            assert!(highlighted.is_none());
         }
         FilePreview::Unsupported { .. } => {}
         other => panic!("unexpected preview variant: {other:?}"),
     }
 }
 
+#[test]
+fn get_file_preview_highlights_source_files() {
+    let (_dir, root) = setup_temp_workspace();
+    let file = child_path(&root, "snippet.txt");
+    std::fs::write(&file, "// -*- mode: python -*-\nimport os\n").unwrap();
+    let long = child_path(&root, "long.rb");
+    std::fs::write(&long, "x = 1\n".repeat(5_000)).unwrap();
+
+    let FilePreview::Text {
+        language,
+        highlighted,
+        ..
+    } = get_file_preview_sync(&file).expect("preview")
+    else {
+        panic!("expected a text preview");
+    };
+    assert_eq!(language.as_deref(), Some("Python"));
+    let lines = highlighted.expect("tokens");
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1]
+        .iter()
+        .any(|t| t.text == "import" && !t.class.is_empty()));
+
+    let FilePreview::Text {
+        content,
+        truncated,
+        highlighted,
+        ..
+    } = get_file_preview_sync(&long).expect("preview")
+    else {
+        panic!("expected a text preview");
+    };
+    assert!(truncated);
+    let highlighted: String = highlighted
+        .expect("tokens")
+        .iter()
+        .flat_map(|line| line.iter().map(|t| t.text.as_str()))
+        .collect();
+    assert_eq!(highlighted, content.replace('\n', ""));
+}
+
+#[test]
+fn get_file_preview_sniffs_text_and_binary_content() {
+    let (_dir, root) = setup_temp_workspace();
+    let write = |name: &str, data: &[u8]| {
+        let path = child_path(&root, name);
+        std::fs::write(&path, data).unwrap();
+        path
+    };
+
+    for (name, data) in [
+        ("Dockerfile", &b"FROM alpine\n"[..]),
+        (".env", b"TOKEN=abc\n"),
+        ("schema.sql", b"SELECT 1;\n"),
+        ("notes.unknownext", "caf\u{e9} au lait\n".as_bytes()),
+        ("empty", b""),
+    ] {
+        let preview = get_file_preview_sync(&write(name, data)).expect("preview");
+        assert!(
+            matches!(preview, FilePreview::Text { .. }),
+            "{name}: {preview:?}"
+        );
+    }
+
+    let FilePreview::Text { language, .. } =
+        get_file_preview_sync(&write("deploy", b"#!/usr/bin/env ruby\nputs 'hi'\n"))
+            .expect("preview")
+    else {
+        panic!("expected a text preview");
+    };
+    assert_eq!(language.as_deref(), Some("Ruby"));
+
+    // Magic bytes name the real type, whatever the extension says.
+    let png = write("image.dat", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+    let FilePreview::Hex { mime, .. } = get_file_preview_sync(&png).expect("preview") else {
+        panic!("expected binary");
+    };
+    assert_eq!(mime, "image/png");
+
+    let blob = write("blob", &[0u8, 159, 146, 150, 1, 2, 3]);
+    let FilePreview::Hex {
+        mime,
+        file_size,
+        rows,
+    } = get_file_preview_sync(&blob).expect("preview")
+    else {
+        panic!("expected binary");
+    };
+    assert_eq!(mime, "application/octet-stream");
+    assert_eq!(file_size, 7);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].hex, "00 9f 92 96 01 02 03");
+
+    // A text name outweighs the content heuristic; undecodable bytes are replaced.
+    let binary_log = write("binary.log", &[0u8, 1, 2, 3, 0xff]);
+    let FilePreview::Text { content, .. } = get_file_preview_sync(&binary_log).expect("preview")
+    else {
+        panic!("expected a text preview");
+    };
+    assert!(content.contains('\u{fffd}'));
+}
+
+#[test]
+fn read_text_range_pages_by_line_offset_and_end() {
+    let (_dir, root) = setup_temp_workspace();
+    let file = child_path(&root, "app.log");
+    let body: String = (1..=2_500).map(|n| format!("line {n}\n")).collect();
+    std::fs::write(&file, &body).unwrap();
+    let state = TextIndexState::new();
+    let read = |range: TextRangeRequest| read_text_range_sync(&file, &range, &state).unwrap();
+
+    // Nothing is indexed yet, so the tail has no line numbers.
+    let tail = read(TextRangeRequest::End { count: 2 });
+    assert_eq!(tail.content, "line 2499\nline 2500\n");
+    assert_eq!(tail.end_offset, body.len() as u64);
+    assert_eq!(tail.first_line, None);
+    assert_eq!(tail.total_lines, None);
+
+    let head = read(TextRangeRequest::Lines { start: 1, count: 3 });
+    assert_eq!(head.content, "line 1\nline 2\nline 3\n");
+    assert_eq!((head.start_offset, head.first_line), (0, Some(1)));
+
+    // Crosses a checkpoint of the sparse index.
+    let middle = read(TextRangeRequest::Lines {
+        start: 2_001,
+        count: 2,
+    });
+    assert_eq!(middle.content, "line 2001\nline 2002\n");
+    assert_eq!(middle.line_count, 2);
+    assert_eq!(middle.total_lines, Some(2_500));
+
+    let tail = read(TextRangeRequest::End { count: 2 });
+    assert_eq!(tail.first_line, Some(2_499));
+
+    // Byte offsets snap forward to the next whole line.
+    let page = read(TextRangeRequest::Bytes {
+        offset: 3,
+        length: 10,
+    });
+    assert_eq!(page.content, "line 2\nline 3\n");
+    assert_eq!((page.start_offset, page.first_line), (7, Some(2)));
+
+    let past = read(TextRangeRequest::Lines {
+        start: 2_501,
+        count: 5,
+    });
+    assert!(past.content.is_empty());
+    assert_eq!(past.first_line, None);
+
+    // A growing log keeps its index; a truncated one is reindexed.
+    let mut f = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&file)
+        .unwrap();
+    std::io::Write::write_all(&mut f, b"line 2501").unwrap();
+    drop(f);
+    let last = read(TextRangeRequest::Lines {
+        start: 2_501,
+        count: 5,
+    });
+    assert_eq!(last.content, "line 2501");
+    assert_eq!(last.first_line, Some(2_501));
+
+    std::fs::write(&file, "a\nb\n").unwrap();
+    let short = read(TextRangeRequest::Lines { start: 2, count: 5 });
+    assert_eq!(short.content, "b\n");
+    assert_eq!(short.total_lines, Some(2));
+
+    // A rotated log at least as long as the old one is reindexed too.
+    let rotated = child_path(&root, "app.log.new");
+    std::fs::write(&rotated, "x\n".repeat(10)).unwrap();
+    std::fs::rename(&rotated, &file).unwrap();
+    let renewed = read(TextRangeRequest::Lines { start: 9, count: 5 });
+    assert_eq!(renewed.content, "x\nx\n");
+    assert_eq!(renewed.total_lines, Some(10));
+
+    // So is a log rewritten in place.
+    std::fs::write(&file, "y\n".repeat(10) + "tail\n").unwrap();
+    let rewritten = read(TextRangeRequest::Lines {
+        start: 11,
+        count: 1,
+    });
+    assert_eq!(rewritten.content, "tail\n");
+    assert_eq!(rewritten.total_lines, Some(11));
+}
+
 #[test]
 fn get_thumbnail_gracefully_skips_non_image() {
     let (dir, root) = setup_temp_workspace();
@@ -46,3 +246,892 @@ fn get_file_content_rejects_oversized_file() {
     drop(f);
     assert!(get_file_content_sync(&file).is_err());
 }
+
+#[test]
+fn read_bytes_range_pages_and_search_bytes_finds_patterns() {
+    let (_dir, root) = setup_temp_workspace();
+    let file = child_path(&root, "data.bin");
+    // The marker straddles the 1 MB boundary where the search reads its next chunk.
+    let mut data = vec![0u8; 2 * 1024 * 1024];
+    let marker_at = 1024 * 1024 - 2;
+    data[marker_at..marker_at + 4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+    data[100..105].copy_from_slice(b"Hello");
+    data[5000..5005].copy_from_slice(b"HELLO");
+    std::fs::write(&file, &data).unwrap();
+
+    let chunk = read_bytes_range_sync(&file, 100, 32).expect("range");
+    assert_eq!(chunk.offset, 96);
+    assert_eq!(chunk.file_size, data.len() as u64);
+    assert_eq!(chunk.rows.len(), 2);
+    assert_eq!(chunk.rows[0].ascii, "....Hello.......");
+
+    let end = read_bytes_range_sync(&file, u64::MAX, 64).expect("range");
+    assert!(end.rows.is_empty());
+    assert!(read_bytes_range_sync("relative.bin", 0, 16).is_err());
+
+    let hex = BytePattern::Hex {
+        value: "de ad ?? ef".to_string(),
+    };
+    let found = search_bytes_sync(&file, &hex, None, None).expect("search");
+    assert_eq!(found.offsets, vec![marker_at as u64]);
+    assert_eq!(found.pattern_length, 4);
+    assert_eq!(found.next_offset, None);
+
+    let text = |case_sensitive| BytePattern::Text {
+        value: "hello".to_string(),
+        case_sensitive,
+    };
+    let found = search_bytes_sync(&file, &text(false), None, Some(1)).expect("search");
+    assert_eq!(found.offsets, vec![100]);
+    assert_eq!(found.next_offset, Some(5000));
+    let rest = search_bytes_sync(&file, &text(false), found.next_offset, None).expect("search");
+    assert_eq!(rest.offsets, vec![5000]);
+
+    let found = search_bytes_sync(&file, &text(true), None, None).expect("search");
+    assert!(found.offsets.is_empty());
+}
+
+/// A PDF with one page per text, document info and a bookmark for the first two pages.
+fn write_pdf(path: &str, pages: &[&str]) {
+    use lopdf::{dictionary, Bookmark, Object};
+
+    let mut doc = pdf_document(pages);
+    let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+    let chapter = doc.add_bookmark(
+        Bookmark::new("Chapter".to_string(), [0.0; 3], 0, page_ids[0]),
+        None,
+    );
+    doc.add_bookmark(
+        Bookmark::new("Section".to_string(), [0.0; 3], 0, page_ids[1]),
+        Some(chapter),
+    );
+    let outline_id = doc.build_outline().unwrap();
+    doc.catalog_mut().unwrap().set("Outlines", outline_id);
+    let info_id = doc.add_object(dictionary! {
+        "Title" => Object::string_literal("Quarterly Report"),
+        "Author" => Object::string_literal("Finance"),
+    });
+    doc.trailer.set("Info", info_id);
+    doc.save(path).unwrap();
+}
+
+#[test]
+fn get_file_preview_reads_pdf_info_outline_and_page_text() {
+    let (_dir, root) = setup_temp_workspace();
+    let file = child_path(&root, "report.pdf");
+    write_pdf(&file, &["First page text", "Second page text"]);
+
+    let FilePreview::Pdf {
+        page_count,
+        title,
+        author,
+        producer,
+        pages,
+        outline,
+        truncated,
+    } = get_file_preview_sync(&file).expect("preview")
+    else {
+        panic!("expected a PDF preview");
+    };
+    assert_eq!(page_count, 2);
+    assert_eq!(title.as_deref(), Some("Quarterly Report"));
+    assert_eq!(author.as_deref(), Some("Finance"));
+    assert_eq!(producer, None);
+    assert!(!truncated);
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[1].number, 2);
+    assert!(pages[0].text.contains("First page text"));
+    assert!(pages[1].text.contains("Second page text"));
+
+    let outline: Vec<_> = outline
+        .iter()
+        .map(|item| (item.title.as_str(), item.level, item.page))
+        .collect();
+    assert_eq!(outline, vec![("Chapter", 1, 1), ("Section", 2, 2)]);
+
+    let broken = child_path(&root, "broken.pdf");
+    std::fs::write(&broken, b"%PDF-1.4 not really").unwrap();
+    assert!(get_file_preview_sync(&broken).is_err());
+}
+
+#[test]
+fn get_file_preview_reads_opendocument_files() {
+    let (_dir, root) = setup_temp_workspace();
+    let odt = child_path(&root, "letter.odt");
+    write_zip(
+        &odt,
+        &[
+            ("mimetype", b"application/vnd.oasis.opendocument.text"),
+            (
+                "content.xml",
+                br#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"><office:body><office:text><text:h text:outline-level="1">Dear team</text:h><text:p>Thanks &amp; regards</text:p></office:text></office:body></office:document-content>"#,
+            ),
+        ],
+    );
+    let FilePreview::Document { paragraphs, .. } = get_file_preview_sync(&odt).expect("preview")
+    else {
+        panic!("expected a document preview");
+    };
+    assert_eq!(paragraphs.len(), 2);
+    assert_eq!(paragraphs[0].style, "heading1");
+    assert_eq!(paragraphs[1].text, "Thanks & regards");
+
+    let ods = child_path(&root, "budget.ods");
+    write_zip(
+        &ods,
+        &[
+            ("mimetype", b"application/vnd.oasis.opendocument.spreadsheet"),
+            (
+                "META-INF/manifest.xml",
+                br#"<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0"><manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/></manifest:manifest>"#,
+            ),
+            (
+                "content.xml",
+                br#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"><office:body><office:spreadsheet><table:table table:name="Budget"><table:table-row><table:table-cell office:value-type="string"><text:p>Item</text:p></table:table-cell><table:table-cell office:value-type="string"><text:p>Cost</text:p></table:table-cell></table:table-row><table:table-row><table:table-cell office:value-type="string"><text:p>Rent</text:p></table:table-cell><table:table-cell office:value-type="float" office:value="1200"><text:p>1200</text:p></table:table-cell></table:table-row></table:table></office:spreadsheet></office:body></office:document-content>"#,
+            ),
+        ],
+    );
+    let FilePreview::Spreadsheet { sheets } = get_file_preview_sync(&ods).expect("preview") else {
+        panic!("expected a spreadsheet preview");
+    };
+    assert_eq!(sheets[0].name, "Budget");
+    assert_eq!(sheets[0].headers, vec!["Item", "Cost"]);
+    assert_eq!(sheets[0].rows, vec![vec!["Rent", "1200"]]);
+
+    // A legacy .xls that isn't a real workbook fails instead of being misread.
+    let xls = child_path(&root, "old.xls");
+    std::fs::write(&xls, b"not a workbook").unwrap();
+    assert!(get_file_preview_sync(&xls).is_err());
+}
+
+#[test]
+fn get_file_preview_reads_docx_tables_runs_links_footnotes_and_images() {
+    let (_dir, root) = setup_temp_workspace();
+    let docx = child_path(&root, "memo.docx");
+
+    let mut png = Vec::new();
+    image::RgbaImage::from_pixel(1600, 400, image::Rgba([200, 30, 30, 128]))
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            image::ImageOutputFormat::Png,
+        )
+        .unwrap();
+
+    let document = br#"<w:document xmlns:w="w" xmlns:r="r" xmlns:a="a" xmlns:wp="wp"><w:body>
+        <w:p><w:pPr><w:pStyle w:val="Heading1"/><w:rPr><w:b/></w:rPr></w:pPr><w:r><w:t>Memo</w:t></w:r></w:p>
+        <w:p>
+            <w:r><w:t xml:space="preserve">Plain </w:t></w:r>
+            <w:r><w:rPr><w:b/><w:i/></w:rPr><w:t>bold italic</w:t></w:r>
+            <w:r><w:rPr><w:u w:val="none"/></w:rPr><w:t xml:space="preserve"> and </w:t></w:r>
+            <w:hyperlink r:id="rIdLink"><w:r><w:rPr><w:u w:val="single"/></w:rPr><w:t>a link</w:t></w:r></w:hyperlink>
+            <w:r><w:footnoteReference w:id="1"/></w:r>
+        </w:p>
+        <w:tbl>
+            <w:tr><w:tc><w:p><w:r><w:t>Name</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Qty</w:t></w:r></w:p></w:tc></w:tr>
+            <w:tr><w:tc><w:p><w:r><w:t>Apples</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>3</w:t></w:r></w:p></w:tc></w:tr>
+        </w:tbl>
+        <w:p><w:r><w:drawing><wp:inline><wp:docPr id="1" name="Picture" descr="Red banner"/>
+            <a:graphic><a:graphicData><a:blip r:embed="rIdImage"/></a:graphicData></a:graphic>
+        </wp:inline></w:drawing></w:r></w:p>
+        <w:p><w:r><w:t xml:space="preserve">Around </w:t></w:r><w:r><mc:AlternateContent xmlns:mc="mc">
+            <mc:Choice><w:drawing><wps:txbx xmlns:wps="wps"><w:txbxContent>
+                <w:p><w:r><w:rPr><w:b/></w:rPr><w:t>Boxed note</w:t></w:r></w:p>
+            </w:txbxContent></wps:txbx></w:drawing></mc:Choice>
+            <mc:Fallback><w:pict><v:textbox xmlns:v="v"><w:txbxContent>
+                <w:p><w:r><w:t>Boxed note</w:t></w:r></w:p>
+            </w:txbxContent></v:textbox></w:pict></mc:Fallback>
+        </mc:AlternateContent></w:r><w:r><w:t>the box</w:t></w:r></w:p>
+    </w:body></w:document>"#;
+    let rels = br#"<Relationships>
+        <Relationship Id="rIdLink" Type="hyperlink" Target="https://example.com" TargetMode="External"/>
+        <Relationship Id="rIdImage" Type="image" Target="media/image1.png"/>
+    </Relationships>"#;
+    let footnotes = br#"<w:footnotes xmlns:w="w">
+        <w:footnote w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>
+        <w:footnote w:id="1"><w:p><w:r><w:t>See appendix.</w:t></w:r></w:p></w:footnote>
+    </w:footnotes>"#;
+    write_zip(
+        &docx,
+        &[
+            ("word/document.xml", document),
+            ("word/_rels/document.xml.rels", rels),
+            ("word/footnotes.xml", footnotes),
+            ("word/media/image1.png", &png),
+        ],
+    );
+
+    let FilePreview::Document {
+        paragraphs,
+        truncated,
+        footnotes,
+        images,
+    } = get_file_preview_sync(&docx).expect("preview")
+    else {
+        panic!("expected a document preview");
+    };
+    assert!(!truncated);
+    assert_eq!(paragraphs.len(), 6);
+    assert_eq!(paragraphs[0].style, "heading1");
+    // Paragraph mark formatting doesn't apply to the text.
+    assert!(!paragraphs[0].runs[0].bold);
+
+    let body = &paragraphs[1];
+    assert_eq!(body.text, "Plain bold italic and a link1");
+    let runs: Vec<_> = body
+        .runs
+        .iter()
+        .map(|r| (r.text.as_str(), r.bold, r.italic, r.underline))
+        .collect();
+    assert_eq!(
+        runs,
+        vec![
+            ("Plain ", false, false, false),
+            ("bold italic", true, true, false),
+            (" and ", false, false, false),
+            ("a link", false, false, true),
+            ("1", false, false, false),
+        ]
+    );
+    assert_eq!(body.runs[3].link.as_deref(), Some("https://example.com"));
+    assert_eq!(body.runs[4].footnote, Some(1));
+    assert_eq!(footnotes.len(), 1);
+    assert_eq!(footnotes[0].text, "See appendix.");
+
+    let table = &paragraphs[2];
+    assert_eq!(table.style, "table");
+    assert_eq!(table.text, "Name\tQty\nApples\t3");
+    let rows = table.table.as_ref().expect("table");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1][0].text, "Apples");
+
+    assert_eq!(paragraphs[3].runs[0].image, Some(0));
+    assert_eq!(images.len(), 1);
+    // The banner has transparency, so it is sent as lossless WebP.
+    assert_eq!(images[0].mime, "image/webp");
+    assert_eq!(images[0].description.as_deref(), Some("Red banner"));
+    let decoded = image::load_from_memory(
+        &base64::engine::general_purpose::STANDARD
+            .decode(&images[0].base64)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (800, 200));
+
+    // Text box content follows the paragraph it is anchored in, once.
+    assert_eq!(paragraphs[4].text, "Around the box");
+    assert_eq!(paragraphs[5].text, "Boxed note");
+    assert!(paragraphs[5].runs[0].bold);
+}
+
+#[test]
+fn get_file_preview_reads_pptx_in_deck_order_with_notes_tables_and_images() {
+    let (_dir, root) = setup_temp_workspace();
+    let pptx = child_path(&root, "deck.pptx");
+
+    let mut jpeg = Vec::new();
+    image::RgbImage::from_pixel(1280, 720, image::Rgb([20, 90, 200]))
+        .write_to(
+            &mut std::io::Cursor::new(&mut jpeg),
+            image::ImageOutputFormat::Jpeg(90),
+        )
+        .unwrap();
+
+    // slide2.xml was moved to the front of the deck.
+    let presentation = br#"<p:presentation xmlns:p="p" xmlns:r="r"><p:sldIdLst>
+        <p:sldId id="257" r:id="rId3"/>
+        <p:sldId id="256" r:id="rId2"/>
+    </p:sldIdLst></p:presentation>"#;
+    let presentation_rels = br#"<Relationships>
+        <Relationship Id="rId1" Type="slideMaster" Target="slideMasters/slideMaster1.xml"/>
+        <Relationship Id="rId2" Type="slide" Target="slides/slide1.xml"/>
+        <Relationship Id="rId3" Type="slide" Target="slides/slide2.xml"/>
+    </Relationships>"#;
+    let title_slide = br#"<p:sld xmlns:p="p" xmlns:a="a" xmlns:r="r"><p:cSld><p:spTree>
+        <p:sp><p:nvSpPr><p:nvPr><p:ph type="ctrTitle"/></p:nvPr></p:nvSpPr>
+            <p:txBody><a:p><a:r><a:t>Quarterly review</a:t></a:r></a:p></p:txBody></p:sp>
+        <p:pic><p:blipFill><a:blip r:embed="rId2"/></p:blipFill></p:pic>
+    </p:spTree></p:cSld></p:sld>"#;
+    let title_rels = br#"<Relationships>
+        <Relationship Id="rId2" Type="image" Target="../media/image1.jpeg"/>
+    </Relationships>"#;
+    let agenda_slide = br#"<p:sld xmlns:p="p" xmlns:a="a" xmlns:r="r"><p:cSld><p:spTree>
+        <p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr>
+            <p:txBody><a:p><a:r><a:t>Agenda</a:t></a:r></a:p></p:txBody></p:sp>
+        <p:sp><p:nvSpPr><p:nvPr><p:ph idx="1"/></p:nvPr></p:nvSpPr>
+            <p:txBody><a:p><a:r><a:t>Numbers</a:t></a:r></a:p></p:txBody></p:sp>
+        <p:graphicFrame><a:graphic><a:graphicData><a:tbl>
+            <a:tr><a:tc><a:txBody><a:p><a:r><a:t>Team</a:t></a:r></a:p></a:txBody></a:tc><a:tc><a:txBody><a:p><a:r><a:t>Score</a:t></a:r></a:p></a:txBody></a:tc></a:tr>
+            <a:tr><a:tc><a:txBody><a:p><a:r><a:t>Ops</a:t></a:r></a:p></a:txBody></a:tc><a:tc><a:txBody><a:p><a:r><a:t>9</a:t></a:r></a:p></a:txBody></a:tc></a:tr>
+        </a:tbl></a:graphicData></a:graphic></p:graphicFrame>
+        <p:pic><p:blipFill><a:blip r:embed="rId7"/></p:blipFill></p:pic>
+    </p:spTree></p:cSld></p:sld>"#;
+    let agenda_rels = br#"<Relationships>
+        <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/notesSlide" Target="../notesSlides/notesSlide1.xml"/>
+        <Relationship Id="rId7" Type="image" Target="../media/image1.jpeg"/>
+    </Relationships>"#;
+    let notes = br#"<p:notes xmlns:p="p" xmlns:a="a"><p:cSld><p:spTree>
+        <p:sp><p:nvSpPr><p:nvPr><p:ph type="sldImg"/></p:nvPr></p:nvSpPr></p:sp>
+        <p:sp><p:nvSpPr><p:nvPr><p:ph type="body" idx="1"/></p:nvPr></p:nvSpPr>
+            <p:txBody><a:p><a:r><a:t>Mention the outage.</a:t></a:r></a:p><a:p><a:r><a:t>Keep it short.</a:t></a:r></a:p></p:txBody></p:sp>
+        <p:sp><p:nvSpPr><p:nvPr><p:ph type="sldNum"/></p:nvPr></p:nvSpPr>
+            <p:txBody><a:p><a:r><a:t>1</a:t></a:r></a:p></p:txBody></p:sp>
+    </p:spTree></p:cSld></p:notes>"#;
+
+    write_zip(
+        &pptx,
+        &[
+            ("ppt/presentation.xml", presentation),
+            ("ppt/_rels/presentation.xml.rels", presentation_rels),
+            ("ppt/slides/slide1.xml", agenda_slide),
+            ("ppt/slides/_rels/slide1.xml.rels", agenda_rels),
+            ("ppt/slides/slide2.xml", title_slide),
+            ("ppt/slides/_rels/slide2.xml.rels", title_rels),
+            ("ppt/notesSlides/notesSlide1.xml", notes),
+            ("ppt/media/image1.jpeg", &jpeg),
+        ],
+    );
+
+    let FilePreview::Presentation { slides } = get_file_preview_sync(&pptx).expect("preview")
+    else {
+        panic!("expected a presentation preview");
+    };
+    assert_eq!(slides.len(), 2);
+
+    assert_eq!(slides[0].number, 1);
+    assert_eq!(slides[0].title.as_deref(), Some("Quarterly review"));
+    assert_eq!(slides[0].notes, None);
+    assert_eq!(slides[0].images.len(), 1);
+    assert_eq!(slides[0].images[0].mime, "image/jpeg");
+    let thumbnail = base64::engine::general_purpose::STANDARD
+        .decode(&slides[0].images[0].base64)
+        .unwrap();
+    let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+    assert_eq!((thumbnail.width(), thumbnail.height()), (320, 180));
+
+    assert_eq!(slides[1].number, 2);
+    assert_eq!(slides[1].title.as_deref(), Some("Agenda"));
+    assert_eq!(slides[1].texts, vec!["Numbers"]);
+    assert_eq!(
+        slides[1].tables,
+        vec![vec![vec!["Team", "Score"], vec!["Ops", "9"]]]
+    );
+    assert_eq!(
+        slides[1].notes.as_deref(),
+        Some("Mention the outage.\nKeep it short.")
+    );
+    assert_eq!(slides[1].images.len(), 1);
+}
+
+#[test]
+fn spreadsheet_preview_types_cells_and_pages_large_sheets() {
+    let (_dir, root) = setup_temp_workspace();
+    let xlsx = child_path(&root, "sales.xlsx");
+
+    let workbook = br#"<workbook xmlns="m" xmlns:r="r"><sheets>
+        <sheet name="Sales" sheetId="1" r:id="rId1"/>
+        <sheet name="Secret" sheetId="2" state="hidden" r:id="rId2"/>
+    </sheets></workbook>"#;
+    let rels = br#"<Relationships>
+        <Relationship Id="rId1" Type="worksheet" Target="worksheets/sheet1.xml"/>
+        <Relationship Id="rId2" Type="worksheet" Target="worksheets/sheet2.xml"/>
+    </Relationships>"#;
+    let styles = br#"<styleSheet xmlns="m">
+        <numFmts count="1"><numFmt numFmtId="164" formatCode="0.0%"/></numFmts>
+        <cellXfs count="3"><xf numFmtId="0"/><xf numFmtId="164"/><xf numFmtId="14"/></cellXfs>
+    </styleSheet>"#;
+
+    let mut sheet = String::from(
+        r#"<worksheet xmlns="m"><sheetData>
+        <row r="1"><c r="A1" t="inlineStr"><is><t>Region</t></is></c><c r="B1" t="inlineStr"><is><t>Share</t></is></c><c r="C1" t="inlineStr"><is><t>Since</t></is></c></row>
+        <row r="2"><c r="A2" t="inlineStr"><is><t>North</t></is></c><c r="B2" s="1"><f>B3/2</f><v>0.25</v></c><c r="C2" s="2"><v>45352</v></c></row>"#,
+    );
+    for row in 3..=300 {
+        sheet.push_str(&format!(
+            r#"<row r="{row}"><c r="A{row}" t="inlineStr"><is><t>R{row}</t></is></c><c r="B{row}"><v>{row}</v></c></row>"#
+        ));
+    }
+    sheet.push_str(r#"</sheetData><mergeCells count="1"><mergeCell ref="A250:B251"/></mergeCells></worksheet>"#);
+    let hidden = br#"<worksheet xmlns="m"><sheetData><row r="1"><c r="A1" t="b"><v>1</v></c></row></sheetData></worksheet>"#;
+
+    write_zip(
+        &xlsx,
+        &[
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/styles.xml", styles),
+            ("xl/worksheets/sheet1.xml", sheet.as_bytes()),
+            ("xl/worksheets/sheet2.xml", hidden),
+        ],
+    );
+
+    let FilePreview::Spreadsheet { sheets } = get_file_preview_sync(&xlsx).expect("preview") else {
+        panic!("expected a spreadsheet preview");
+    };
+    let sales = &sheets[0];
+    assert_eq!(sales.headers, vec!["Region", "Share", "Since"]);
+    assert_eq!(sales.row_offset, 1);
+    assert_eq!(sales.rows[0][0], "North");
+    assert_eq!(sales.rows[0][2], "2024-03-01T00:00:00");
+    assert!(sales.truncated);
+    assert!(!sales.hidden);
+
+    let share = sales
+        .cells
+        .iter()
+        .find(|c| (c.row, c.column) == (1, 1))
+        .expect("typed cell");
+    assert_eq!(share.value, CellValue::Number { value: 0.25 });
+    assert_eq!(share.formula.as_deref(), Some("B3/2"));
+    assert_eq!(share.number_format.as_deref(), Some("0.0%"));
+    let since = sales
+        .cells
+        .iter()
+        .find(|c| (c.row, c.column) == (1, 2))
+        .expect("date cell");
+    assert!(matches!(since.value, CellValue::Date { .. }));
+    assert_eq!(since.number_format.as_deref(), Some("mm-dd-yy"));
+
+    assert_eq!(
+        sales.merged,
+        vec![MergedRange {
+            start_row: 249,
+            start_column: 0,
+            end_row: 250,
+            end_column: 1,
+        }]
+    );
+    assert!(sheets[1].hidden);
+    // A lone boolean isn't a header row.
+    assert!(sheets[1].headers.is_empty());
+
+    let state = SheetCacheState::new();
+    let page = read_sheet_range_sync(&xlsx, "Sales", 249, 10, &state).expect("page");
+    assert_eq!(page.row_offset, 249);
+    assert_eq!(page.rows.len(), 10);
+    assert_eq!(page.rows[0], vec!["R250", "250", ""]);
+    assert_eq!(page.merged.len(), 1);
+    assert_eq!(page.end_row, 300);
+    assert!(page
+        .cells
+        .iter()
+        .any(|c| c.row == 249 && c.value == CellValue::Number { value: 250.0 }));
+
+    let tail = read_sheet_range_sync(&xlsx, "Sales", 295, 100, &state).expect("page");
+    assert_eq!(tail.rows.len(), 5);
+    assert!(tail.merged.is_empty());
+    assert!(read_sheet_range_sync(&xlsx, "Missing", 0, 10, &state).is_err());
+
+    // A rewritten workbook isn't served from the cached sheet.
+    write_zip(
+        &xlsx,
+        &[
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/styles.xml", styles),
+            ("xl/worksheets/sheet1.xml", hidden),
+            ("xl/worksheets/sheet2.xml", hidden),
+        ],
+    );
+    let page = read_sheet_range_sync(&xlsx, "Sales", 0, 10, &state).expect("page");
+    assert_eq!(page.rows, vec![vec!["TRUE"]]);
+    assert_eq!(page.end_row, 1);
+}
+
+#[test]
+fn get_thumbnail_uses_embedded_previews_and_covers() {
+    let (_dir, root) = setup_temp_workspace();
+    let png = |width: u32, height: u32| {
+        let mut data = Vec::new();
+        image::RgbImage::from_pixel(width, height, image::Rgb([10, 120, 60]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut data),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+        data
+    };
+    let thumbnail_size = |path: &str| {
+        let thumbnail = get_thumbnail_sync(path, 64).expect("thumbnail");
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(thumbnail.base64)
+            .unwrap();
+        let img = image::load_from_memory(&data).unwrap();
+        (img.width(), img.height())
+    };
+
+    let docx = child_path(&root, "report.docx");
+    write_zip(
+        &docx,
+        &[
+            (
+                "_rels/.rels",
+                br#"<Relationships>
+                    <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+                    <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/thumbnail" Target="docProps/preview.png"/>
+                </Relationships>"#,
+            ),
+            ("docProps/preview.png", &png(256, 128)),
+        ],
+    );
+    assert_eq!(thumbnail_size(&docx), (64, 32));
+
+    let odt = child_path(&root, "notes.odt");
+    write_zip(&odt, &[("Thumbnails/thumbnail.png", &png(100, 200))]);
+    assert_eq!(thumbnail_size(&odt), (32, 64));
+
+    let epub = child_path(&root, "novel.epub");
+    write_zip(
+        &epub,
+        &[
+            (
+                "META-INF/container.xml",
+                br#"<container><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                br#"<package><metadata><meta name="cover" content="art"/></metadata><manifest>
+                    <item id="map" href="images/map.png" media-type="image/png"/>
+                    <item id="art" href="images/front%20cover.png" media-type="image/png"/>
+                </manifest></package>"#,
+            ),
+            ("OEBPS/images/map.png", &png(300, 300)),
+            ("OEBPS/images/front cover.png", &png(90, 120)),
+        ],
+    );
+    assert_eq!(thumbnail_size(&epub), (48, 64));
+
+    let cbz = child_path(&root, "issue.cbz");
+    write_zip(
+        &cbz,
+        &[
+            ("__MACOSX/._001.png", b"junk"),
+            ("pages/002.png", &png(300, 300)),
+            ("pages/001.png", &png(120, 180)),
+            ("ComicInfo.xml", b"<ComicInfo/>"),
+        ],
+    );
+    assert_eq!(thumbnail_size(&cbz), (43, 64));
+
+    let bare = child_path(&root, "bare.pptx");
+    write_zip(&bare, &[("ppt/presentation.xml", b"<p:presentation/>")]);
+    let err = get_thumbnail_sync(&bare, 64).unwrap_err();
+    assert!(err.contains("No embedded thumbnail"));
+}
+
+/// A JPEG with the given EXIF fields and XMP packet as APP1 segments.
+fn write_photo(path: &str, width: u32, height: u32, fields: &[exif::Field], xmp: &str) {
+    let mut jpeg = Vec::new();
+    image::RgbImage::from_fn(width, height, |x, _| {
+        image::Rgb([if x < width / 2 { 255 } else { 0 }, 0, 0])
+    })
+    .write_to(
+        &mut std::io::Cursor::new(&mut jpeg),
+        image::ImageOutputFormat::Jpeg(90),
+    )
+    .unwrap();
+
+    let mut writer = exif::experimental::Writer::new();
+    for field in fields {
+        writer.push_field(field);
+    }
+    let mut tiff = std::io::Cursor::new(Vec::new());
+    writer.write(&mut tiff, false).unwrap();
+
+    let segment = |header: &[u8], body: &[u8]| {
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend(((header.len() + body.len() + 2) as u16).to_be_bytes());
+        segment.extend(header);
+        segment.extend(body);
+        segment
+    };
+    let mut photo = jpeg[..2].to_vec();
+    photo.extend(segment(b"Exif\0\0", tiff.get_ref()));
+    photo.extend(segment(b"http://ns.adobe.com/xap/1.0/\0", xmp.as_bytes()));
+    photo.extend(&jpeg[2..]);
+    std::fs::write(path, photo).unwrap();
+}
+
+#[test]
+fn image_metadata_reads_exif_and_xmp_and_previews_apply_orientation() {
+    use exif::{Field, In, Rational, Tag, Value};
+
+    let (_dir, root) = setup_temp_workspace();
+    let photo = child_path(&root, "IMG_0001.jpg");
+    let field = |tag, value| Field {
+        tag,
+        ifd_num: In::PRIMARY,
+        value,
+    };
+    let ascii = |text: &str| Value::Ascii(vec![text.as_bytes().to_vec()]);
+    let rational = |num, denom| Rational { num, denom };
+    write_photo(
+        &photo,
+        40,
+        20,
+        &[
+            field(Tag::Orientation, Value::Short(vec![6])),
+            field(Tag::Make, ascii("Fujifilm")),
+            field(Tag::Model, ascii("X-T5")),
+            field(Tag::LensModel, ascii("XF35mmF1.4 R")),
+            field(Tag::ExposureTime, Value::Rational(vec![rational(1, 250)])),
+            field(Tag::FNumber, Value::Rational(vec![rational(28, 10)])),
+            field(Tag::PhotographicSensitivity, Value::Short(vec![400])),
+            field(Tag::FocalLength, Value::Rational(vec![rational(35, 1)])),
+            field(Tag::DateTimeOriginal, ascii("2024:05:01 14:30:05")),
+            field(Tag::OffsetTimeOriginal, ascii("+02:00")),
+            field(Tag::GPSLatitudeRef, ascii("N")),
+            field(
+                Tag::GPSLatitude,
+                Value::Rational(vec![rational(52, 1), rational(31, 1), rational(12, 1)]),
+            ),
+            field(Tag::GPSLongitudeRef, ascii("W")),
+            field(
+                Tag::GPSLongitude,
+                Value::Rational(vec![rational(13, 1), rational(30, 1), rational(0, 1)]),
+            ),
+            field(Tag::GPSAltitude, Value::Rational(vec![rational(345, 10)])),
+        ],
+        r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF><rdf:Description xmp:Rating="5">
+            <dc:description><rdf:Alt><rdf:li xml:lang="x-default">Canal at dusk</rdf:li></rdf:Alt></dc:description>
+            <dc:subject><rdf:Bag><rdf:li>city</rdf:li><rdf:li>water</rdf:li></rdf:Bag></dc:subject>
+        </rdf:Description></rdf:RDF></x:xmpmeta>"#,
+    );
+
+    let metadata = get_image_metadata_sync(&photo).expect("metadata");
+    assert_eq!(metadata.format, "Jpeg");
+    assert_eq!((metadata.width, metadata.height), (20, 40));
+    assert_eq!(metadata.color_type.as_deref(), Some("Rgb8"));
+    assert_eq!(metadata.bit_depth, Some(8));
+    assert!(!metadata.has_alpha);
+    assert_eq!(metadata.orientation, Some(6));
+    assert_eq!(metadata.camera_make.as_deref(), Some("Fujifilm"));
+    assert_eq!(metadata.camera_model.as_deref(), Some("X-T5"));
+    assert_eq!(metadata.lens.as_deref(), Some("XF35mmF1.4 R"));
+    assert_eq!(metadata.exposure_time.as_deref(), Some("1/250"));
+    assert_eq!(metadata.f_number, Some(2.8));
+    assert_eq!(metadata.iso, Some(400));
+    assert_eq!(metadata.focal_length, Some(35.0));
+    assert_eq!(
+        metadata.captured_at.as_deref(),
+        Some("2024-05-01T14:30:05+02:00")
+    );
+    let gps = metadata.gps.expect("gps");
+    assert!((gps.latitude - 52.52).abs() < 1e-9);
+    assert!((gps.longitude + 13.5).abs() < 1e-9);
+    assert_eq!(gps.altitude, Some(34.5));
+    assert_eq!(metadata.rating, Some(5));
+    assert_eq!(metadata.description.as_deref(), Some("Canal at dusk"));
+    assert_eq!(metadata.keywords, vec!["city", "water"]);
+
+    // Orientation 6 turns the wide, left-red frame into a tall, top-red one.
+    let decode = |base64: &str| {
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(base64)
+            .unwrap();
+        image::load_from_memory(&data).unwrap().to_rgb8()
+    };
+    let thumbnail = decode(&get_thumbnail_sync(&photo, 64).expect("thumbnail").base64);
+    assert_eq!(thumbnail.dimensions(), (20, 40));
+    assert!(thumbnail.get_pixel(10, 5)[0] > 200);
+    assert!(thumbnail.get_pixel(10, 35)[0] < 50);
+
+    let FilePreview::Image { base64, mime } = get_file_preview_sync(&photo).expect("preview")
+    else {
+        panic!("expected an image preview");
+    };
+    assert_eq!(mime, "image/jpeg");
+    assert_eq!(decode(&base64).dimensions(), (20, 40));
+
+    let plain = child_path(&root, "plain.png");
+    image::RgbaImage::new(8, 4).save(&plain).unwrap();
+    let metadata = get_image_metadata_sync(&plain).expect("metadata");
+    assert_eq!((metadata.width, metadata.height), (8, 4));
+    assert_eq!(metadata.color_type.as_deref(), Some("Rgba8"));
+    assert!(metadata.has_alpha);
+    assert_eq!(metadata.orientation, None);
+    assert!(metadata.gps.is_none());
+}
+
+/// An uncompressed 8-bit RGBA Photoshop document, with its composite matted
+/// against white the way Photoshop stores it.
+fn write_psd(path: &str, width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) {
+    let mut psd = b"8BPS".to_vec();
+    psd.extend(1u16.to_be_bytes());
+    psd.extend([0; 6]);
+    psd.extend(4u16.to_be_bytes());
+    psd.extend(height.to_be_bytes());
+    psd.extend(width.to_be_bytes());
+    psd.extend(8u16.to_be_bytes());
+    psd.extend(3u16.to_be_bytes());
+    // Empty color mode data, image resources and layer sections, then raw data.
+    psd.extend([0; 12]);
+    psd.extend(0u16.to_be_bytes());
+    for channel in 0..4 {
+        for y in 0..height {
+            for x in 0..width {
+                let [r, g, b, a] = pixel(x, y);
+                let matte =
+                    |c: u8| ((u16::from(c) * u16::from(a)) / 255 + 255 - u16::from(a)) as u8;
+                psd.push([matte(r), matte(g), matte(b), a][channel]);
+            }
+        }
+    }
+    std::fs::write(path, psd).unwrap();
+}
+
+#[test]
+fn image_previews_and_thumbnails_dispatch_on_content_and_read_more_formats() {
+    let (_dir, root) = setup_temp_workspace();
+    let decode = |base64: &str| {
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(base64)
+            .unwrap();
+        image::load_from_memory(&data).unwrap().to_rgba8()
+    };
+    let thumbnail = |path: &str| decode(&get_thumbnail_sync(path, 64).expect("thumbnail").base64);
+    let preview = |path: &str| match get_file_preview_sync(path).expect("preview") {
+        FilePreview::Image { base64, mime } => (mime, decode(&base64)),
+        other => panic!("expected an image preview for {path}, got {other:?}"),
+    };
+
+    let gradient = image::RgbImage::from_fn(96, 48, |x, _| image::Rgb([x as u8 * 2, 80, 160]));
+    for (name, format) in [
+        ("scan.tiff", image::ImageFormat::Tiff),
+        ("sprite.tga", image::ImageFormat::Tga),
+        ("frame.ppm", image::ImageFormat::Pnm),
+        ("tile.qoi", image::ImageFormat::Qoi),
+    ] {
+        let path = child_path(&root, name);
+        gradient.save_with_format(&path, format).unwrap();
+        let (mime, img) = preview(&path);
+        assert_eq!(mime, "image/jpeg", "{name}");
+        assert_eq!(img.dimensions(), (96, 48), "{name}");
+        assert_eq!(thumbnail(&path).dimensions(), (64, 32), "{name}");
+    }
+
+    // Content wins over the extension in both directions.
+    let disguised = child_path(&root, "export.dat");
+    gradient
+        .save_with_format(&disguised, image::ImageFormat::Png)
+        .unwrap();
+    let (mime, _) = preview(&disguised);
+    assert_eq!(mime, "image/jpeg");
+    assert_eq!(thumbnail(&disguised).dimensions(), (64, 32));
+
+    let not_png = child_path(&root, "notes.png");
+    std::fs::write(&not_png, "P1 is the first phase of the rollout\n").unwrap();
+    assert!(matches!(
+        get_file_preview_sync(&not_png).expect("preview"),
+        FilePreview::Text { .. }
+    ));
+    assert!(get_thumbnail_sync(&not_png, 64).is_err());
+
+    let svg = child_path(&root, "logo.svg");
+    std::fs::write(
+        &svg,
+        r#"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <rect x="0" y="0" width="100" height="100" fill="red"/>
+</svg>"#,
+    )
+    .unwrap();
+    let small = thumbnail(&svg);
+    assert_eq!(small.dimensions(), (64, 32));
+    assert_eq!(small.get_pixel(10, 16).0, [255, 0, 0, 255]);
+    assert_eq!(small.get_pixel(54, 16)[3], 0);
+    let (mime, large) = preview(&svg);
+    assert_eq!(mime, "image/webp");
+    assert_eq!(large.dimensions(), (2048, 1024));
+
+    let psd = child_path(&root, "poster.psd");
+    write_psd(&psd, 4, 2, |x, _| match x {
+        0 | 1 => [0, 0, 255, 255],
+        2 => [255, 0, 0, 128],
+        _ => [0, 0, 0, 0],
+    });
+    let (mime, img) = preview(&psd);
+    assert_eq!(mime, "image/webp");
+    assert_eq!(img.get_pixel(0, 0).0, [0, 0, 255, 255]);
+    let half = img.get_pixel(2, 1).0;
+    assert_eq!((half[0], half[3]), (255, 128));
+    assert!(half[1] <= 1 && half[2] <= 1);
+    assert_eq!(img.get_pixel(3, 0)[3], 0);
+    assert_eq!(thumbnail(&psd).dimensions(), (16, 8));
+}
+
+#[test]
+fn image_previews_are_downscaled_to_the_viewport() {
+    let (_dir, root) = setup_temp_workspace();
+    let decode = |preview: FilePreview| match preview {
+        FilePreview::Image { base64, mime } => {
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(base64)
+                .unwrap();
+            (mime, image::load_from_memory(&data).unwrap())
+        }
+        other => panic!("expected an image preview, got {other:?}"),
+    };
+
+    // Noise barely compresses, so this PNG is well over the old 5 MB cap.
+    let mut state = 0x2545_f491_u32;
+    let noise = image::RgbImage::from_fn(3000, 1000, |_, _| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let [r, g, b, _] = state.to_le_bytes();
+        image::Rgb([r, g, b])
+    });
+    let large = child_path(&root, "noise.png");
+    noise.save(&large).unwrap();
+    assert!(std::fs::metadata(&large).unwrap().len() > 5_000_000);
+
+    let (mime, img) = decode(get_file_preview_sync(&large).expect("preview"));
+    assert_eq!(mime, "image/jpeg");
+    assert_eq!((img.width(), img.height()), (2048, 683));
+    let (_, img) = decode(get_image_preview_sync(&large, 300, 200).expect("preview"));
+    assert_eq!((img.width(), img.height()), (300, 100));
+
+    // Transparency is kept, and smaller images aren't scaled up.
+    let overlay = child_path(&root, "overlay.png");
+    image::RgbaImage::from_pixel(40, 20, image::Rgba([0, 128, 0, 64]))
+        .save(&overlay)
+        .unwrap();
+    let (mime, img) = decode(get_image_preview_sync(&overlay, 800, 600).expect("preview"));
+    assert_eq!(mime, "image/webp");
+    assert_eq!(img.to_rgba8().get_pixel(5, 5).0, [0, 128, 0, 64]);
+
+    let gif = child_path(&root, "spinner.gif");
+    image::RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255]))
+        .save(&gif)
+        .unwrap();
+    let FilePreview::Image { base64, mime } = get_file_preview_sync(&gif).expect("preview") else {
+        panic!("expected an image preview");
+    };
+    assert_eq!(mime, "image/gif");
+    assert_eq!(
+        base64::engine::general_purpose::STANDARD
+            .decode(base64)
+            .unwrap(),
+        std::fs::read(&gif).unwrap()
+    );
+
+    // Only the header is read for images over the pixel limit.
+    let mut bmp = b"BM".to_vec();
+    bmp.extend(54u32.to_le_bytes());
+    bmp.extend([0; 4]);
+    bmp.extend(54u32.to_le_bytes());
+    bmp.extend(40u32.to_le_bytes());
+    bmp.extend(20_000i32.to_le_bytes());
+    bmp.extend(20_000i32.to_le_bytes());
+    bmp.extend(1u16.to_le_bytes());
+    bmp.extend(24u16.to_le_bytes());
+    bmp.extend([0; 24]);
+    let huge = child_path(&root, "huge.bmp");
+    std::fs::write(&huge, bmp).unwrap();
+    let FilePreview::Unsupported { mime } = get_image_preview_sync(&huge, 800, 600).unwrap() else {
+        panic!("expected an unsupported preview");
+    };
+    assert_eq!(mime, "image/bmp (too large)");
+
+    let text = child_path(&root, "readme.txt");
+    std::fs::write(&text, "not an image").unwrap();
+    assert!(get_image_preview_sync(&text, 800, 600).is_err());
+}
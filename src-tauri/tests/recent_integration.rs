@@ -0,0 +1,90 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use chrono::Local;
+use common::setup_temp_workspace;
+use file_manager_lib::commands::index::{add_index_root_sync, IndexState};
+use file_manager_lib::commands::recent_changes_sync;
+use file_manager_lib::models::RecentDay;
+
+fn create_recent_tree(root: &Path) {
+    for dir in ["docs", ".config", "app/node_modules/pkg", "app/src"] {
+        fs::create_dir_all(root.join(dir)).expect("mkdir");
+    }
+    for file in [
+        "docs/report.txt",
+        "docs/.draft.txt",
+        ".config/settings.json",
+        "app/node_modules/pkg/index.js",
+        "app/src/main.rs",
+    ] {
+        fs::write(root.join(file), file).expect("write");
+    }
+}
+
+fn listed_names(days: &[RecentDay]) -> Vec<String> {
+    let mut names: Vec<String> = days
+        .iter()
+        .flat_map(|day| day.entries.iter().map(|e| e.name.clone()))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn recent_changes_walk_skips_hidden_and_cache_directories() {
+    let (dir, root) = setup_temp_workspace();
+    create_recent_tree(dir.path());
+    let index = IndexState::in_memory().expect("index");
+    let roots = vec![root.clone()];
+
+    let days = recent_changes_sync(&roots, None, None, false, &index).expect("recent");
+    assert_eq!(listed_names(&days), vec!["main.rs", "report.txt"]);
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].date, Local::now().date_naive().to_string());
+
+    let days = recent_changes_sync(&roots, None, None, true, &index).expect("recent");
+    assert_eq!(
+        listed_names(&days),
+        vec![
+            ".draft.txt",
+            "index.js",
+            "main.rs",
+            "report.txt",
+            "settings.json"
+        ]
+    );
+
+    let days = recent_changes_sync(&roots, None, Some(1), false, &index).expect("recent");
+    assert_eq!(listed_names(&days).len(), 1);
+
+    let tomorrow = SystemTime::now() + Duration::from_secs(24 * 60 * 60);
+    let since = tomorrow
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let days = recent_changes_sync(&roots, Some(since), None, false, &index).expect("recent");
+    assert!(days.is_empty());
+
+    assert!(recent_changes_sync(&["relative".to_string()], None, None, false, &index).is_err());
+}
+
+#[test]
+fn recent_changes_uses_the_index_for_indexed_roots() {
+    let (dir, root) = setup_temp_workspace();
+    create_recent_tree(dir.path());
+    let index = IndexState::in_memory().expect("index");
+    add_index_root_sync(&root, &index).expect("add root");
+    assert!(index.wait_until_idle(Duration::from_secs(10)));
+
+    // Nested and overlapping roots are answered from the index without duplicates.
+    let roots = vec![root.clone(), format!("{root}/app")];
+    let days = recent_changes_sync(&roots, None, None, false, &index).expect("recent");
+    assert_eq!(listed_names(&days), vec!["main.rs", "report.txt"]);
+
+    let days = recent_changes_sync(&roots, None, None, true, &index).expect("recent");
+    assert_eq!(listed_names(&days).len(), 5);
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use common::{create_fixture_tree, setup_temp_workspace};
+use file_manager_lib::commands::saved_search::SavedSearchState;
+use file_manager_lib::commands::{
+    delete_saved_search_sync, list_saved_searches_sync, open_saved_search_sync, save_search_sync,
+    unwatch_saved_search_sync, watch_saved_search_sync,
+};
+use file_manager_lib::models::SearchOptions;
+
+fn options(root: &str, query: &str) -> SearchOptions {
+    SearchOptions {
+        query: query.to_string(),
+        search_path: root.to_string(),
+        ..Default::default()
+    }
+}
+
+fn names(entries: &[file_manager_lib::models::FileEntry]) -> Vec<String> {
+    entries.iter().map(|e| e.path.clone()).collect()
+}
+
+#[test]
+fn saved_searches_persist_update_and_delete() {
+    let (dir, root) = setup_temp_workspace();
+    let store = dir.path().join("data").join("saved-searches.json");
+
+    let state = SavedSearchState::open(&store).expect("open store");
+    let first = save_search_sync(None, "Notes", options(&root, "nested"), &state).expect("save");
+    let second = save_search_sync(None, " ", options(&root, "readme"), &state).expect("save");
+    assert_ne!(first.id, second.id);
+    assert_eq!(second.name, "readme");
+
+    let renamed = save_search_sync(Some(&first.id), "All notes", options(&root, "txt"), &state)
+        .expect("update");
+    assert_eq!(renamed.created, first.created);
+    drop(state);
+
+    let state = SavedSearchState::open(&store).expect("reopen store");
+    let listed = list_saved_searches_sync(&state).expect("list");
+    assert_eq!(
+        listed.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+        vec!["All notes", "readme"]
+    );
+    assert_eq!(listed[0].options.query, "txt");
+
+    assert!(delete_saved_search_sync(&first.id, &state).expect("delete"));
+    assert!(!delete_saved_search_sync(&first.id, &state).expect("delete again"));
+    assert!(save_search_sync(Some(&first.id), "Gone", options(&root, "x"), &state).is_err());
+
+    let state = SavedSearchState::open(&store).expect("reopen store");
+    assert_eq!(list_saved_searches_sync(&state).expect("list").len(), 1);
+}
+
+#[test]
+fn open_saved_search_lists_hits_like_a_directory() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let state = SavedSearchState::in_memory();
+
+    let search = save_search_sync(None, "Nested", options(&root, "nested"), &state).expect("save");
+    let entries = open_saved_search_sync(&search.id, &state).expect("open");
+
+    let mut expected = vec![
+        dir.path().join("nested.txt").to_string_lossy().to_string(),
+        dir.path()
+            .join("subdir")
+            .join("nested.txt")
+            .to_string_lossy()
+            .to_string(),
+    ];
+    expected.sort();
+    let mut found = names(&entries);
+    found.sort();
+    assert_eq!(found, expected);
+    assert!(entries.iter().all(|e| !e.is_dir && e.size > 0));
+
+    assert!(open_saved_search_sync("missing", &state).is_err());
+}
+
+#[test]
+fn watched_saved_search_reports_new_hits() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let state = SavedSearchState::in_memory();
+    let search = save_search_sync(None, "Readme", options(&root, "readme"), &state).expect("save");
+
+    let (tx, rx) = channel();
+    let initial = watch_saved_search_sync(&search.id, &state, move |update| {
+        let _ = tx.send(update);
+    })
+    .expect("watch");
+    assert_eq!(initial.len(), 1);
+    assert_eq!(state.live_search_count(), 1);
+
+    fs::write(Path::new(&root).join("subdir").join("readme-2.txt"), "more").expect("write");
+
+    let update = rx
+        .recv_timeout(Duration::from_secs(10))
+        .expect("saved-search update");
+    assert_eq!(update.id, search.id);
+    assert_eq!(update.entries.len(), 2);
+
+    assert!(unwatch_saved_search_sync(&search.id, &state).expect("unwatch"));
+    assert_eq!(state.live_search_count(), 0);
+}
+
+#[test]
+fn corrupt_store_is_moved_aside() {
+    let (dir, root) = setup_temp_workspace();
+    let store = dir.path().join("saved-searches.json");
+    fs::write(&store, "{ not json").unwrap();
+
+    let state = SavedSearchState::open(&store).expect("open store");
+    assert!(list_saved_searches_sync(&state).expect("list").is_empty());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("saved-searches.json.corrupt")).unwrap(),
+        "{ not json"
+    );
+
+    // Saving keeps working and persists again.
+    save_search_sync(None, "Notes", options(&root, "nested"), &state).expect("save");
+    let state = SavedSearchState::open(&store).expect("reopen store");
+    assert_eq!(list_saved_searches_sync(&state).expect("list").len(), 1);
+}
+
+#[test]
+fn watched_saved_search_follows_a_new_root() {
+    let (dir, _) = setup_temp_workspace();
+    let (first, second) = (dir.path().join("first"), dir.path().join("second"));
+    fs::create_dir(&first).unwrap();
+    fs::create_dir(&second).unwrap();
+    let state = SavedSearchState::in_memory();
+    let first_root = first.to_string_lossy().to_string();
+    let search =
+        save_search_sync(None, "Logs", options(&first_root, ".log"), &state).expect("save");
+
+    let (tx, rx) = channel();
+    watch_saved_search_sync(&search.id, &state, move |update| {
+        let _ = tx.send(update);
+    })
+    .expect("watch");
+
+    let second_root = second.to_string_lossy().to_string();
+    save_search_sync(
+        Some(&search.id),
+        "Logs",
+        options(&second_root, ".log"),
+        &state,
+    )
+    .expect("update");
+    assert_eq!(state.live_search_count(), 1);
+
+    fs::write(second.join("app.log"), "line").expect("write");
+    let update = rx
+        .recv_timeout(Duration::from_secs(10))
+        .expect("saved-search update");
+    assert_eq!(update.entries.len(), 1);
+    assert!(update.entries[0].path.ends_with("app.log"));
+}
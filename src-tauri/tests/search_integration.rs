@@ -1,10 +1,19 @@
 mod common;
 
-use common::{create_fixture_tree, setup_temp_workspace};
+use common::{create_fixture_tree, docx_bytes, pdf_bytes, setup_temp_workspace, zip_bytes};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use file_manager_lib::commands::search::{
-    search_by_name_sync, search_content_sync, search_files_sync,
+    cancel_search_sync, fuzzy_search_sync, parse_search_query_sync, replace_in_files_sync,
+    search_by_name_sync, search_content_sync, search_files_cancellable_sync, search_files_sync,
+    undo_replace_sync, SearchState,
+};
+use file_manager_lib::models::{
+    EntryKind, FuzzySearchOptions, MatchLocation, ReplaceOptions, ReplaceSelection, SearchFilters,
+    SearchOptions, SearchResult, TimeBound, TimeRange,
 };
-use file_manager_lib::models::SearchOptions;
 
 #[test]
 fn search_files_finds_by_name() {
@@ -17,6 +26,7 @@ fn search_files_finds_by_name() {
         case_sensitive: false,
         max_results: Some(10),
         file_extensions: None,
+        ..Default::default()
     };
     let results = search_files_sync(&options).expect("search");
     assert!(results.iter().any(|r| r.name == "readme.txt"));
@@ -47,6 +57,7 @@ fn search_files_rejects_missing_root() {
         case_sensitive: false,
         max_results: Some(10),
         file_extensions: None,
+        ..Default::default()
     };
     let err = search_files_sync(&options).unwrap_err().to_string();
     assert!(err.contains("not found") || err.contains("NotFound") || err.contains("SearchPath"));
@@ -63,8 +74,976 @@ fn search_files_stream_returns_results() {
         case_sensitive: false,
         max_results: Some(10),
         file_extensions: None,
+        ..Default::default()
     };
     // Without AppHandle events, assert sync path parity (stream command wraps sync + emit).
     let sync_count = search_files_sync(&options).expect("sync").len();
     assert!(sync_count >= 1);
 }
+
+#[test]
+fn search_files_cancellable_stops_when_cancelled() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let options = SearchOptions {
+        query: "nested".to_string(),
+        search_path: root,
+        search_content: false,
+        case_sensitive: false,
+        max_results: None,
+        file_extensions: None,
+        ..Default::default()
+    };
+
+    let running = AtomicBool::new(false);
+    let results = search_files_cancellable_sync(&options, "s1", &running).expect("search");
+    assert_eq!(results.len(), 2);
+
+    let cancelled = AtomicBool::new(true);
+    let err = search_files_cancellable_sync(&options, "s2", &cancelled)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("cancelled"));
+}
+
+#[test]
+fn new_search_supersedes_previous_search_from_same_window() {
+    let state = SearchState::new();
+    let first = state.begin("first", "main").expect("begin first");
+    let other_window = state.begin("other", "secondary").expect("begin other");
+    let second = state.begin("second", "main").expect("begin second");
+
+    assert!(first.load(Ordering::Relaxed));
+    assert!(!other_window.load(Ordering::Relaxed));
+    assert!(!second.load(Ordering::Relaxed));
+    assert_eq!(state.active_search_count(), 2);
+
+    state.finish("other");
+    assert_eq!(state.active_search_count(), 1);
+}
+
+#[test]
+fn cancel_search_flags_active_search_only_once() {
+    let state = SearchState::new();
+    let flag = state.begin("s1", "main").expect("begin");
+
+    assert!(cancel_search_sync("s1", &state).expect("cancel"));
+    assert!(flag.load(Ordering::Relaxed));
+    assert_eq!(state.active_search_count(), 0);
+
+    assert!(!cancel_search_sync("s1", &state).expect("cancel again"));
+    assert!(!cancel_search_sync("unknown", &state).expect("cancel unknown"));
+}
+
+fn name_search(root: &str, query: &str) -> SearchOptions {
+    SearchOptions {
+        query: query.to_string(),
+        search_path: root.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn search_files_respects_gitignore_when_enabled() {
+    let (dir, root) = setup_temp_workspace();
+    fs::create_dir_all(dir.path().join("target")).unwrap();
+    fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+    fs::write(dir.path().join("target").join("build.log"), "").unwrap();
+    fs::write(dir.path().join("app.log"), "").unwrap();
+
+    let all = search_files_sync(&name_search(&root, ".log")).expect("search");
+    assert_eq!(all.len(), 2);
+
+    let options = SearchOptions {
+        respect_gitignore: true,
+        ..name_search(&root, ".log")
+    };
+    let filtered = search_files_sync(&options).expect("search");
+    let names: Vec<_> = filtered.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["app.log"]);
+}
+
+#[test]
+fn search_files_skips_exclude_globs() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+
+    let options = SearchOptions {
+        exclude_globs: Some(vec!["subdir/".to_string()]),
+        ..name_search(&root, "nested")
+    };
+    let results = search_files_sync(&options).expect("search");
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].path.contains("subdir"));
+
+    let invalid = SearchOptions {
+        exclude_globs: Some(vec!["a{b".to_string()]),
+        ..name_search(&root, "nested")
+    };
+    let err = search_files_sync(&invalid).unwrap_err().to_string();
+    assert!(err.contains("Invalid search pattern"));
+}
+
+#[test]
+fn search_files_limits_depth_and_sorts_results() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+
+    let shallow = SearchOptions {
+        max_depth: Some(1),
+        ..name_search(&root, "nested")
+    };
+    assert_eq!(search_files_sync(&shallow).expect("search").len(), 1);
+
+    let results = search_files_sync(&name_search(&root, ".txt")).expect("search");
+    let paths: Vec<_> = results.iter().map(|r| r.path.clone()).collect();
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths, sorted);
+}
+
+#[test]
+fn search_files_returns_the_same_results_when_the_limit_is_hit() {
+    let (dir, root) = setup_temp_workspace();
+    for group in ["a", "b", "c", "d"] {
+        let folder = dir.path().join(group);
+        fs::create_dir_all(&folder).unwrap();
+        for i in 0..30 {
+            fs::write(folder.join(format!("report-{i:02}.txt")), "").unwrap();
+        }
+    }
+    let options = SearchOptions {
+        max_results: Some(5),
+        ..name_search(&root, "report")
+    };
+
+    let expected: Vec<String> = (0..5)
+        .map(|i| {
+            dir.path()
+                .join("a")
+                .join(format!("report-{i:02}.txt"))
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    let paths = |results: Vec<SearchResult>| -> Vec<String> {
+        results.into_iter().map(|r| r.path).collect()
+    };
+    for _ in 0..5 {
+        assert_eq!(
+            paths(search_files_sync(&options).expect("search")),
+            expected
+        );
+        let streamed = search_files_cancellable_sync(&options, "limit", &AtomicBool::new(false))
+            .expect("search");
+        assert_eq!(paths(streamed), expected);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+#[allow(deprecated)]
+fn search_files_follows_symlinks_without_looping() {
+    use std::os::unix::fs::symlink;
+
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    symlink(dir.path(), dir.path().join("subdir").join("loop")).unwrap();
+
+    let options = SearchOptions {
+        follow_symlinks: true,
+        max_depth: Some(32),
+        ..name_search(&root, "readme")
+    };
+    let results = search_files_sync(&options).expect("search");
+    assert_eq!(results.len(), 1);
+}
+
+fn filtered_search(root: &str, query: &str, filters: SearchFilters) -> Vec<String> {
+    let options = SearchOptions {
+        filters: Some(filters),
+        ..name_search(root, query)
+    };
+    search_files_sync(&options)
+        .expect("search")
+        .into_iter()
+        .map(|r| r.name)
+        .collect()
+}
+
+#[test]
+fn search_filters_by_size_kind_and_emptiness() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    fs::write(dir.path().join("empty.txt"), "").unwrap();
+
+    let large = filtered_search(
+        &root,
+        "",
+        SearchFilters {
+            min_size: Some(12),
+            kind: Some(EntryKind::File),
+            ..Default::default()
+        },
+    );
+    assert_eq!(large, vec!["readme.txt", "nested.txt"]);
+
+    let mut empty = filtered_search(
+        &root,
+        "",
+        SearchFilters {
+            empty: Some(true),
+            ..Default::default()
+        },
+    );
+    empty.sort();
+    assert_eq!(empty, vec!["empty-dir", "empty.txt"]);
+
+    let dirs = filtered_search(
+        &root,
+        "dir",
+        SearchFilters {
+            kind: Some(EntryKind::Directory),
+            ..Default::default()
+        },
+    );
+    assert_eq!(dirs.len(), 2);
+}
+
+#[test]
+fn search_filters_by_relative_and_absolute_mtime() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+
+    let recent = filtered_search(
+        &root,
+        "readme",
+        SearchFilters {
+            modified: Some(TimeRange {
+                after: Some(TimeBound::Relative {
+                    seconds: 7 * 86_400,
+                }),
+                before: None,
+            }),
+            ..Default::default()
+        },
+    );
+    assert_eq!(recent, vec!["readme.txt"]);
+
+    let ancient = filtered_search(
+        &root,
+        "readme",
+        SearchFilters {
+            modified: Some(TimeRange {
+                after: None,
+                before: Some(TimeBound::Absolute {
+                    timestamp: 946_684_800,
+                }),
+            }),
+            ..Default::default()
+        },
+    );
+    assert!(ancient.is_empty());
+}
+
+#[test]
+fn search_filters_apply_to_content_search() {
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+
+    let options = SearchOptions {
+        query: "nested".to_string(),
+        search_path: root,
+        search_content: true,
+        filters: Some(SearchFilters {
+            max_size: Some(11),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let results = search_files_sync(&options).expect("search");
+    let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+    // "root nested" is 11 bytes; "nested content" is 14 and the directory has no match.
+    assert_eq!(names, vec!["nested.txt"]);
+    assert!(!results[0].path.contains("subdir"));
+}
+
+#[cfg(unix)]
+#[test]
+fn search_filters_by_hidden_executable_and_owner() {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let (dir, root) = setup_temp_workspace();
+    create_fixture_tree(dir.path());
+    let script = dir.path().join("run.sh");
+    fs::write(&script, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(dir.path().join(".env"), "KEY=1").unwrap();
+
+    let executables = filtered_search(
+        &root,
+        "",
+        SearchFilters {
+            executable: Some(true),
+            ..Default::default()
+        },
+    );
+    assert_eq!(executables, vec!["run.sh"]);
+
+    let hidden = filtered_search(
+        &root,
+        "env",
+        SearchFilters {
+            hidden: Some(true),
+            ..Default::default()
+        },
+    );
+    assert_eq!(hidden, vec![".env"]);
+
+    let uid = fs::metadata(&script).unwrap().uid();
+    let owned = filtered_search(
+        &root,
+        "run",
+        SearchFilters {
+            owner: Some(uid.to_string()),
+            ..Default::default()
+        },
+    );
+    assert_eq!(owned, vec!["run.sh"]);
+
+    let options = SearchOptions {
+        filters: Some(SearchFilters {
+            owner: Some("no-such-user-xyz".to_string()),
+            ..Default::default()
+        }),
+        ..name_search(&root, "run")
+    };
+    assert!(search_files_sync(&options).is_err());
+}
+
+fn query_search(root: &str, input: &str, search_content: bool) -> Vec<String> {
+    let base = SearchOptions {
+        search_path: root.to_string(),
+        search_content,
+        ..Default::default()
+    };
+    let options = parse_search_query_sync(input, base).expect("query parses");
+    let mut names: Vec<String> = search_files_sync(&options)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn search_files_evaluates_parsed_query() {
+    let (dir, root) = setup_temp_workspace();
+    fs::create_dir_all(dir.path().join("target")).unwrap();
+    fs::write(dir.path().join("notes.txt"), "remember the todo list").unwrap();
+    fs::write(dir.path().join("readme.md"), "nothing here").unwrap();
+    fs::write(dir.path().join("target").join("build.txt"), "todo").unwrap();
+    fs::write(dir.path().join("big.txt"), "x".repeat(4096)).unwrap();
+
+    assert_eq!(
+        query_search(&root, "ext:txt -path:target", false),
+        vec!["big.txt", "notes.txt"]
+    );
+    assert_eq!(
+        query_search(&root, "(name:*.md OR size:>2k) NOT is:dir", false),
+        vec!["big.txt", "readme.md"]
+    );
+    assert_eq!(
+        query_search(&root, "content:todo -path:target", false),
+        vec!["notes.txt"]
+    );
+
+    let base = SearchOptions {
+        search_path: root.clone(),
+        search_content: true,
+        ..Default::default()
+    };
+    let options = parse_search_query_sync(r#""todo" ext:txt"#, base).unwrap();
+    assert_eq!(options.query, r#""todo" ext:txt"#);
+    let results = search_files_sync(&options).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.matches.len() == 1));
+}
+
+#[test]
+fn search_query_paths_are_relative_to_the_root() {
+    let (dir, _root) = setup_temp_workspace();
+    // The root itself sits under a directory named `target`.
+    let workspace = dir.path().join("target").join("ws");
+    fs::create_dir_all(workspace.join("src").join("util")).unwrap();
+    fs::create_dir_all(workspace.join("tests")).unwrap();
+    fs::write(workspace.join("src").join("main.rs"), "").unwrap();
+    fs::write(workspace.join("src").join("util").join("deep.rs"), "").unwrap();
+    fs::write(workspace.join("tests").join("it.rs"), "").unwrap();
+    fs::write(workspace.join("notes.txt"), "").unwrap();
+    let root = workspace.to_string_lossy().to_string();
+
+    assert_eq!(query_search(&root, "path:src/*.rs", false), vec!["main.rs"]);
+    assert_eq!(query_search(&root, "path:**/tests/*", false), vec!["it.rs"]);
+    assert_eq!(
+        query_search(&root, "ext:rs -path:src/**", false),
+        vec!["it.rs"]
+    );
+    assert_eq!(
+        query_search(&root, "path:util ext:rs", false),
+        vec!["deep.rs"]
+    );
+    assert_eq!(
+        query_search(&root, "-path:target ext:txt", false),
+        vec!["notes.txt"]
+    );
+}
+
+#[test]
+fn parse_search_query_reports_error_span() {
+    let err = parse_search_query_sync("ext:rs (size:>1M", SearchOptions::default()).unwrap_err();
+    assert_eq!((err.start, err.end), (7, 8));
+    assert!(err.message.contains("parenthesis"));
+}
+
+fn fuzzy_options(root: &str, query: &str) -> FuzzySearchOptions {
+    FuzzySearchOptions {
+        query: query.to_string(),
+        search_path: root.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn fuzzy_search_ranks_basename_and_shallow_matches_first() {
+    let (dir, root) = setup_temp_workspace();
+    let deep = dir.path().join("src").join("components").join("widgets");
+    fs::create_dir_all(&deep).unwrap();
+    fs::write(dir.path().join("main.rs"), "").unwrap();
+    fs::write(deep.join("main.rs"), "").unwrap();
+    fs::write(dir.path().join("src").join("mapping.rs"), "").unwrap();
+    fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+    let state = SearchState::new();
+    let results = fuzzy_search_sync(&fuzzy_options(&root, "mainrs"), &state).unwrap();
+    let relative: Vec<&str> = results.iter().map(|r| r.relative_path.as_str()).collect();
+
+    assert_eq!(relative[0], "main.rs");
+    assert!(relative.iter().all(|p| !p.contains("notes")));
+    assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+    assert_eq!(results[0].indices, vec![0, 1, 2, 3, 5, 6]);
+
+    // Highlight indices of deeper matches point into the file name.
+    let nested = results
+        .iter()
+        .find(|r| {
+            r.relative_path.ends_with("widgets/main.rs")
+                || r.relative_path.ends_with("widgets\\main.rs")
+        })
+        .expect("nested main.rs");
+    let name_start = (nested.relative_path.len() - "main.rs".len()) as u32;
+    assert!(nested.indices.iter().all(|&i| i >= name_start));
+}
+
+#[test]
+fn fuzzy_search_boosts_recent_paths() {
+    let (dir, root) = setup_temp_workspace();
+    fs::create_dir_all(dir.path().join("a").join("b")).unwrap();
+    fs::write(dir.path().join("report.txt"), "").unwrap();
+    let recent = dir.path().join("a").join("b").join("report.txt");
+    fs::write(&recent, "").unwrap();
+
+    let state = SearchState::new();
+    let plain = fuzzy_search_sync(&fuzzy_options(&root, "report"), &state).unwrap();
+    assert_eq!(plain[0].relative_path, "report.txt");
+
+    let options = FuzzySearchOptions {
+        recent_paths: Some(vec![recent.to_string_lossy().to_string()]),
+        ..fuzzy_options(&root, "report")
+    };
+    let boosted = fuzzy_search_sync(&options, &state).unwrap();
+    assert_eq!(boosted[0].path, recent.to_string_lossy());
+}
+
+#[test]
+fn fuzzy_search_handles_empty_query_and_missing_root() {
+    let (_dir, root) = setup_temp_workspace();
+    let state = SearchState::new();
+
+    assert!(fuzzy_search_sync(&fuzzy_options(&root, "  "), &state)
+        .unwrap()
+        .is_empty());
+    assert!(fuzzy_search_sync(&fuzzy_options(&format!("{root}/missing"), "x"), &state).is_err());
+}
+
+#[test]
+fn fuzzy_search_sees_files_created_and_deleted_after_caching() {
+    let (dir, root) = setup_temp_workspace();
+    let nested = dir.path().join("docs").join("drafts");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(dir.path().join("budget.xlsx"), "").unwrap();
+
+    let state = SearchState::new();
+    let names = |query: &str| -> Vec<String> {
+        fuzzy_search_sync(&fuzzy_options(&root, query), &state)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect()
+    };
+    assert_eq!(names("budget"), vec!["budget.xlsx"]);
+    assert!(names("roadmap").is_empty());
+
+    // Changes reach the cache through its watcher, well within the TTL.
+    fs::write(nested.join("roadmap.md"), "").unwrap();
+    fs::remove_file(dir.path().join("budget.xlsx")).unwrap();
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while names("roadmap").is_empty() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert_eq!(names("roadmap"), vec!["roadmap.md"]);
+    assert!(names("budget").is_empty());
+}
+
+#[test]
+fn fuzzy_search_reports_utf16_indices() {
+    let (dir, root) = setup_temp_workspace();
+    fs::write(dir.path().join("😀note.md"), "").unwrap();
+
+    let results = fuzzy_search_sync(&fuzzy_options(&root, "note"), &SearchState::new()).unwrap();
+    assert_eq!(results[0].indices, vec![2, 3, 4, 5]);
+}
+
+fn write_office_fixtures(root: &Path) {
+    fs::write(
+        root.join("letter.docx"),
+        docx_bytes(&["Dear team,", "The invoice is attached."]),
+    )
+    .unwrap();
+
+    let odt = r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"><office:body><office:text><text:h>Minutes</text:h><text:p>Nobody mentioned the <text:span>invoice</text:span>.</text:p></office:text></office:body></office:document-content>"#;
+    fs::write(
+        root.join("minutes.odt"),
+        zip_bytes(&[("content.xml", odt.as_bytes())]),
+    )
+    .unwrap();
+
+    let slide = |text: &str| {
+        format!(
+            r#"<p:sld xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><p:cSld><p:spTree><p:sp><p:txBody><a:p><a:r><a:t>{text}</a:t></a:r></a:p></p:txBody></p:sp></p:spTree></p:cSld></p:sld>"#
+        )
+    };
+    let (first, second) = (slide("Agenda"), slide("Overdue invoice review"));
+    fs::write(
+        root.join("deck.pptx"),
+        zip_bytes(&[
+            ("ppt/slides/slide1.xml", first.as_bytes()),
+            ("ppt/slides/slide2.xml", second.as_bytes()),
+        ]),
+    )
+    .unwrap();
+
+    let workbook = r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Ledger" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+    let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+    let sheet = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>Item</t></is></c></row><row r="3"><c r="B3" t="inlineStr"><is><t>Unpaid invoice</t></is></c></row></sheetData></worksheet>"#;
+    fs::write(
+        root.join("ledger.xlsx"),
+        zip_bytes(&[
+            ("xl/workbook.xml", workbook.as_bytes()),
+            ("xl/_rels/workbook.xml.rels", rels.as_bytes()),
+            ("xl/worksheets/sheet1.xml", sheet.as_bytes()),
+        ]),
+    )
+    .unwrap();
+
+    fs::write(
+        root.join("scan.pdf"),
+        pdf_bytes(&["Cover page", "Invoice total due"]),
+    )
+    .unwrap();
+}
+
+fn content_search(root: &str, query: &str) -> Vec<SearchResult> {
+    search_files_sync(&SearchOptions {
+        query: query.to_string(),
+        search_path: root.to_string(),
+        search_content: true,
+        ..Default::default()
+    })
+    .expect("search")
+}
+
+fn first_location(results: &[SearchResult], name: &str) -> Option<MatchLocation> {
+    results
+        .iter()
+        .find(|r| r.name == name)
+        .and_then(|r| r.matches.first())
+        .and_then(|m| m.location.clone())
+}
+
+#[test]
+fn content_search_reads_office_documents_and_pdfs() {
+    let (dir, root) = setup_temp_workspace();
+    write_office_fixtures(dir.path());
+
+    let results = content_search(&root, "invoice");
+    let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "deck.pptx",
+            "ledger.xlsx",
+            "letter.docx",
+            "minutes.odt",
+            "scan.pdf"
+        ]
+    );
+
+    assert_eq!(
+        first_location(&results, "letter.docx"),
+        Some(MatchLocation::Paragraph { index: 2 })
+    );
+    assert_eq!(
+        first_location(&results, "minutes.odt"),
+        Some(MatchLocation::Paragraph { index: 2 })
+    );
+    assert_eq!(
+        first_location(&results, "deck.pptx"),
+        Some(MatchLocation::Slide { number: 2 })
+    );
+    assert_eq!(
+        first_location(&results, "ledger.xlsx"),
+        Some(MatchLocation::Cell {
+            sheet: "Ledger".to_string(),
+            cell: "B3".to_string(),
+        })
+    );
+    assert_eq!(
+        first_location(&results, "scan.pdf"),
+        Some(MatchLocation::Page { number: 2 })
+    );
+
+    let letter = results.iter().find(|r| r.name == "letter.docx").unwrap();
+    assert_eq!(letter.matches[0].line_content, "The invoice is attached.");
+    assert_eq!(letter.matches[0].match_start, 4);
+
+    // Raw zipped XML no longer produces matches on markup.
+    assert!(content_search(&root, "w:document").is_empty());
+}
+
+#[test]
+fn content_search_numbers_slides_in_presentation_order() {
+    let (dir, root) = setup_temp_workspace();
+    let slide = |text: &str| {
+        format!(
+            r#"<p:sld xmlns:p="p" xmlns:a="a"><p:cSld><p:spTree><p:sp><p:txBody><a:p><a:r><a:t>{text}</a:t></a:r></a:p></p:txBody></p:sp></p:spTree></p:cSld></p:sld>"#
+        )
+    };
+    // slide2.xml was moved to the front of the deck.
+    let presentation = br#"<p:presentation xmlns:p="p" xmlns:r="r"><p:sldIdLst><p:sldId id="256" r:id="rId2"/><p:sldId id="257" r:id="rId1"/></p:sldIdLst></p:presentation>"#;
+    let rels = br#"<Relationships><Relationship Id="rId1" Type="slide" Target="slides/slide1.xml"/><Relationship Id="rId2" Type="slide" Target="slides/slide2.xml"/></Relationships>"#;
+    let (first, second) = (slide("Closing remarks"), slide("Opening remarks"));
+    fs::write(
+        dir.path().join("deck.pptx"),
+        zip_bytes(&[
+            ("ppt/presentation.xml", presentation.as_slice()),
+            ("ppt/_rels/presentation.xml.rels", rels.as_slice()),
+            ("ppt/slides/slide1.xml", first.as_bytes()),
+            ("ppt/slides/slide2.xml", second.as_bytes()),
+        ]),
+    )
+    .unwrap();
+
+    let results = content_search(&root, "Opening");
+    assert_eq!(
+        first_location(&results, "deck.pptx"),
+        Some(MatchLocation::Slide { number: 1 })
+    );
+    let results = content_search(&root, "Closing");
+    assert_eq!(
+        first_location(&results, "deck.pptx"),
+        Some(MatchLocation::Slide { number: 2 })
+    );
+}
+
+#[test]
+fn content_search_descends_into_archives() {
+    let (dir, root) = setup_temp_workspace();
+    fs::write(
+        dir.path().join("bundle.zip"),
+        zip_bytes(&[
+            (
+                "docs/readme.txt",
+                b"first line\nthe needle is here\n".as_slice(),
+            ),
+            ("docs/report.docx", &docx_bytes(&["Another needle"])),
+            ("other.txt", b"nothing to see".as_slice()),
+        ]),
+    )
+    .unwrap();
+
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    let data = b"a needle in a tarball";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    tar.append_data(&mut header, "src/notes.md", data.as_slice())
+        .unwrap();
+    fs::write(
+        dir.path().join("sources.tar.gz"),
+        tar.into_inner().unwrap().finish().unwrap(),
+    )
+    .unwrap();
+
+    let results = content_search(&root, "needle");
+    let paths: Vec<_> = results.iter().map(|r| r.path.as_str()).collect();
+    let bundle = dir.path().join("bundle.zip").to_string_lossy().to_string();
+    let sources = dir
+        .path()
+        .join("sources.tar.gz")
+        .to_string_lossy()
+        .to_string();
+    assert_eq!(
+        paths,
+        vec![
+            format!("{bundle}!/docs/readme.txt"),
+            format!("{bundle}!/docs/report.docx"),
+            format!("{sources}!/src/notes.md"),
+        ]
+    );
+
+    assert_eq!(results[0].name, "readme.txt");
+    assert!(!results[0].is_dir);
+    assert_eq!(results[0].matches[0].line_number, 2);
+    assert_eq!(
+        results[1].matches[0].location,
+        Some(MatchLocation::Paragraph { index: 1 })
+    );
+
+    // Extension filters apply to the files inside the archive.
+    let filtered = search_files_sync(&SearchOptions {
+        query: "needle".to_string(),
+        search_path: root.clone(),
+        search_content: true,
+        file_extensions: Some(vec!["md".to_string()]),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].name, "notes.md");
+
+    // Inner names match like regular file names.
+    let by_name = content_search(&root, "other.txt");
+    assert_eq!(by_name.len(), 1);
+    assert!(by_name[0].path.ends_with("!/other.txt"));
+}
+
+fn replace_options(root: &str, query: &str, replacement: &str) -> ReplaceOptions {
+    ReplaceOptions {
+        search: SearchOptions {
+            query: query.to_string(),
+            search_path: root.to_string(),
+            search_content: true,
+            ..Default::default()
+        },
+        replacement: replacement.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn replace_dry_run_previews_regex_replacements() {
+    let (dir, root) = setup_temp_workspace();
+    let config = dir.path().join("config.ini");
+    fs::write(&config, "host = alpha\nport = 80\n\nname = beta\n").unwrap();
+    fs::write(dir.path().join("blob.bin"), b"name = x\0").unwrap();
+    let state = SearchState::new();
+
+    let options = ReplaceOptions {
+        regex: true,
+        dry_run: true,
+        ..replace_options(&root, r"^(\w+) = (\w+)$", "${1}: $2")
+    };
+    let report = replace_in_files_sync(&options, &state).unwrap();
+
+    assert_eq!(report.files.len(), 1);
+    assert!(report.undo_id.is_none());
+    assert_eq!(report.total_replaced, 3);
+    let file = &report.files[0];
+    assert_eq!(file.replaced, 3);
+    let lines: Vec<_> = file.matches.iter().map(|m| m.line_number).collect();
+    assert_eq!(lines, vec![1, 2, 4]);
+    assert_eq!(file.matches[2].matched, "name = beta");
+    assert_eq!(file.matches[2].replacement, "name: beta");
+    assert!(file.diff.contains("\n-host = alpha\n"));
+    assert!(file.diff.contains("\n+host: alpha\n"));
+
+    // Nothing is written in a dry run.
+    assert_eq!(
+        fs::read_to_string(&config).unwrap(),
+        "host = alpha\nport = 80\n\nname = beta\n"
+    );
+
+    let invalid = ReplaceOptions {
+        regex: true,
+        ..replace_options(&root, "(unclosed", "x")
+    };
+    assert!(replace_in_files_sync(&invalid, &state).is_err());
+}
+
+#[test]
+fn replace_keeps_the_first_files_by_path_and_reports_the_cut() {
+    let (dir, root) = setup_temp_workspace();
+    for name in ["e.txt", "c.txt", "a.txt", "d.txt", "b.txt"] {
+        fs::write(dir.path().join(name), "todo\n").unwrap();
+    }
+    let state = SearchState::new();
+
+    let mut options = replace_options(&root, "todo", "done");
+    options.search.max_results = Some(2);
+    let report = replace_in_files_sync(&options, &state).unwrap();
+    assert!(report.truncated);
+    let names: Vec<_> = report
+        .files
+        .iter()
+        .map(|f| Path::new(&f.path).file_name().unwrap().to_owned())
+        .collect();
+    assert_eq!(names, ["a.txt", "b.txt"]);
+    assert_eq!(
+        fs::read_to_string(dir.path().join("c.txt")).unwrap(),
+        "todo\n"
+    );
+
+    options.search.max_results = None;
+    let rest = replace_in_files_sync(&options, &state).unwrap();
+    assert!(!rest.truncated);
+    assert_eq!(rest.total_replaced, 3);
+
+    // A query expression or a name-only search isn't taken as literal text.
+    let parsed = parse_search_query_sync("todo ext:txt", options.search.clone()).unwrap();
+    let expression = ReplaceOptions {
+        search: parsed,
+        ..replace_options(&root, "todo", "done")
+    };
+    assert!(replace_in_files_sync(&expression, &state).is_err());
+    let mut names_only = replace_options(&root, "done", "todo");
+    names_only.search.search_content = false;
+    assert!(replace_in_files_sync(&names_only, &state).is_err());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+        "done\n"
+    );
+}
+
+#[test]
+fn replace_rejects_selections_outside_the_root() {
+    let (dir, _) = setup_temp_workspace();
+    let root = dir.path().join("project");
+    fs::create_dir(&root).unwrap();
+    let outside = dir.path().join("outside.txt");
+    fs::write(&outside, "todo\n").unwrap();
+    let state = SearchState::new();
+
+    let select = |path: PathBuf| ReplaceOptions {
+        selection: Some(vec![ReplaceSelection {
+            path: path.to_string_lossy().into_owned(),
+            indices: vec![0],
+            version: None,
+        }]),
+        ..replace_options(&root.to_string_lossy(), "todo", "done")
+    };
+    let escaping = root.join("..").join("outside.txt");
+    assert!(replace_in_files_sync(&select(escaping), &state).is_err());
+    assert_eq!(fs::read_to_string(&outside).unwrap(), "todo\n");
+
+    #[cfg(unix)]
+    {
+        let link = root.join("link.txt");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+        assert!(replace_in_files_sync(&select(link.clone()), &state).is_err());
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "todo\n");
+
+        // When the search follows links, the target is rewritten and the link kept.
+        let mut following = select(link.clone());
+        following.search.follow_symlinks = true;
+        let report = replace_in_files_sync(&following, &state).unwrap();
+        assert_eq!(report.total_replaced, 1);
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "done\n");
+    }
+}
+
+#[test]
+fn replace_applies_selection_and_can_be_undone() {
+    let (dir, root) = setup_temp_workspace();
+    let notes = dir.path().join("notes.txt");
+    let other = dir.path().join("other.txt");
+    fs::write(&notes, "TODO one\ntodo two\nTODO three\n").unwrap();
+    fs::write(&other, "no markers here but todo\n").unwrap();
+    let state = SearchState::new();
+
+    let preview = replace_in_files_sync(
+        &ReplaceOptions {
+            dry_run: true,
+            ..replace_options(&root, "todo", "DONE")
+        },
+        &state,
+    )
+    .unwrap();
+    assert_eq!(preview.total_replaced, 4);
+    let notes_preview = preview
+        .files
+        .iter()
+        .find(|f| f.path.ends_with("notes.txt"))
+        .unwrap();
+
+    // Replace only the first and last match in notes.txt; other.txt is not selected.
+    let apply = ReplaceOptions {
+        selection: Some(vec![ReplaceSelection {
+            path: notes_preview.path.clone(),
+            indices: vec![0, 2],
+            version: Some(notes_preview.version.clone()),
+        }]),
+        ..replace_options(&root, "todo", "DONE")
+    };
+    let report = replace_in_files_sync(&apply, &state).unwrap();
+    assert_eq!(report.total_replaced, 2);
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "DONE one\ntodo two\nDONE three\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&other).unwrap(),
+        "no markers here but todo\n"
+    );
+
+    // The preview's version no longer matches the file.
+    let stale = replace_in_files_sync(&apply, &state).unwrap();
+    assert_eq!(stale.total_replaced, 0);
+    assert!(stale.files[0].error.is_some());
+
+    let undo_id = report.undo_id.expect("undo id");
+    let undone = undo_replace_sync(&undo_id, &state).unwrap();
+    assert_eq!(undone.restored.len(), 1);
+    assert!(undone.conflicts.is_empty());
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "TODO one\ntodo two\nTODO three\n"
+    );
+    assert!(undo_replace_sync(&undo_id, &state).is_err());
+
+    // Edits made after a replacement are never overwritten by undo.
+    let report = replace_in_files_sync(&replace_options(&root, "todo", "done"), &state).unwrap();
+    assert_eq!(report.files.len(), 2);
+    fs::write(&other, "edited afterwards\n").unwrap();
+    let undone = undo_replace_sync(&report.undo_id.unwrap(), &state).unwrap();
+    assert_eq!(undone.restored.len(), 1);
+    assert_eq!(undone.conflicts.len(), 1);
+    assert!(undone.conflicts[0].ends_with("other.txt"));
+    assert_eq!(fs::read_to_string(&other).unwrap(), "edited afterwards\n");
+}
@@ -1,9 +1,17 @@
 mod common;
 
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
 use common::setup_temp_workspace;
 use file_manager_lib::commands::watcher::{
-    unwatch_all_sync, unwatch_directory_sync, validate_watch_directory_path, WatcherState,
+    tail_file_sync, untail_file_sync, unwatch_all_sync, unwatch_directory_sync,
+    validate_watch_directory_path, WatcherState,
 };
+use file_manager_lib::models::FileTailEvent;
 
 #[test]
 fn watch_and_unwatch_lifecycle() {
@@ -33,3 +41,71 @@ fn watch_directory_rejects_empty_path() {
     assert!(err.contains("Empty") || err.contains("empty"));
     assert_eq!(state.active_watcher_count(), 0);
 }
+
+fn append(path: &Path, text: &str) {
+    let mut file = OpenOptions::new().append(true).open(path).expect("open");
+    file.write_all(text.as_bytes()).expect("append");
+}
+
+/// Collects tail events until one matches, since a write may be reported in pieces.
+fn wait_for(rx: &Receiver<FileTailEvent>, done: impl Fn(&FileTailEvent) -> bool) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut lines = Vec::new();
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        let event = rx.recv_timeout(left).expect("tail event");
+        lines.extend(event.lines.iter().cloned());
+        if done(&event) {
+            return lines;
+        }
+    }
+    panic!("no matching tail event");
+}
+
+#[test]
+fn tail_file_follows_appends_truncation_and_rotation() {
+    let (dir, _root) = setup_temp_workspace();
+    let log = dir.path().join("app.log");
+    fs::write(&log, "old line\n").expect("write");
+    let log_str = log.to_string_lossy().to_string();
+
+    let state = WatcherState::new();
+    let (tx, rx) = channel();
+    tail_file_sync(&log_str, &state, move |event| {
+        let _ = tx.send(event);
+    })
+    .expect("tail");
+    assert_eq!(state.active_tail_count(), 1);
+
+    append(&log, "a\r\nb\n");
+    let lines = wait_for(&rx, |e| e.lines.last().is_some_and(|l| l == "b"));
+    assert_eq!(lines, vec!["a", "b"]);
+
+    // A line is only reported once its newline is written.
+    append(&log, "c");
+    append(&log, "\n");
+    assert_eq!(wait_for(&rx, |e| !e.lines.is_empty()), vec!["c"]);
+
+    fs::write(&log, "").expect("truncate");
+    wait_for(&rx, |e| e.truncated);
+
+    fs::rename(&log, dir.path().join("app.log.1")).expect("rotate");
+    fs::write(&log, "fresh\n").expect("recreate");
+    let lines = wait_for(&rx, |e| e.lines.iter().any(|l| l == "fresh"));
+    assert_eq!(lines, vec!["fresh"]);
+
+    untail_file_sync(&log_str, &state).expect("untail");
+    assert_eq!(state.active_tail_count(), 0);
+    while rx.try_recv().is_ok() {}
+    append(&log, "ignored\n");
+    assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+}
+
+#[test]
+fn tail_file_rejects_directories_and_relative_paths() {
+    let (_dir, root) = setup_temp_workspace();
+    let state = WatcherState::new();
+    assert!(tail_file_sync(&root, &state, |_| {}).is_err());
+    assert!(tail_file_sync("relative.log", &state, |_| {}).is_err());
+    assert!(tail_file_sync("", &state, |_| {}).is_err());
+    assert_eq!(state.active_tail_count(), 0);
+}
@@ -6,7 +6,7 @@ import { useSearchStore } from "../../model/store"
 import { useSearchWithProgress } from "../useSearchWithProgress"
 
 vi.mock("@/shared/api/tauri/client", () => ({
-  tauriClient: { searchFilesStream: vi.fn() },
+  tauriClient: { searchFilesStream: vi.fn(), cancelSearch: vi.fn(async () => true) },
 }))
 
 vi.mock("@/shared/api/tauri", () => ({
@@ -86,16 +86,19 @@ describe("useSearchWithProgress", () => {
     })
 
     useSearchStore.setState({ query: "readme", searchPath: "C:/test", shouldCancel: false })
-    vi.mocked(tauriClient.searchFilesStream).mockImplementation(async () => {
+    vi.mocked(tauriClient.searchFilesStream).mockImplementation(async (_options, searchId) => {
       progressCb?.({
         event: "search-progress",
         id: 1,
-        payload: { scanned: 10, found: 1, current_path: "C:/test" },
+        payload: { search_id: searchId, scanned: 10, found: 1, current_path: "C:/test" },
       })
       batchCb?.({
         event: "search-batch",
         id: 2,
-        payload: [{ path: "C:/test/readme.txt", name: "readme.txt", is_dir: false, matches: [] }],
+        payload: {
+          search_id: searchId,
+          results: [{ path: "C:/test/readme.txt", name: "readme.txt", is_dir: false, matches: [] }],
+        },
       })
       return [{ path: "C:/test/readme.txt", name: "readme.txt", is_dir: false, matches: [] }]
     })
@@ -112,4 +115,69 @@ describe("useSearchWithProgress", () => {
 
     expect(toast.success).toHaveBeenCalled()
   })
+
+  it("ignores batch events from superseded searches", async () => {
+    const { tauriEvents } = await import("@/shared/api/tauri")
+    let batchCb: EventCallback<SearchBatchEvent> | null = null
+
+    vi.mocked(tauriEvents.searchBatch).mockImplementation(async (cb) => {
+      batchCb = cb
+      return () => {}
+    })
+
+    useSearchStore.setState({ query: "readme", searchPath: "C:/test", shouldCancel: false })
+    vi.mocked(tauriClient.searchFilesStream).mockImplementation(async () => {
+      batchCb?.({
+        event: "search-batch",
+        id: 1,
+        payload: {
+          search_id: "stale-search",
+          results: [{ path: "C:/test/old.txt", name: "old.txt", is_dir: false, matches: [] }],
+        },
+      })
+      return []
+    })
+
+    vi.useFakeTimers()
+    const { result } = renderHook(() => useSearchWithProgress())
+    await act(async () => {
+      await result.current.search()
+    })
+    act(() => {
+      vi.advanceTimersByTime(200)
+    })
+    vi.useRealTimers()
+
+    expect(useSearchStore.getState().results).toEqual([])
+  })
+
+  it("superseded search rejection does not show an error toast", async () => {
+    useSearchStore.setState({ query: "readme", searchPath: "C:/test", shouldCancel: false })
+
+    let rejectFirst: ((reason: unknown) => void) | null = null
+    vi.mocked(tauriClient.searchFilesStream)
+      .mockImplementationOnce(
+        () =>
+          new Promise((_resolve, reject) => {
+            rejectFirst = reject
+          }),
+      )
+      .mockResolvedValueOnce([])
+
+    const { result } = renderHook(() => useSearchWithProgress())
+    let first: Promise<void> = Promise.resolve()
+    act(() => {
+      first = result.current.search()
+    })
+    await waitFor(() => {
+      expect(rejectFirst).not.toBeNull()
+    })
+    await act(async () => {
+      await result.current.search()
+      rejectFirst?.("Operation cancelled")
+      await first
+    })
+
+    expect(toast.error).not.toHaveBeenCalled()
+  })
 })
@@ -11,6 +11,16 @@ export function useSearchWithProgress() {
   const lastUpdateRef = useRef<number>(0)
   const pendingResultsRef = useRef<SearchResult[]>([])
   const flushTimeoutRef = useRef<number | null>(null)
+  const searchIdRef = useRef<string | null>(null)
+  const shouldCancel = useSearchStore((s) => s.shouldCancel)
+
+  // Stop the backend walk when the user cancels from the UI.
+  useEffect(() => {
+    const searchId = searchIdRef.current
+    if (shouldCancel && searchId) {
+      tauriClient.cancelSearch(searchId).catch(() => {})
+    }
+  }, [shouldCancel])
 
   // Cleanup listener on unmount
   useEffect(() => {
@@ -59,6 +69,11 @@ export function useSearchWithProgress() {
     }
     pendingResultsRef.current = []
 
+    // New search id for this run. The backend cancels the previous search from this window,
+    // and events from superseded searches are ignored below.
+    const searchId = `${Date.now()}-${Math.random().toString(16).slice(2)}`
+    searchIdRef.current = searchId
+
     setIsSearching(true)
     setProgress({ scanned: 0, found: 0, currentPath: searchPath })
     setResults([])
@@ -66,7 +81,7 @@ export function useSearchWithProgress() {
     try {
       // Subscribe to progress events with throttle
       const unlistenProgress = await tauriEvents.searchProgress((event) => {
-        if (useSearchStore.getState().shouldCancel) {
+        if (event.payload.search_id !== searchId || useSearchStore.getState().shouldCancel) {
           return
         }
         const now = Date.now()
@@ -82,11 +97,11 @@ export function useSearchWithProgress() {
       })
 
       const unlistenBatch = await tauriEvents.searchBatch((event) => {
-        if (useSearchStore.getState().shouldCancel) {
+        if (event.payload.search_id !== searchId || useSearchStore.getState().shouldCancel) {
           return
         }
 
-        pendingResultsRef.current.push(...event.payload)
+        pendingResultsRef.current.push(...event.payload.results)
 
         // Soft-throttle UI updates for results to avoid too many re-renders.
         if (flushTimeoutRef.current === null) {
@@ -112,30 +127,38 @@ export function useSearchWithProgress() {
         file_extensions: null,
       }
 
-      const files = await tauriClient.searchFilesStream(options)
+      const files = await tauriClient.searchFilesStream(options, searchId)
 
-      if (!useSearchStore.getState().shouldCancel) {
+      if (searchIdRef.current === searchId && !useSearchStore.getState().shouldCancel) {
         // Ensure final list is consistent with backend (e.g., if some batches were throttled).
         setResults(files)
         toast.success(`Найдено ${files.length} файлов`)
       }
     } catch (error) {
-      toast.error(`Ошибка поиска: ${String(error)}`)
+      // Superseded or cancelled searches reject with "Operation cancelled"; that is not an error.
+      if (searchIdRef.current === searchId && !useSearchStore.getState().shouldCancel) {
+        toast.error(`Ошибка поиска: ${String(error)}`)
+      }
     } finally {
-      setIsSearching(false)
-      setProgress(null)
+      // When superseded, a newer search owns the listeners and progress state.
+      if (searchIdRef.current === searchId) {
+        searchIdRef.current = null
 
-      // Clear listener
-      for (const unlisten of unlistenRefs.current) {
-        unlisten()
-      }
-      unlistenRefs.current = []
+        setIsSearching(false)
+        setProgress(null)
 
-      if (flushTimeoutRef.current !== null) {
-        window.clearTimeout(flushTimeoutRef.current)
-        flushTimeoutRef.current = null
+        // Clear listener
+        for (const unlisten of unlistenRefs.current) {
+          unlisten()
+        }
+        unlistenRefs.current = []
+
+        if (flushTimeoutRef.current !== null) {
+          window.clearTimeout(flushTimeoutRef.current)
+          flushTimeoutRef.current = null
+        }
+        pendingResultsRef.current = []
       }
-      pendingResultsRef.current = []
     }
   }, [performance.maxSearchResults])
 
@@ -29,6 +29,7 @@ const mocks = vi.hoisted(() => ({
   pathExists: vi.fn(),
   searchFiles: vi.fn(),
   searchFilesStream: vi.fn(),
+  cancelSearch: vi.fn(),
   searchByName: vi.fn(),
   searchContent: vi.fn(),
   getFilePreview: vi.fn(),
@@ -68,6 +69,7 @@ describe("tauriClient coverage", () => {
     mocks.pathExists.mockResolvedValue(ok(true))
     mocks.searchFiles.mockResolvedValue(ok([]))
     mocks.searchFilesStream.mockResolvedValue(ok([]))
+    mocks.cancelSearch.mockResolvedValue(ok(true))
     mocks.searchByName.mockResolvedValue(ok([]))
     mocks.searchContent.mockResolvedValue(ok([]))
     mocks.getFilePreview.mockResolvedValue(ok({ kind: "text", content: "x" }))
@@ -93,8 +95,9 @@ describe("tauriClient coverage", () => {
       tauriClient.searchFiles(searchOptions({ search_path: "/", query: "q" })),
     ).resolves.toEqual([])
     await expect(
-      tauriClient.searchFilesStream(searchOptions({ search_path: "/", query: "q" })),
+      tauriClient.searchFilesStream(searchOptions({ search_path: "/", query: "q" }), "search-1"),
     ).resolves.toEqual([])
+    await expect(tauriClient.cancelSearch("search-1")).resolves.toBe(true)
     await expect(tauriClient.searchByName("/", "q", 10)).resolves.toEqual([])
     await expect(tauriClient.searchContent("/", "q", null, 10)).resolves.toEqual([])
     await expect(tauriClient.getFilePreview("/f")).resolves.toEqual({ kind: "text", content: "x" })
@@ -163,10 +163,24 @@ async searchFiles(options: SearchOptions) : Promise<Result<SearchResult[], strin
 },
 /**
  * Streaming search with progress events.
+ * 
+ * Starting a stream cancels the previous search from the same window. Every emitted
+ * event carries `search_id` so the frontend can drop results of superseded searches.
  */
-async searchFilesStream(options: SearchOptions) : Promise<Result<SearchResult[], string>> {
+async searchFilesStream(options: SearchOptions, searchId: string) : Promise<Result<SearchResult[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("search_files_stream", { options }) };
+    return { status: "ok", data: await TAURI_INVOKE("search_files_stream", { options, searchId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cancels a streamed search. Returns `false` if it already finished.
+ */
+async cancelSearch(searchId: string) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_search", { searchId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -194,6 +208,204 @@ async searchContent(searchPath: string, query: string, extensions: string[] | nu
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Parses a query-language string into search options based on `base`.
+ * 
+ * The returned options keep the raw text in `query` and carry the parsed
+ * `expression`, which takes precedence when searching.
+ */
+async parseSearchQuery(input: string, base: SearchOptions) : Promise<Result<SearchOptions, QueryError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("parse_search_query", { input, base }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fuzzy filename search ranked by score, with highlight indices.
+ */
+async fuzzySearch(options: FuzzySearchOptions) : Promise<Result<FuzzyMatch[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fuzzy_search", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replaces matches of a search across files.
+ * 
+ * In dry-run mode nothing is written and each file reports a diff; otherwise
+ * files are rewritten atomically and the result carries an `undo_id`.
+ */
+async replaceInFiles(options: ReplaceOptions) : Promise<Result<ReplaceReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("replace_in_files", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Restores the files changed by `replace_in_files`, unless they were edited since.
+ */
+async undoReplace(undoId: string) : Promise<Result<ReplaceUndoReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("undo_replace", { undoId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Lists saved searches by name.
+ */
+async listSavedSearches() : Promise<Result<SavedSearch[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_saved_searches") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Saves a new search, or replaces the name and options of the one with `id`.
+ */
+async saveSearch(name: string, options: SearchOptions, id: string | null) : Promise<Result<SavedSearch, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_search", { name, options, id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Deletes a saved search. Returns `false` if there was none with `id`.
+ */
+async deleteSavedSearch(id: string) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_saved_search", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Runs a saved search and returns its hits as a directory listing.
+ */
+async openSavedSearch(id: string) : Promise<Result<FileEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_saved_search", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Opens a saved search and keeps it live until `unwatch_saved_search`.
+ * 
+ * Returns the current listing; later listings arrive as `saved-search-updated` events.
+ */
+async watchSavedSearch(id: string) : Promise<Result<FileEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("watch_saved_search", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stops live updates for a saved search. Returns `false` if it wasn't watched.
+ */
+async unwatchSavedSearch(id: string) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unwatch_saved_search", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Adds a directory to the index and starts scanning it in the background.
+ */
+async addIndexRoot(path: string) : Promise<Result<IndexRootStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_index_root", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Removes a directory and its entries from the index. Returns `false` if it wasn't indexed.
+ */
+async removeIndexRoot(path: string) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_index_root", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns the state of every indexed root.
+ */
+async getIndexStatus() : Promise<Result<IndexStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_index_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Discards and rescans one indexed root, or all of them when `path` is omitted.
+ */
+async rebuildIndex(path: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rebuild_index", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Locate-style search over indexed roots by name and metadata.
+ */
+async searchIndex(query: IndexQuery) : Promise<Result<FileEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_index", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Ranked full-text search over the contents of indexed files.
+ */
+async searchContentIndex(query: ContentQuery) : Promise<Result<ContentHit[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_content_index", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Lists the files most recently modified or created under `roots`, grouped by day.
+ * 
+ * `since` is a Unix timestamp and defaults to a week ago. Hidden files and
+ * cache directories are skipped unless `show_all` is set.
+ */
+async recentChanges(roots: string[], since: number | null, limit: number | null, showAll: boolean | null) : Promise<Result<RecentDay[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recent_changes", { roots, since, limit, showAll }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Generates a preview for a file.
  */
@@ -205,6 +417,17 @@ async getFilePreview(path: string) : Promise<Result<FilePreview, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Generates an image preview fitted into a viewport of `max_width` x `max_height`.
+ */
+async getImagePreview(path: string, maxWidth: number, maxHeight: number) : Promise<Result<FilePreview, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_image_preview", { path, maxWidth, maxHeight }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Generates a thumbnail (resized image) as base64 with given max side length.
  */
@@ -216,6 +439,67 @@ async getThumbnail(path: string, maxSide: number) : Promise<Result<Thumbnail, st
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Reads a window of whole lines from a text file of any size.
+ * 
+ * Supports paging by byte offset, going to a line and jumping to the end.
+ */
+async readTextRange(path: string, range: TextRangeRequest) : Promise<Result<TextChunk, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_text_range", { path, range }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reads raw bytes of any file as hex dump rows, for paging through binaries.
+ */
+async readBytesRange(path: string, offset: number, length: number) : Promise<Result<HexChunk, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_bytes_range", { path, offset, length }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Finds the offsets of a byte sequence or string in a file.
+ * 
+ * Pass the returned `next_offset` as `from` to continue past `limit` matches.
+ */
+async searchBytes(path: string, pattern: BytePattern, from: number | null, limit: number | null) : Promise<Result<ByteSearchResult, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_bytes", { path, pattern, from, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reads a page of rows from one sheet of a spreadsheet.
+ * 
+ * `row_offset` is a 0-based sheet row, as in `SpreadsheetSheet::row_offset`.
+ */
+async readSheetRange(path: string, sheet: string, rowOffset: number, rows: number) : Promise<Result<SheetRange, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_sheet_range", { path, sheet, rowOffset, rows }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reads the pixel format and EXIF/XMP metadata of an image.
+ */
+async getImageMetadata(path: string) : Promise<Result<ImageMetadata, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_image_metadata", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Starts watching a directory for filesystem changes.
  */
@@ -239,7 +523,7 @@ async unwatchDirectory(path: string) : Promise<Result<null, string>> {
 }
 },
 /**
- * Stops watching all directories. Called on app cleanup.
+ * Stops watching all directories and tailing all files. Called on app cleanup.
  */
 async unwatchAll() : Promise<Result<null, string>> {
     try {
@@ -248,6 +532,28 @@ async unwatchAll() : Promise<Result<null, string>> {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+/**
+ * Follows a file as it grows, emitting appended lines as `file-tail` events.
+ */
+async tailFile(path: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("tail_file", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stops tailing a file.
+ */
+async untailFile(path: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("untail_file", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
@@ -261,47 +567,638 @@ async unwatchAll() : Promise<Result<null, string>> {
 
 /** user-defined types **/
 
+/**
+ * What `search_bytes` looks for.
+ */
+export type BytePattern = 
+/**
+ * Hex byte values such as `"de ad be ef"`; `??` matches any byte.
+ */
+{ type: "Hex"; value: string } | 
+/**
+ * UTF-8 text, ignoring ASCII case unless `case_sensitive` is set.
+ */
+{ type: "Text"; value: string; case_sensitive?: boolean }
+/**
+ * Offsets where a byte pattern was found.
+ */
+export type ByteSearchResult = { offsets: number[]; pattern_length: number; 
+/**
+ * Where to resume when the match limit was reached; `None` once the file is exhausted.
+ */
+next_offset: number | null }
+/**
+ * The value of a spreadsheet cell, as stored (a cached result for formulas).
+ */
+export type CellValue = { type: "Empty" } | { type: "Text"; value: string } | { type: "Number"; value: number } | { type: "Bool"; value: boolean } | 
+/**
+ * ISO 8601 date and time, e.g. "2024-03-01T00:00:00".
+ */
+{ type: "Date"; value: string } | 
+/**
+ * ISO 8601 duration, or seconds formatted as one.
+ */
+{ type: "Duration"; value: string } | 
+/**
+ * Error code such as "#DIV/0!".
+ */
+{ type: "Error"; value: string }
+/**
+ * A ranked full-text hit.
+ */
+export type ContentHit = { path: string; name: string; score: number; 
+/**
+ * Excerpt around the best matching terms; empty when only the name matched.
+ */
+snippet: string; highlights: TextRange[] }
 /**
  * A content match within a file (line-based).
+ * 
+ * For documents, lines are counted over the extracted text and `location`
+ * says where in the document the line came from.
+ */
+export type ContentMatch = { line_number: number; line_content: string; match_start: number; match_end: number; location?: MatchLocation | null }
+/**
+ * A full-text query against indexed file contents.
+ */
+export type ContentQuery = { 
+/**
+ * Words to find; all must occur. Supports `"phrases"`, `-exclusions` and `OR`.
  */
-export type ContentMatch = { line_number: number; line_content: string; match_start: number; match_end: number }
+query: string; 
+/**
+ * Restrict results to these directories; defaults to all indexed roots.
+ */
+roots?: string[] | null; max_results: number | null }
+/**
+ * A footnote of a document.
+ */
+export type DocFootnote = { id: number; text: string }
+/**
+ * A downscaled picture embedded in a document.
+ */
+export type DocImage = { base64: string; mime: string; 
+/**
+ * Alternative text from the document, if any.
+ */
+description: string | null }
 /**
  * A paragraph extracted from a DOCX document.
  */
 export type DocParagraph = { text: string; 
 /**
- * Style hint: "heading1", "heading2", "heading3", "listItem", "normal"
+ * Style hint: "heading1", "heading2", "heading3", "listItem", "table", "normal"
+ */
+style: string; 
+/**
+ * `text` split by formatting; empty for formats without run information.
+ */
+runs?: DocRun[]; 
+/**
+ * Rows of cells when `style` is "table"; `text` then holds the cells
+ * separated by tabs and the rows by newlines.
+ */
+table?: DocTableCell[][] | null }
+/**
+ * A run of uniformly formatted text in a document paragraph.
+ */
+export type DocRun = { text: string; bold?: boolean; italic?: boolean; underline?: boolean; 
+/**
+ * Hyperlink target: a URL, or `#bookmark` for links within the document.
+ */
+link?: string | null; 
+/**
+ * Index into the document's `images` for an embedded picture.
+ */
+image?: number | null; 
+/**
+ * Id of the footnote this run refers to, listed in the document's `footnotes`.
+ */
+footnote?: number | null }
+/**
+ * A table cell; its paragraphs are joined with newlines.
  */
-style: string }
+export type DocTableCell = { text: string; runs?: DocRun[] }
 /**
  * Represents a drive/volume on the system.
  */
 export type DriveInfo = { name: string; path: string; total_space: number; free_space: number; drive_type: string }
+/**
+ * Kind of filesystem entry for search filtering.
+ */
+export type EntryKind = "File" | "Directory" | "Symlink"
 /**
  * Represents a file or directory entry in the filesystem.
  */
 export type FileEntry = { name: string; path: string; is_dir: boolean; is_hidden: boolean; size: number; modified: number | null; created: number | null; extension: string | null }
-export type FilePreview = { type: "Text"; content: string; truncated: boolean } | { type: "Image"; base64: string; mime: string } | { type: "Document"; paragraphs: DocParagraph[]; truncated: boolean } | { type: "Spreadsheet"; sheets: SpreadsheetSheet[] } | { type: "Presentation"; slides: PresentationSlide[] } | { type: "Unsupported"; mime: string }
+export type FilePreview = { type: "Text"; content: string; truncated: boolean; language?: string | null; highlighted?: HighlightToken[][] | null } | { type: "Image"; base64: string; mime: string } | { type: "Document"; paragraphs: DocParagraph[]; truncated: boolean; footnotes?: DocFootnote[]; images?: DocImage[] } | { type: "Spreadsheet"; sheets: SpreadsheetSheet[] } | { type: "Presentation"; slides: PresentationSlide[] } | { type: "Pdf"; page_count: number; title: string | null; author: string | null; producer: string | null; pages: PdfPage[]; outline: PdfOutlineItem[]; truncated: boolean } | 
+/**
+ * Hex dump of the start of a binary file; `read_bytes_range` pages further.
+ */
+{ type: "Hex"; mime: string; file_size: number; rows: HexRow[] } | { type: "Unsupported"; mime: string }
+/**
+ * Changes made (or planned) in one file.
+ */
+export type FileReplacement = { path: string; 
+/**
+ * Fingerprint of the content the matches were found in.
+ */
+version: string; matches: ReplaceMatch[]; 
+/**
+ * Unified diff of the selected replacements.
+ */
+diff: string; 
+/**
+ * Number of matches replaced (or to be replaced in a dry run).
+ */
+replaced: number; 
+/**
+ * Set when the file could not be written; nothing was changed in it.
+ */
+error: string | null }
+/**
+ * A ranked fuzzy filename match.
+ */
+export type FuzzyMatch = { path: string; name: string; 
+/**
+ * Path relative to the search root; `indices` point into it.
+ */
+relative_path: string; is_dir: boolean; score: number; 
+/**
+ * Sorted UTF-16 offsets of matched characters in `relative_path`.
+ */
+indices: number[] }
+/**
+ * Options for fuzzy filename search.
+ */
+export type FuzzySearchOptions = { query: string; search_path: string; max_results: number | null; 
+/**
+ * Recently used paths, most recent first; matches among them rank higher.
+ */
+recent_paths?: string[] | null; 
+/**
+ * Skip entries ignored by `.gitignore`, `.ignore` and global git excludes.
+ */
+respect_gitignore?: boolean }
+/**
+ * Where a photo was taken, in decimal degrees.
+ */
+export type GpsPosition = { latitude: number; longitude: number; 
+/**
+ * Metres above sea level.
+ */
+altitude: number | null }
+/**
+ * A window of raw bytes rendered as hex dump rows.
+ */
+export type HexChunk = { 
+/**
+ * Offset of the first row, aligned down to a whole row.
+ */
+offset: number; rows: HexRow[]; file_size: number }
+/**
+ * One row of a hex dump.
+ */
+export type HexRow = { offset: number; 
+/**
+ * Space-separated byte values, e.g. `"89 50 4e 47"`.
+ */
+hex: string; 
+/**
+ * The same bytes as ASCII, with `.` for anything unprintable.
+ */
+ascii: string }
+/**
+ * A run of highlighted text sharing one syntax scope.
+ */
+export type HighlightToken = { text: string; 
+/**
+ * Scope atoms of the innermost scope, e.g. `"string quoted double rust"`; empty for plain text.
+ */
+class: string }
+/**
+ * Pixel format and EXIF/XMP metadata of an image file.
+ */
+export type ImageMetadata = { 
+/**
+ * Format detected from the content, e.g. `"Jpeg"`.
+ */
+format: string; 
+/**
+ * Displayed size, with the sides swapped for rotated orientations.
+ */
+width: number; height: number; 
+/**
+ * e.g. `"Rgb8"`; `None` when the header couldn't be read.
+ */
+color_type: string | null; 
+/**
+ * Bits per channel.
+ */
+bit_depth: number | null; has_alpha: boolean; 
+/**
+ * EXIF orientation, 1 (upright) to 8.
+ */
+orientation: number | null; camera_make: string | null; camera_model: string | null; lens: string | null; 
+/**
+ * Exposure time as cameras show it, e.g. `"1/250"`.
+ */
+exposure_time: string | null; f_number: number | null; iso: number | null; 
+/**
+ * Focal length in millimetres.
+ */
+focal_length: number | null; 
+/**
+ * Capture time as `YYYY-MM-DDTHH:MM:SS`, plus the UTC offset when recorded.
+ */
+captured_at: string | null; gps: GpsPosition | null; 
+/**
+ * XMP star rating; -1 marks a rejected photo.
+ */
+rating: number | null; title: string | null; description: string | null; creator: string | null; keywords: string[] }
+/**
+ * A name and metadata query against the file index.
+ */
+export type IndexQuery = { 
+/**
+ * Case-insensitive substring of the file name; empty matches everything.
+ */
+query: string; 
+/**
+ * Restrict results to these directories; defaults to all indexed roots.
+ */
+roots?: string[] | null; filters?: SearchFilters | null; max_results: number | null }
+/**
+ * Indexing state of a root.
+ */
+export type IndexRootState = 
+/**
+ * Waiting for its first scan.
+ */
+{ type: "Pending" } | 
+/**
+ * A full scan is running.
+ */
+{ type: "Scanning"; scanned: number } | 
+/**
+ * Scanned and kept fresh by the watcher.
+ */
+{ type: "Ready" } | 
+/**
+ * The last scan or watcher setup failed.
+ */
+{ type: "Error"; message: string }
+/**
+ * Status of one indexed root.
+ */
+export type IndexRootStatus = { path: string; state: IndexRootState; entries: number; 
+/**
+ * Unix timestamp of the last completed full scan.
+ */
+last_scan: number | null; 
+/**
+ * Whether a recursive watcher is keeping the root fresh.
+ */
+watching: boolean }
+/**
+ * Overall status of the file index.
+ */
+export type IndexStatus = { roots: IndexRootStatus[]; total_entries: number; 
+/**
+ * Number of files in the full-text content index.
+ */
+content_documents: number; 
+/**
+ * Location of the database; `None` when the index only lives in memory.
+ */
+database_path: string | null }
+/**
+ * Position of a match inside a structured document.
+ */
+export type MatchLocation = 
+/**
+ * 1-based paragraph of a word processing document.
+ */
+{ type: "Paragraph"; index: number } | 
+/**
+ * Footnote of a word processing document, by its id in the document.
+ */
+{ type: "Footnote"; id: number } | 
+/**
+ * Spreadsheet cell, e.g. `sheet: "Budget", cell: "B4"`.
+ */
+{ type: "Cell"; sheet: string; cell: string } | 
+/**
+ * 1-based slide of a presentation.
+ */
+{ type: "Slide"; number: number } | 
+/**
+ * 1-based page of a PDF.
+ */
+{ type: "Page"; number: number }
+/**
+ * A block of merged cells, with inclusive 0-based sheet positions.
+ */
+export type MergedRange = { start_row: number; start_column: number; end_row: number; end_column: number }
+/**
+ * A bookmark in a PDF's outline.
+ */
+export type PdfOutlineItem = { title: string; 
+/**
+ * Nesting depth, starting at 1 for top-level entries.
+ */
+level: number; page: number }
+/**
+ * Text extracted from one page of a PDF.
+ */
+export type PdfPage = { 
+/**
+ * 1-based page number.
+ */
+number: number; text: string }
 /**
  * A slide extracted from a PPTX presentation.
  */
-export type PresentationSlide = { number: number; title: string | null; texts: string[] }
+export type PresentationSlide = { 
+/**
+ * 1-based position in the presentation's slide order.
+ */
+number: number; title: string | null; texts: string[]; 
+/**
+ * Speaker notes.
+ */
+notes?: string | null; 
+/**
+ * Cell text of each table on the slide, by row.
+ */
+tables?: string[][][]; 
+/**
+ * Pictures on the slide, shrunk to `SLIDE_IMAGE_MAX_SIDE`.
+ */
+images?: DocImage[] }
+/**
+ * A query syntax error with the span to underline.
+ * 
+ * Offsets are UTF-16 code units, matching JavaScript string indices.
+ */
+export type QueryError = { message: string; start: number; end: number }
+/**
+ * A boolean search expression, usually produced by `parse_search_query`.
+ */
+export type QueryExpr = { type: "And"; items: QueryExpr[] } | { type: "Or"; items: QueryExpr[] } | { type: "Not"; item: QueryExpr } | 
+/**
+ * Name contains `value`, or file content does when `search_content` is set.
+ */
+{ type: "Text"; value: string } | 
+/**
+ * File content contains `value`, regardless of `search_content`.
+ */
+{ type: "Content"; value: string } | 
+/**
+ * File name matches a glob pattern.
+ */
+{ type: "Name"; pattern: string } | 
+/**
+ * Path relative to the search root contains `pattern`, or matches it when it is a glob.
+ */
+{ type: "Path"; pattern: string } | 
+/**
+ * File extension equals `value` (case-insensitive, without the dot).
+ */
+{ type: "Extension"; value: string } | { type: "Filter"; filters: SearchFilters }
+/**
+ * Files changed on one day, newest first.
+ */
+export type RecentDay = { 
+/**
+ * Local calendar date, `YYYY-MM-DD`.
+ */
+date: string; entries: FileEntry[] }
+/**
+ * A single match and what it becomes.
+ */
+export type ReplaceMatch = { index: number; line_number: number; line_content: string; matched: string; replacement: string }
+/**
+ * Options for replacing search matches across files.
+ */
+export type ReplaceOptions = { 
+/**
+ * Which files to touch and what to find; `search.query` is the pattern.
+ * 
+ * `search.search_content` must be set, and `search.expression` must not be.
+ */
+search: SearchOptions; 
+/**
+ * Replacement text. With `regex`, `$1` and `${name}` insert capture groups.
+ */
+replacement: string; 
+/**
+ * Treat the query as a regular expression instead of literal text.
+ */
+regex?: boolean; 
+/**
+ * Only replace these matches; every match is replaced when omitted.
+ */
+selection?: ReplaceSelection[] | null; 
+/**
+ * Compute the changes without writing anything.
+ */
+dry_run?: boolean }
+/**
+ * Outcome of `replace_in_files`.
+ */
+export type ReplaceReport = { files: FileReplacement[]; total_replaced: number; 
+/**
+ * Pass to `undo_replace` to restore the original contents; `None` for dry runs.
+ */
+undo_id: string | null; 
+/**
+ * More files matched than `search.max_results`; only the first ones by path
+ * were included.
+ */
+truncated: boolean }
+/**
+ * Matches chosen in one file, by their `index` from a dry run.
+ */
+export type ReplaceSelection = { path: string; indices: number[]; 
+/**
+ * `version` from the dry run; the file is skipped if it changed since.
+ */
+version?: string | null }
+/**
+ * Outcome of `undo_replace`.
+ */
+export type ReplaceUndoReport = { restored: string[]; 
+/**
+ * Files left alone because they were modified after the replacement.
+ */
+conflicts: string[] }
+/**
+ * A named search that can be opened like a directory.
+ */
+export type SavedSearch = { id: string; name: string; options: SearchOptions; 
+/**
+ * Unix timestamps in seconds.
+ */
+created: number; updated: number }
+/**
+ * Metadata predicates for search. Every set field must match (logical AND).
+ */
+export type SearchFilters = { 
+/**
+ * Minimum file size in bytes (inclusive). Directories have size 0.
+ */
+min_size?: number | null; 
+/**
+ * Maximum file size in bytes (inclusive).
+ */
+max_size?: number | null; modified?: TimeRange | null; created?: TimeRange | null; kind?: EntryKind | null; 
+/**
+ * Zero-length files and directories without children.
+ */
+empty?: boolean | null; hidden?: boolean | null; 
+/**
+ * Executable files: any execute bit on Unix, executable extensions on Windows.
+ */
+executable?: boolean | null; 
+/**
+ * Owner user name or numeric uid (Unix only).
+ */
+owner?: string | null }
 /**
  * Options for file search operations.
  */
-export type SearchOptions = { query: string; search_path: string; search_content: boolean; case_sensitive: boolean; max_results: number | null; file_extensions: string[] | null }
+export type SearchOptions = { query: string; search_path: string; search_content: boolean; case_sensitive: boolean; max_results: number | null; file_extensions: string[] | null; 
+/**
+ * Skip entries ignored by `.gitignore`, `.ignore` and global git excludes.
+ */
+respect_gitignore?: boolean; 
+/**
+ * Glob patterns (gitignore syntax, relative to `search_path`) to leave out of the walk.
+ */
+exclude_globs?: string[] | null; 
+/**
+ * Maximum directory depth; defaults to `MAX_SEARCH_DEPTH`.
+ */
+max_depth?: number | null; 
+/**
+ * Descend into symlinked directories. Symlink loops are detected and skipped.
+ */
+follow_symlinks?: boolean; 
+/**
+ * Metadata predicates an entry must satisfy in addition to the query.
+ */
+filters?: SearchFilters | null; 
+/**
+ * Parsed query expression. When set, it replaces `query` for matching.
+ */
+expression?: QueryExpr | null }
 /**
  * A search result entry.
  */
 export type SearchResult = { path: string; name: string; is_dir: boolean; matches: ContentMatch[] }
 /**
- * A sheet extracted from an XLSX spreadsheet.
+ * Type information for a cell that isn't plain text.
+ * 
+ * Row and column are 0-based sheet positions.
+ */
+export type SheetCell = { row: number; column: number; value: CellValue; 
+/**
+ * Formula text without the leading `=`.
+ */
+formula: string | null; 
+/**
+ * Number format code, e.g. `"0.00%"`; only read from XLSX files.
+ */
+number_format: string | null }
+/**
+ * A page of rows read from one sheet.
+ */
+export type SheetRange = { rows: string[][]; 
+/**
+ * Sheet position of `rows[0][0]`.
+ */
+row_offset: number; column_offset: number; cells: SheetCell[]; 
+/**
+ * Merged ranges overlapping the returned rows.
+ */
+merged: MergedRange[]; 
+/**
+ * Sheet row after the last used one.
+ */
+end_row: number }
+/**
+ * A sheet extracted from a spreadsheet.
+ */
+export type SpreadsheetSheet = { name: string; 
+/**
+ * The first row when it looks like column titles; otherwise empty.
+ */
+headers: string[]; rows: string[][]; total_rows: number; truncated: boolean; 
+/**
+ * Sheet position of `rows[0][0]`, for paging with `read_sheet_range`.
+ */
+row_offset?: number; column_offset?: number; 
+/**
+ * Typed values of the cells in `headers` and `rows` that aren't plain text.
+ */
+cells?: SheetCell[]; merged?: MergedRange[]; hidden?: boolean }
+/**
+ * A window of whole lines read from a text file.
+ */
+export type TextChunk = { content: string; 
+/**
+ * Byte range of `content` in the file, end exclusive.
+ */
+start_offset: number; end_offset: number; 
+/**
+ * 1-based number of the first line, when the line index has reached it.
+ */
+first_line: number | null; line_count: number; file_size: number; 
+/**
+ * Number of lines in the file, once the line index has scanned all of it.
+ */
+total_lines: number | null }
+/**
+ * Highlighted span within a snippet, as UTF-16 offsets.
  */
-export type SpreadsheetSheet = { name: string; headers: string[]; rows: string[][]; total_rows: number; truncated: boolean }
+export type TextRange = { start: number; end: number }
+/**
+ * Which part of a text file `read_text_range` returns.
+ */
+export type TextRangeRequest = 
+/**
+ * Whole lines starting at or after byte `offset`, up to about `length` bytes.
+ */
+{ type: "Bytes"; offset: number; length: number } | 
+/**
+ * `count` lines starting at 1-based line `start` ("go to line").
+ */
+{ type: "Lines"; start: number; count: number } | 
+/**
+ * The last `count` lines ("jump to end").
+ */
+{ type: "End"; count: number }
 /**
  * File preview content types.
  */
 export type Thumbnail = { base64: string; mime: string; type: "Thumbnail" }
+/**
+ * A point in time, either absolute or relative to when the search starts.
+ */
+export type TimeBound = 
+/**
+ * Unix timestamp in seconds.
+ */
+{ type: "Absolute"; timestamp: number } | 
+/**
+ * Seconds before the search starts, e.g. `604800` for "7 days ago".
+ */
+{ type: "Relative"; seconds: number }
+/**
+ * An inclusive time range; either side may be open.
+ */
+export type TimeRange = { after: TimeBound | null; before: TimeBound | null }
 
 /** tauri-specta globals **/
 
@@ -73,8 +73,12 @@ export const tauriClient = {
     return unwrapResult(await commands.searchFiles(options))
   },
 
-  async searchFilesStream(options: SearchOptions): Promise<SearchResult[]> {
-    return unwrapResult(await commands.searchFilesStream(options))
+  async searchFilesStream(options: SearchOptions, searchId: string): Promise<SearchResult[]> {
+    return unwrapResult(await commands.searchFilesStream(options, searchId))
+  },
+
+  async cancelSearch(searchId: string): Promise<boolean> {
+    return unwrapResult(await commands.cancelSearch(searchId))
   },
 
   async searchByName(
@@ -8,14 +8,22 @@ export type FsChangeEvent = {
 }
 
 export type SearchProgressEvent = {
+  search_id: string
   scanned: number
   found: number
   current_path: string
 }
 
-export type SearchBatchEvent = SearchResult[]
+export type SearchBatchEvent = {
+  search_id: string
+  results: SearchResult[]
+}
 
-export type SearchCompleteEvent = number
+export type SearchCompleteEvent = {
+  search_id: string
+  found: number
+  cancelled: boolean
+}
 
 export type CopyProgressEvent = {
   current: number
@@ -34,6 +42,19 @@ export type DirectoryCompleteEvent = {
   request_id: string
 }
 
+export type FileTailEvent = {
+  path: string
+  lines: string[]
+  truncated: boolean
+  rotated: boolean
+  skipped_bytes: number
+}
+
+export type SavedSearchUpdatedEvent = {
+  id: string
+  entries: FileEntry[]
+}
+
 /**
  * Typed wrappers around Tauri events emitted by the Rust backend.
  *
@@ -69,4 +90,12 @@ export const tauriEvents = {
   copyProgress(cb: EventCallback<CopyProgressEvent>): Promise<UnlistenFn> {
     return listen<CopyProgressEvent>("copy-progress", cb)
   },
+
+  fileTail(cb: EventCallback<FileTailEvent>): Promise<UnlistenFn> {
+    return listen<FileTailEvent>("file-tail", cb)
+  },
+
+  savedSearchUpdated(cb: EventCallback<SavedSearchUpdatedEvent>): Promise<UnlistenFn> {
+    return listen<SavedSearchUpdatedEvent>("saved-search-updated", cb)
+  },
 } as const